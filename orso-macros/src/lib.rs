@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
     parse_macro_input, punctuated::Punctuated, token::Comma, Attribute, Data, DeriveInput, Fields,
     Lit,
@@ -16,80 +16,154 @@ pub fn orso_table(_args: TokenStream, input: TokenStream) -> TokenStream {
     input
 }
 
+// orso_index attribute (passthrough - read by the derive)
+#[proc_macro_attribute]
+pub fn orso_index(_args: TokenStream, input: TokenStream) -> TokenStream {
+    input
+}
+
 // Derive macro for Orso trait
-#[proc_macro_derive(Orso, attributes(orso_table, orso_column))]
+#[proc_macro_derive(Orso, attributes(orso_table, orso_column, orso_index))]
 pub fn derive_orso(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
 
-    // Extract table name from attributes or use default
-    let table_name =
-        extract_orso_table_name(&input.attrs).unwrap_or_else(|| name.to_string().to_lowercase());
+    // Extract table name and default order from attributes
+    let (table_options, table_name_error) = match parse_orso_table(&name, &input.attrs) {
+        Ok(options) => (options, None),
+        Err(error) => (TableOptions::default(), Some(error)),
+    };
+    let TableOptions {
+        table_name,
+        order_by,
+    } = table_options;
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
     // Extract field metadata
-    let (
+    let metadata = if let Data::Struct(data) = &input.data {
+        if let Fields::Named(fields) = &data.fields {
+            extract_field_metadata_original(&fields.named)
+        } else {
+            FieldMetadata::default()
+        }
+    } else {
+        FieldMetadata::default()
+    };
+    let FieldMetadata {
         field_names,
         column_definitions,
         field_types,
         nullable_flags,
         primary_key_field,
+        primary_key_type,
+        primary_key_uuid_blob,
         created_at_field,
         updated_at_field,
+        created_by_field,
+        updated_by_field,
+        schema_version_field,
         unique_fields,
-        compressed_fields, // New compression flags
-    ) = if let Data::Struct(data) = &input.data {
-        if let Fields::Named(fields) = &data.fields {
-            extract_field_metadata_original(&fields.named)
-        } else {
-            (
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                None,
-                None,
-                None,
-                vec![],
-                vec![],
-            )
-        }
-    } else {
-        (
-            vec![],
-            vec![],
-            vec![],
-            vec![],
-            None,
-            None,
-            None,
-            vec![],
-            vec![],
-        )
+        foreign_keys,
+        generated_fields,
+        empty_as_null_fields,
+        not_null_default_fields,
+        json_fields,
+        fts_fields,
+        default_now_fields,
+        touch_on_update_fields,
+        check_bool_fields,
+        compressed_fields,
+        compressed_float_fields,
+        compressed_time_fields,
+        compressed_text_fields,
+        compressed_text_flags,
+        wide_integer_fields,
+        known_columns,
+        checked_fields,
+        mut errors,
+    } = metadata;
+
+    let struct_fields: Vec<String> = match &input.data {
+        Data::Struct(data) => data
+            .fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref().map(|ident| ident.to_string()))
+            .collect(),
+        _ => Vec::new(),
     };
-
-    // Generate dynamic getters based on actual fields found
-    let primary_key_getter = if let Some(ref pk_field) = primary_key_field {
-        quote! {
-            match &self.#pk_field {
-                Some(pk) => Some(pk.to_string()),
-                None => None,
+    errors.extend(table_name_error);
+    // Compressed columns hold encoded bytes, so they can't order rows
+    let compressed_columns: Vec<String> = struct_fields
+        .iter()
+        .zip(compressed_fields.iter().zip(&compressed_text_flags))
+        .filter(|(_, (&compressed, &text))| compressed || text)
+        .map(|(field, _)| field.clone())
+        .collect();
+    let default_sort = match order_by
+        .map(|order_by| parse_order_by(&order_by, &struct_fields, &compressed_columns))
+    {
+        Some(Ok(sorts)) => {
+            let sorts = sorts.iter().map(|(column, descending)| {
+                let order = if *descending {
+                    quote! { orso::SortOrder::Desc }
+                } else {
+                    quote! { orso::SortOrder::Asc }
+                };
+                quote! { orso::Sort::new(#column, #order) }
+            });
+            quote! {
+                fn default_sort() -> Option<Vec<orso::Sort>> {
+                    Some(vec![#(#sorts),*])
+                }
             }
         }
-    } else {
-        quote! { None }
-    };
-
-    let primary_key_setter = if let Some(ref pk_field) = primary_key_field {
-        quote! {
-            if let Ok(parsed_id) = id.parse() {
-                self.#pk_field = Some(parsed_id);
-            }
+        Some(Err(error)) => {
+            errors.push(error);
+            quote! {}
         }
-    } else {
-        quote! { /* No primary key field found */ }
+        None => quote! {},
     };
+    let mut indexes = Vec::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("orso_index"))
+    {
+        match parse_orso_index(attr, &struct_fields) {
+            Ok(index) => indexes.push(index),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    if let Some(error) = errors.into_iter().reduce(|mut all, error| {
+        all.combine(error);
+        all
+    }) {
+        return error.to_compile_error().into();
+    }
+
+    // Key type and accessors, inferred from the primary-key field (Option<T> or T)
+    let (key_type, primary_key_getter, primary_key_setter) =
+        match (&primary_key_field, &primary_key_type) {
+            (Some(pk_field), Some(pk_type)) => match option_inner_type(pk_type) {
+                Some(inner) => (
+                    quote! { #inner },
+                    quote! { self.#pk_field.clone() },
+                    quote! { self.#pk_field = Some(key); },
+                ),
+                None => (
+                    quote! { #pk_type },
+                    quote! { Some(self.#pk_field.clone()) },
+                    quote! { self.#pk_field = key; },
+                ),
+            },
+            _ => (
+                quote! { String },
+                quote! { None },
+                quote! { let _ = key; /* No primary key field found */ },
+            ),
+        };
 
     let created_at_getter = if let Some(ref ca_field) = created_at_field {
         quote! { self.#ca_field }
@@ -103,6 +177,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
         quote! { None }
     };
 
+    // as_new clears the key (when it's an Option) and both timestamps so insert regenerates them
+    let primary_key_clear = match (&primary_key_field, &primary_key_type) {
+        (Some(pk_field), Some(pk_type)) if option_inner_type(pk_type).is_some() => {
+            quote! { record.#pk_field = None; }
+        }
+        _ => quote! {},
+    };
+    let timestamp_clears: Vec<_> = created_at_field
+        .iter()
+        .chain(updated_at_field.iter())
+        .map(|field| quote! { record.#field = None; })
+        .collect();
+
     let updated_at_setter = if let Some(ref ua_field) = updated_at_field {
         quote! { self.#ua_field = Some(updated_at); }
     } else {
@@ -128,18 +215,365 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
         quote! { None }
     };
 
+    let created_by_field_name = if let Some(ref cb_field) = created_by_field {
+        quote! { Some(stringify!(#cb_field)) }
+    } else {
+        quote! { None }
+    };
+
+    let updated_by_field_name = if let Some(ref ub_field) = updated_by_field {
+        quote! { Some(stringify!(#ub_field)) }
+    } else {
+        quote! { None }
+    };
+
+    let schema_version_field_name = if let Some(ref sv_field) = schema_version_field {
+        quote! { Some(stringify!(#sv_field)) }
+    } else {
+        quote! { None }
+    };
+
     // Generate unique fields list
     let unique_field_names: Vec<proc_macro2::TokenStream> = unique_fields
         .iter()
         .map(|field| quote! { stringify!(#field) })
         .collect();
 
+    let empty_as_null_names: Vec<proc_macro2::TokenStream> = empty_as_null_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let not_null_default_names: Vec<proc_macro2::TokenStream> = not_null_default_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let json_names: Vec<proc_macro2::TokenStream> = json_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let foreign_key_entries: Vec<proc_macro2::TokenStream> = foreign_keys
+        .iter()
+        .map(|(field, table)| quote! { (stringify!(#field), #table) })
+        .collect();
+
+    // fetch_<name> for each ref column, e.g. fetch_user for user_id
+    let fetch_ref_methods: Vec<proc_macro2::TokenStream> = foreign_keys
+        .iter()
+        .map(|(field, table)| {
+            let field_str = field.to_string();
+            let base = field_str.strip_suffix("_id").unwrap_or(&field_str);
+            let method = format_ident!("fetch_{}", base);
+            let doc = format!("Load the `{}` row that `{}` refers to", table, field_str);
+            quote! {
+                #[doc = #doc]
+                pub async fn #method<P: orso::Orso>(
+                    &self,
+                    db: &orso::Database,
+                ) -> orso::Result<Option<P>> {
+                    <Self as orso::Orso>::fetch_ref::<P>(self, stringify!(#field), db).await
+                }
+            }
+        })
+        .collect();
+
+    let fts_names: Vec<proc_macro2::TokenStream> = fts_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let default_now_names: Vec<proc_macro2::TokenStream> = default_now_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let touch_on_update_names: Vec<proc_macro2::TokenStream> = touch_on_update_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let check_bool_names: Vec<proc_macro2::TokenStream> = check_bool_fields
+        .iter()
+        .map(|field| quote! { stringify!(#field) })
+        .collect();
+
+    let index_entries: Vec<proc_macro2::TokenStream> = indexes
+        .iter()
+        .map(|index| {
+            let columns = &index.columns;
+            let descending = index.descending;
+            let unique = index.unique;
+            let predicate = match &index.predicate {
+                Some(predicate) => quote! { Some(#predicate) },
+                None => quote! { None },
+            };
+            let name = match &index.name {
+                Some(name) => quote! { Some(#name) },
+                None => quote! { None },
+            };
+            quote! {
+                orso::TableIndex {
+                    columns: vec![#(#columns),*],
+                    descending: #descending,
+                    unique: #unique,
+                    predicate: #predicate,
+                    name: #name,
+                }
+            }
+        })
+        .collect();
+
+    // Generate generated-column list
+    let generated_field_entries: Vec<proc_macro2::TokenStream> = generated_fields
+        .iter()
+        .map(|(field, expression, stored)| {
+            quote! {
+                (stringify!(#field), orso::GeneratedColumn {
+                    expression: #expression.to_string(),
+                    stored: #stored,
+                })
+            }
+        })
+        .collect();
+
     // Generate compressed fields list
     let compressed_field_flags: Vec<proc_macro2::TokenStream> = compressed_fields
         .iter()
         .map(|&is_compressed| quote! { #is_compressed })
         .collect();
 
+    // Compressed float and wide integer fields bypass the JSON round-trip in to_map/from_map
+    let has_direct_fields = !compressed_float_fields.is_empty() || !wide_integer_fields.is_empty();
+    let direct_mut = if has_direct_fields {
+        quote! { mut }
+    } else {
+        quote! {}
+    };
+    let float_to_map: Vec<proc_macro2::TokenStream> = compressed_float_fields
+        .iter()
+        .map(|(field, element)| {
+            let compress_fn = format_ident!("compress_{}", element);
+            quote! {
+                if map.remove(stringify!(#field)).is_some() {
+                    let blob = orso::FloatingCodec::default()
+                        .#compress_fn(&self.#field, None)
+                        .map_err(|e| orso::Error::Serialization(format!(
+                            "Failed to compress {}: {}", stringify!(#field), e
+                        )))?;
+                    result.insert(std::borrow::Cow::Borrowed(stringify!(#field)), orso::Value::Blob(blob));
+                }
+            }
+        })
+        .collect();
+    let float_from_map: Vec<proc_macro2::TokenStream> = compressed_float_fields
+        .iter()
+        .map(|(field, element)| {
+            let decompress_fn = format_ident!("decompress_{}", element);
+            let decoded = format_ident!("__orso_decoded_{}", field);
+            quote! {
+                let #decoded: Vec<#element> = match map.remove(stringify!(#field)) {
+                    // The codec encodes an empty vector as an empty blob
+                    Some(orso::Value::Blob(blob)) if blob.is_empty() => Vec::new(),
+                    Some(orso::Value::Blob(blob)) if blob.starts_with(b"ORSO") => {
+                        orso::FloatingCodec::default()
+                            .#decompress_fn(&blob, None)
+                            .map_err(|e| orso::Error::Serialization(format!(
+                                "Failed to decompress {}: {}", stringify!(#field), e
+                            )))?
+                    }
+                    // Legacy uncompressed JSON, stored as a blob or as text
+                    Some(orso::Value::Blob(blob)) => serde_json::from_slice(&blob)?,
+                    Some(orso::Value::Text(text)) => serde_json::from_str(&text)?,
+                    _ => Vec::new(),
+                };
+                // Placeholder so the struct deserializes; the real values are assigned after
+                json_map.insert(stringify!(#field).to_string(), serde_json::Value::Array(Vec::new()));
+            }
+        })
+        .collect();
+    // Compressed timestamp vectors go through the integer codec as epoch milliseconds
+    let time_to_map: Vec<proc_macro2::TokenStream> = compressed_time_fields
+        .iter()
+        .map(|field| {
+            quote! {
+                if map.remove(stringify!(#field)).is_some() {
+                    let millis: Vec<i64> =
+                        self.#field.iter().map(|time| time.timestamp_millis()).collect();
+                    let blob = orso::IntegerCodec::default()
+                        .compress_i64(&millis)
+                        .map_err(|e| orso::Error::Serialization(format!(
+                            "Failed to compress {}: {}", stringify!(#field), e
+                        )))?;
+                    result.insert(std::borrow::Cow::Borrowed(stringify!(#field)), orso::Value::Blob(blob));
+                }
+            }
+        })
+        .collect();
+    let time_from_map: Vec<proc_macro2::TokenStream> = compressed_time_fields
+        .iter()
+        .map(|field| {
+            quote! {
+                let times = match map.remove(stringify!(#field)) {
+                    Some(orso::Value::Blob(blob)) if blob.is_empty() => serde_json::Value::Array(Vec::new()),
+                    Some(orso::Value::Blob(blob)) if blob.starts_with(b"ORSO") => {
+                        let millis = orso::IntegerCodec::default()
+                            .decompress_i64(&blob)
+                            .map_err(|e| orso::Error::Serialization(format!(
+                                "Failed to decompress {}: {}", stringify!(#field), e
+                            )))?;
+                        let mut times = Vec::with_capacity(millis.len());
+                        for ms in millis {
+                            let time = orso::chrono::DateTime::<orso::chrono::Utc>::from_timestamp_millis(ms)
+                                .ok_or_else(|| orso::Error::Serialization(format!(
+                                    "Timestamp {} out of range in {}", ms, stringify!(#field)
+                                )))?;
+                            times.push(serde_json::to_value(time)?);
+                        }
+                        serde_json::Value::Array(times)
+                    }
+                    // Legacy uncompressed JSON, stored as a blob or as text
+                    Some(orso::Value::Blob(blob)) => serde_json::from_slice(&blob)?,
+                    Some(orso::Value::Text(text)) => serde_json::from_str(&text)?,
+                    _ => serde_json::Value::Array(Vec::new()),
+                };
+                json_map.insert(stringify!(#field).to_string(), times);
+            }
+        })
+        .collect();
+    // compress_text fields: the string's UTF-8 bytes, compressed into a BLOB
+    let text_to_map: Vec<proc_macro2::TokenStream> = compressed_text_fields
+        .iter()
+        .map(|field| {
+            quote! {
+                if map.remove(stringify!(#field)).is_some() {
+                    let blob = orso::Utils::compress_text(&self.#field)?;
+                    result.insert(std::borrow::Cow::Borrowed(stringify!(#field)), orso::Value::Blob(blob));
+                }
+            }
+        })
+        .collect();
+    let text_from_map: Vec<proc_macro2::TokenStream> = compressed_text_fields
+        .iter()
+        .map(|field| {
+            quote! {
+                let text = match map.remove(stringify!(#field)) {
+                    Some(orso::Value::Blob(blob)) => orso::Utils::decompress_text(&blob)?,
+                    // Written before the column was compressed
+                    Some(orso::Value::Text(text)) => text,
+                    _ => String::new(),
+                };
+                json_map.insert(stringify!(#field).to_string(), serde_json::Value::String(text));
+            }
+        })
+        .collect();
+    let float_assign: Vec<proc_macro2::TokenStream> = compressed_float_fields
+        .iter()
+        .map(|(field, _)| {
+            let decoded = format_ident!("__orso_decoded_{}", field);
+            quote! { result.#field = #decoded; }
+        })
+        .collect();
+    // i128/u128 fields: serde_json can't hold values beyond 64 bits, so the struct is
+    // serialized with them zeroed and their exact decimal text is stored instead
+    let serialized = if wide_integer_fields.is_empty() {
+        quote! { self }
+    } else {
+        let resets = wide_integer_fields.iter().map(|(field, _, _)| {
+            quote! { __orso_shadow.#field = Default::default(); }
+        });
+        quote! {
+            &{
+                let mut __orso_shadow = self.clone();
+                #(#resets)*
+                __orso_shadow
+            }
+        }
+    };
+    let wide_to_map: Vec<proc_macro2::TokenStream> = wide_integer_fields
+        .iter()
+        .map(|(field, _, optional)| {
+            // None stays in the map as null, like any other Option
+            let value = if *optional {
+                quote! { self.#field.as_ref() }
+            } else {
+                quote! { Some(&self.#field) }
+            };
+            quote! {
+                if let Some(value) = #value {
+                    if map.remove(stringify!(#field)).is_some() {
+                        result.insert(
+                            std::borrow::Cow::Borrowed(stringify!(#field)),
+                            orso::Value::Text(value.to_string()),
+                        );
+                    }
+                }
+            }
+        })
+        .collect();
+    let wide_from_map: Vec<proc_macro2::TokenStream> = wide_integer_fields
+        .iter()
+        .map(|(field, integer, optional)| {
+            let decoded = format_ident!("__orso_decoded_{}", field);
+            let (decoded_value, placeholder) = if *optional {
+                (quote! { parsed }, quote! { serde_json::Value::Null })
+            } else {
+                (
+                    quote! {
+                        parsed.ok_or_else(|| orso::Error::Serialization(format!(
+                            "missing field `{}`", stringify!(#field)
+                        )))?
+                    },
+                    quote! { serde_json::Value::from(0) },
+                )
+            };
+            quote! {
+                let parsed = orso::as_text::from_column::<#integer>(
+                    stringify!(#field),
+                    map.remove(stringify!(#field)),
+                )?;
+                let #decoded = #decoded_value;
+                // Placeholder so the struct deserializes; the real value is assigned after
+                json_map.insert(stringify!(#field).to_string(), #placeholder);
+            }
+        })
+        .collect();
+    let wide_assign: Vec<proc_macro2::TokenStream> = wide_integer_fields
+        .iter()
+        .map(|(field, _, _)| {
+            let decoded = format_ident!("__orso_decoded_{}", field);
+            quote! { result.#field = #decoded; }
+        })
+        .collect();
+
+    // Extra table columns (e.g. ones a migration preserved) never reach serde, so they
+    // can't trip deny_unknown_fields or get decoded for nothing
+    let drop_unknown_columns = known_columns.map(|known| {
+        quote! {
+            let known_columns: &[&str] = &[#(#known),*];
+            map.retain(|column, _| known_columns.contains(&column.as_str()));
+        }
+    });
+
+    // On a failed from_map, find the first column whose value doesn't fit its field
+    let field_checks: Vec<proc_macro2::TokenStream> = checked_fields
+        .iter()
+        .map(|(field, ty)| {
+            quote! {
+                if let Some(json) = json_value.get(stringify!(#field)) {
+                    if let Err(field_err) = <#ty as orso::Deserialize>::deserialize(json) {
+                        return Err(orso::Error::FieldDeserialization {
+                            field: stringify!(#field).to_string(),
+                            value: map.get(stringify!(#field)).cloned().unwrap_or(orso::Value::Null),
+                            message: field_err.to_string(),
+                        });
+                    }
+                }
+            }
+        })
+        .collect();
+
     // Generate only the trait implementation
     let expanded = quote! {
         impl #impl_generics orso::Orso for #name #ty_generics #where_clause {
@@ -159,18 +593,73 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                 #updated_at_field_name
             }
 
+            fn created_by_field() -> Option<&'static str> {
+                #created_by_field_name
+            }
+
+            fn updated_by_field() -> Option<&'static str> {
+                #updated_by_field_name
+            }
+
+            fn schema_version_field() -> Option<&'static str> {
+                #schema_version_field_name
+            }
+
             fn unique_fields() -> Vec<&'static str> {
                 vec![#(#unique_field_names),*]
             }
 
-            fn get_primary_key(&self) -> Option<String> {
+            fn foreign_keys() -> Vec<(&'static str, &'static str)> {
+                vec![#(#foreign_key_entries),*]
+            }
+
+            fn primary_key_uuid_blob() -> bool {
+                #primary_key_uuid_blob
+            }
+
+            fn fts_fields() -> Vec<&'static str> {
+                vec![#(#fts_names),*]
+            }
+
+            fn default_now_fields() -> Vec<&'static str> {
+                vec![#(#default_now_names),*]
+            }
+
+            fn touch_on_update_fields() -> Vec<&'static str> {
+                vec![#(#touch_on_update_names),*]
+            }
+
+            fn generated_fields() -> Vec<(&'static str, orso::GeneratedColumn)> {
+                vec![#(#generated_field_entries),*]
+            }
+
+            fn check_bool_fields() -> Vec<&'static str> {
+                vec![#(#check_bool_names),*]
+            }
+
+            fn indexes() -> Vec<orso::TableIndex> {
+                vec![#(#index_entries),*]
+            }
+
+            #default_sort
+
+            type Key = #key_type;
+
+            fn get_key(&self) -> Option<Self::Key> {
                 #primary_key_getter
             }
 
-            fn set_primary_key(&mut self, id: String) {
+            fn set_key(&mut self, key: Self::Key) {
                 #primary_key_setter
             }
 
+            fn as_new(&self) -> Self {
+                let mut record = self.clone();
+                #primary_key_clear
+                #(#timestamp_clears)*
+                record
+            }
+
             fn get_created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
                 #created_at_getter
             }
@@ -199,6 +688,10 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                 vec![#(#compressed_field_flags),*]
             }
 
+            fn field_compressed_text() -> Vec<bool> {
+                vec![#(#compressed_text_flags),*]
+            }
+
             fn columns() -> Vec<&'static str> {
                 vec![#(#field_names),*]
             }
@@ -214,39 +707,67 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                 )
             }
 
-            fn to_map(&self) -> orso::Result<std::collections::HashMap<String, orso::Value>> {
+            fn to_map_cow_only(
+                &self,
+                fields: Option<&[&str]>,
+            ) -> orso::Result<std::collections::HashMap<std::borrow::Cow<'static, str>, orso::Value>> {
                 use serde_json;
-                let json = serde_json::to_value(self)?;
-                let map: std::collections::HashMap<String, serde_json::Value> =
-                    serde_json::from_value(json)?;
+                use std::borrow::Cow;
+                // Take the serialized object as is rather than rebuilding it as a HashMap
+                let mut map = match serde_json::to_value(#serialized)? {
+                    serde_json::Value::Object(map) => map,
+                    other => {
+                        return Err(orso::Error::Serialization(format!(
+                            "Expected {} to serialize to an object, got {}",
+                            stringify!(#name), other
+                        )))
+                    }
+                };
+                if let Some(fields) = fields {
+                    map.retain(|k, _| fields.contains(&k.as_str()));
+                }
+
+                // Keys borrow the static field names; only unknown keys are copied
+                let field_names = Self::field_names();
+                let column_key = |key: &str| -> Cow<'static, str> {
+                    match field_names.iter().find(|name| **name == key) {
+                        Some(name) => Cow::Borrowed(*name),
+                        None => Cow::Owned(key.to_string()),
+                    }
+                };
+
+                let mut result = std::collections::HashMap::with_capacity(map.len());
 
-                let mut result = std::collections::HashMap::new();
+                #(#float_to_map)*
+                #(#time_to_map)*
+                #(#text_to_map)*
+                #(#wide_to_map)*
 
                 // Get field names for auto-generated fields
                 let pk_field = Self::primary_key_field();
-                let created_field = Self::created_at_field();
-                let updated_field = Self::updated_at_field();
+                let default_now_fields = Self::default_now_fields();
+                // None in a not_null Option field leaves the column to its DEFAULT
+                let not_null_defaults: &[&str] = &[#(#not_null_default_names),*];
 
                 // Get compression information
-                let field_names = Self::field_names();
                 let field_types = Self::field_types();
                 let compressed_flags = Self::field_compressed();
 
                 // Group compressed fields by type for batch processing
-                let mut compressed_i64_fields: std::collections::HashMap<String, Vec<i64>> = std::collections::HashMap::new();
-                let mut compressed_u64_fields: std::collections::HashMap<String, Vec<u64>> = std::collections::HashMap::new();
-                let mut compressed_i32_fields: std::collections::HashMap<String, Vec<i32>> = std::collections::HashMap::new();
-                let mut compressed_u32_fields: std::collections::HashMap<String, Vec<u32>> = std::collections::HashMap::new();
-                let mut compressed_f64_fields: std::collections::HashMap<String, Vec<f64>> = std::collections::HashMap::new();
-                let mut compressed_f32_fields: std::collections::HashMap<String, Vec<f32>> = std::collections::HashMap::new();
+                let mut compressed_i64_fields: std::collections::HashMap<Cow<'static, str>, Vec<i64>> = std::collections::HashMap::new();
+                let mut compressed_u64_fields: std::collections::HashMap<Cow<'static, str>, Vec<u64>> = std::collections::HashMap::new();
+                let mut compressed_i32_fields: std::collections::HashMap<Cow<'static, str>, Vec<i32>> = std::collections::HashMap::new();
+                let mut compressed_u32_fields: std::collections::HashMap<Cow<'static, str>, Vec<u32>> = std::collections::HashMap::new();
+                let mut compressed_f64_fields: std::collections::HashMap<Cow<'static, str>, Vec<f64>> = std::collections::HashMap::new();
+                let mut compressed_f32_fields: std::collections::HashMap<Cow<'static, str>, Vec<f32>> = std::collections::HashMap::new();
 
                 // First pass: collect compressed fields by type
                 for (k, v) in &map {
                     // Skip auto-generated fields when they are null - let SQLite use DEFAULT values
                     let should_skip = matches!(v, serde_json::Value::Null) && (
-                        *k == pk_field ||
-                        (created_field.is_some() && *k == created_field.unwrap()) ||
-                        (updated_field.is_some() && *k == updated_field.unwrap())
+                        *k == pk_field
+                            || default_now_fields.contains(&k.as_str())
+                            || not_null_defaults.contains(&k.as_str())
                     );
 
                     if should_skip {
@@ -278,7 +799,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                     if let Some(pos) = field_names.iter().position(|&name| name == *k) {
                                         if matches!(field_types.get(pos), Some(orso::FieldType::Numeric)) {
                                             // This is a floating-point field, collect for f64 compression
-                                            compressed_f64_fields.insert(k.clone(), vec);
+                                            compressed_f64_fields.insert(column_key(k), vec);
                                             continue; // Skip normal processing for this field
                                         }
                                     }
@@ -299,7 +820,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                     if let Some(pos) = field_names.iter().position(|&name| name == *k) {
                                         if matches!(field_types.get(pos), Some(orso::FieldType::Numeric)) {
                                             // This is a floating-point field, collect for f32 compression
-                                            compressed_f32_fields.insert(k.clone(), vec);
+                                            compressed_f32_fields.insert(column_key(k), vec);
                                             continue; // Skip normal processing for this field
                                         }
                                     }
@@ -316,7 +837,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                 }).collect();
 
                                 if let Ok(vec) = i64_result {
-                                    compressed_i64_fields.insert(k.clone(), vec);
+                                    compressed_i64_fields.insert(column_key(k), vec);
                                     continue; // Skip normal processing for this field
                                 }
 
@@ -331,7 +852,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                 }).collect();
 
                                 if let Ok(vec) = u64_result {
-                                    compressed_u64_fields.insert(k.clone(), vec);
+                                    compressed_u64_fields.insert(column_key(k), vec);
                                     continue; // Skip normal processing for this field
                                 }
 
@@ -346,7 +867,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                 }).collect();
 
                                 if let Ok(vec) = i32_result {
-                                    compressed_i32_fields.insert(k.clone(), vec);
+                                    compressed_i32_fields.insert(column_key(k), vec);
                                     continue; // Skip normal processing for this field
                                 }
 
@@ -361,7 +882,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                 }).collect();
 
                                 if let Ok(vec) = u32_result {
-                                    compressed_u32_fields.insert(k.clone(), vec);
+                                    compressed_u32_fields.insert(column_key(k), vec);
                                     continue; // Skip normal processing for this field
                                 }
                             }
@@ -383,19 +904,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             }
                             Err(_) => {
                                 // Fallback to JSON string
-                                if let Some(original_value) = map.get(&field_name) {
+                                if let Some(original_value) = map.get(field_name.as_ref()) {
                                     result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                 }
                             }
                         }
                     } else {
                         // Multiple fields - process in batch
-                        let field_names: Vec<String> = compressed_i64_fields.keys().cloned().collect();
+                        let batch_names: Vec<Cow<'static, str>> = compressed_i64_fields.keys().cloned().collect();
                         let arrays: Vec<Vec<i64>> = compressed_i64_fields.values().cloned().collect();
 
                         match codec.compress_many_i64(&arrays) {
                             Ok(compressed_blobs) => {
-                                for (field_name, blob) in field_names.into_iter().zip(compressed_blobs.into_iter()) {
+                                for (field_name, blob) in batch_names.into_iter().zip(compressed_blobs.into_iter()) {
                                     result.insert(field_name, orso::Value::Blob(blob));
                                 }
                             }
@@ -408,7 +929,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         }
                                         Err(_) => {
                                             // Ultimate fallback to JSON string
-                                            if let Some(original_value) = map.get(&field_name) {
+                                            if let Some(original_value) = map.get(field_name.as_ref()) {
                                                 result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                             }
                                         }
@@ -431,19 +952,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             }
                             Err(_) => {
                                 // Fallback to JSON string
-                                if let Some(original_value) = map.get(&field_name) {
+                                if let Some(original_value) = map.get(field_name.as_ref()) {
                                     result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                 }
                             }
                         }
                     } else {
                         // Multiple fields - process in batch
-                        let field_names: Vec<String> = compressed_u64_fields.keys().cloned().collect();
+                        let batch_names: Vec<Cow<'static, str>> = compressed_u64_fields.keys().cloned().collect();
                         let arrays: Vec<Vec<u64>> = compressed_u64_fields.values().cloned().collect();
 
                         match codec.compress_many_u64(&arrays) {
                             Ok(compressed_blobs) => {
-                                for (field_name, blob) in field_names.into_iter().zip(compressed_blobs.into_iter()) {
+                                for (field_name, blob) in batch_names.into_iter().zip(compressed_blobs.into_iter()) {
                                     result.insert(field_name, orso::Value::Blob(blob));
                                 }
                             }
@@ -456,7 +977,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         }
                                         Err(_) => {
                                             // Ultimate fallback to JSON string
-                                            if let Some(original_value) = map.get(&field_name) {
+                                            if let Some(original_value) = map.get(field_name.as_ref()) {
                                                 result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                             }
                                         }
@@ -480,19 +1001,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             }
                             Err(_) => {
                                 // Fallback to JSON string
-                                if let Some(original_value) = map.get(&field_name) {
+                                if let Some(original_value) = map.get(field_name.as_ref()) {
                                     result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                 }
                             }
                         }
                     } else {
                         // Multiple fields - process in batch
-                        let field_names: Vec<String> = compressed_i32_fields.keys().cloned().collect();
+                        let batch_names: Vec<Cow<'static, str>> = compressed_i32_fields.keys().cloned().collect();
                         let arrays: Vec<Vec<i64>> = compressed_i32_fields.values().map(|vec| vec.iter().map(|&x| x as i64).collect()).collect();
 
                         match codec.compress_many_i64(&arrays) {
                             Ok(compressed_blobs) => {
-                                for (field_name, blob) in field_names.into_iter().zip(compressed_blobs.into_iter()) {
+                                for (field_name, blob) in batch_names.into_iter().zip(compressed_blobs.into_iter()) {
                                     result.insert(field_name, orso::Value::Blob(blob));
                                 }
                             }
@@ -506,7 +1027,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         }
                                         Err(_) => {
                                             // Ultimate fallback to JSON string
-                                            if let Some(original_value) = map.get(&field_name) {
+                                            if let Some(original_value) = map.get(field_name.as_ref()) {
                                                 result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                             }
                                         }
@@ -530,19 +1051,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             }
                             Err(_) => {
                                 // Fallback to JSON string
-                                if let Some(original_value) = map.get(&field_name) {
+                                if let Some(original_value) = map.get(field_name.as_ref()) {
                                     result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                 }
                             }
                         }
                     } else {
                         // Multiple fields - process in batch
-                        let field_names: Vec<String> = compressed_u32_fields.keys().cloned().collect();
+                        let batch_names: Vec<Cow<'static, str>> = compressed_u32_fields.keys().cloned().collect();
                         let arrays: Vec<Vec<u64>> = compressed_u32_fields.values().map(|vec| vec.iter().map(|&x| x as u64).collect()).collect();
 
                         match codec.compress_many_u64(&arrays) {
                             Ok(compressed_blobs) => {
-                                for (field_name, blob) in field_names.into_iter().zip(compressed_blobs.into_iter()) {
+                                for (field_name, blob) in batch_names.into_iter().zip(compressed_blobs.into_iter()) {
                                     result.insert(field_name, orso::Value::Blob(blob));
                                 }
                             }
@@ -556,7 +1077,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         }
                                         Err(_) => {
                                             // Ultimate fallback to JSON string
-                                            if let Some(original_value) = map.get(&field_name) {
+                                            if let Some(original_value) = map.get(field_name.as_ref()) {
                                                 result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                             }
                                         }
@@ -579,19 +1100,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             }
                             Err(_) => {
                                 // Fallback to JSON string
-                                if let Some(original_value) = map.get(&field_name) {
+                                if let Some(original_value) = map.get(field_name.as_ref()) {
                                     result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                 }
                             }
                         }
                     } else {
                         // Multiple fields - process in batch
-                        let field_names: Vec<String> = compressed_f64_fields.keys().cloned().collect();
+                        let batch_names: Vec<Cow<'static, str>> = compressed_f64_fields.keys().cloned().collect();
                         let arrays: Vec<Vec<f64>> = compressed_f64_fields.values().cloned().collect();
 
                         match codec.compress_many_f64(&arrays, None) {
                             Ok(compressed_blobs) => {
-                                for (field_name, blob) in field_names.into_iter().zip(compressed_blobs.into_iter()) {
+                                for (field_name, blob) in batch_names.into_iter().zip(compressed_blobs.into_iter()) {
                                     result.insert(field_name, orso::Value::Blob(blob));
                                 }
                             }
@@ -604,7 +1125,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         }
                                         Err(_) => {
                                             // Ultimate fallback to JSON string
-                                            if let Some(original_value) = map.get(&field_name) {
+                                            if let Some(original_value) = map.get(field_name.as_ref()) {
                                                 result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                             }
                                         }
@@ -627,19 +1148,19 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             }
                             Err(_) => {
                                 // Fallback to JSON string
-                                if let Some(original_value) = map.get(&field_name) {
+                                if let Some(original_value) = map.get(field_name.as_ref()) {
                                     result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                 }
                             }
                         }
                     } else {
                         // Multiple fields - process in batch
-                        let field_names: Vec<String> = compressed_f32_fields.keys().cloned().collect();
+                        let batch_names: Vec<Cow<'static, str>> = compressed_f32_fields.keys().cloned().collect();
                         let arrays: Vec<Vec<f32>> = compressed_f32_fields.values().cloned().collect();
 
                         match codec.compress_many_f32(&arrays, None) {
                             Ok(compressed_blobs) => {
-                                for (field_name, blob) in field_names.into_iter().zip(compressed_blobs.into_iter()) {
+                                for (field_name, blob) in batch_names.into_iter().zip(compressed_blobs.into_iter()) {
                                     result.insert(field_name, orso::Value::Blob(blob));
                                 }
                             }
@@ -652,7 +1173,7 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         }
                                         Err(_) => {
                                             // Ultimate fallback to JSON string
-                                            if let Some(original_value) = map.get(&field_name) {
+                                            if let Some(original_value) = map.get(field_name.as_ref()) {
                                                 result.insert(field_name, orso::Value::Text(serde_json::to_string(original_value)?));
                                             }
                                         }
@@ -666,15 +1187,15 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                 // Second pass: process non-compressed fields and any fields that fell through
                 for (k, v) in map {
                     // Skip fields that were already processed as compressed
-                    if result.contains_key(&k) {
+                    if result.contains_key(k.as_str()) {
                         continue;
                     }
 
                     // Skip auto-generated fields when they are null - let SQLite use DEFAULT values
                     let should_skip = matches!(v, serde_json::Value::Null) && (
-                        k == pk_field ||
-                        (created_field.is_some() && k == created_field.unwrap()) ||
-                        (updated_field.is_some() && k == updated_field.unwrap())
+                        k == pk_field
+                            || default_now_fields.contains(&k.as_str())
+                            || not_null_defaults.contains(&k.as_str())
                     );
 
                     if should_skip {
@@ -697,7 +1218,39 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                         serde_json::Value::Array(_) => orso::Value::Text(serde_json::to_string(&v)?),
                         serde_json::Value::Object(_) => orso::Value::Text(serde_json::to_string(&v)?),
                     };
-                    result.insert(k, value);
+                    result.insert(column_key(&k), value);
+                }
+
+                // Generated columns are computed by SQLite and never written
+                for (name, _) in Self::generated_fields() {
+                    result.remove(name);
+                }
+
+                // Fields marked empty_as_null store Some("") as NULL
+                let empty_as_null: &[&str] = &[#(#empty_as_null_names),*];
+                for name in empty_as_null {
+                    if matches!(result.get(*name), Some(orso::Value::Text(text)) if text.is_empty()) {
+                        result.insert(Cow::Borrowed(*name), orso::Value::Null);
+                    }
+                }
+
+                // Every write stamps the model's current schema version
+                if let Some(field) = Self::schema_version_field() {
+                    let selected = match fields {
+                        Some(fields) => fields.contains(&field),
+                        None => true,
+                    };
+                    if selected {
+                        result.insert(Cow::Borrowed(field), orso::Value::Integer(Self::schema_version()));
+                    }
+                }
+
+                // uuid_blob keys are written as their 16 bytes
+                if Self::primary_key_uuid_blob() {
+                    if let Some(orso::Value::Text(text)) = result.get(pk_field) {
+                        let blob = orso::Utils::uuid_text_to_blob(text)?;
+                        result.insert(Cow::Borrowed(pk_field), orso::Value::Blob(blob));
+                    }
                 }
 
                 Ok(result)
@@ -707,10 +1260,29 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                 use serde_json;
                 let mut json_map = serde_json::Map::new();
 
+                // uuid_blob keys are read back in their hyphenated text form
+                if Self::primary_key_uuid_blob() {
+                    let pk_field = Self::primary_key_field();
+                    if let Some(orso::Value::Blob(bytes)) = map.get(pk_field) {
+                        if let Some(text) = orso::Utils::uuid_blob_to_text(bytes) {
+                            map.insert(pk_field.to_string(), orso::Value::Text(text));
+                        }
+                    }
+                }
+
+                #drop_unknown_columns
+
+                #(#float_from_map)*
+                #(#time_from_map)*
+                #(#text_from_map)*
+                #(#wide_from_map)*
+
                 // Get field metadata for type-aware conversion
                 let field_names = Self::field_names();
                 let field_types = Self::field_types();
                 let compressed_flags = Self::field_compressed();
+                // Collection fields stored as JSON text, parsed back before deserializing
+                let json_columns: &[&str] = &[#(#json_names),*];
 
                 // Group compressed fields by type for batch processing
                 let mut compressed_i64_blobs: std::collections::HashMap<String, Vec<u8>> = std::collections::HashMap::new();
@@ -729,6 +1301,10 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
 
                     if is_compressed {
                         match v {
+                            orso::Value::Blob(blob) if blob.is_empty() => {
+                                // The codec encodes an empty vector as an empty blob
+                                json_map.insert(k.clone(), serde_json::Value::Array(Vec::new()));
+                            }
                             orso::Value::Blob(blob) => {
                                 // Check blob header to determine the correct type
                                 if blob.len() >= 7 && &blob[0..4] == b"ORSO" {
@@ -741,6 +1317,9 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                         5 => compressed_f32_blobs.insert(k.clone(), blob.clone()),
                                         _ => compressed_i64_blobs.insert(k.clone(), blob.clone()), // Default to i64
                                     };
+                                } else if let Ok(legacy) = serde_json::from_slice::<serde_json::Value>(blob) {
+                                    // Legacy uncompressed JSON left in the column by a migration
+                                    json_map.insert(k.clone(), legacy);
                                 } else {
                                     // Unknown format, assume i64
                                     compressed_i64_blobs.insert(k.clone(), blob.clone());
@@ -1186,8 +1765,18 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                             // Check if this field should be a boolean based on field type
                             if let Some(pos) = field_names.iter().position(|&name| name == *k) {
                                 if matches!(field_types.get(pos), Some(orso::FieldType::Boolean)) {
-                                    // This is a boolean field, convert 0/1 to bool
-                                    serde_json::Value::Bool(*i != 0)
+                                    // This is a boolean field, convert 0/1 to bool; anything
+                                    // else is a corrupt value, not `true`
+                                    match *i {
+                                        0 | 1 => serde_json::Value::Bool(*i == 1),
+                                        _ => {
+                                            return Err(orso::Error::FieldDeserialization {
+                                                field: k.clone(),
+                                                value: v.clone(),
+                                                message: "expected 0 or 1 for a bool column".to_string(),
+                                            });
+                                        }
+                                    }
                                 } else {
                                     serde_json::Value::Number(serde_json::Number::from(*i))
                                 }
@@ -1202,6 +1791,9 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                                 serde_json::Value::String(f.to_string())
                             }
                         }
+                        orso::Value::Text(s) if json_columns.contains(&k.as_str()) => {
+                            serde_json::from_str::<serde_json::Value>(s).unwrap_or_else(|_| serde_json::Value::String(s.clone()))
+                        }
                         orso::Value::Text(s) => {
                             // Check if this might be a SQLite datetime that needs conversion
                             if s.len() == 19 && s.chars().nth(4) == Some('-') && s.chars().nth(7) == Some('-') && s.chars().nth(10) == Some(' ') {
@@ -1226,10 +1818,16 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
 
                 let json_value = serde_json::Value::Object(json_map);
 
-                match serde_json::from_value(json_value) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(orso::Error::Serialization(e.to_string()))
-                }
+                let #direct_mut result: Self = match <Self as orso::Deserialize>::deserialize(&json_value) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        #(#field_checks)*
+                        return Err(orso::Error::Serialization(e.to_string()));
+                    }
+                };
+                #(#float_assign)*
+                #(#wide_assign)*
+                Ok(result)
             }
 
 
@@ -1276,6 +1874,11 @@ pub fn derive_orso(input: TokenStream) -> TokenStream {
                 }
             }
         }
+
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#fetch_ref_methods)*
+        }
     };
 
     TokenStream::from(expanded)
@@ -1286,65 +1889,162 @@ fn parse_field_column_definition(field: &syn::Field) -> String {
     let field_name = field.ident.as_ref().unwrap().to_string();
 
     // Check for orso_column attributes
-    for attr in &field.attrs {
-        if attr.path().is_ident("orso_column") {
-            return parse_orso_column_attr(attr, &field_name, &field.ty);
-        }
+    if field
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("orso_column"))
+    {
+        return parse_orso_column_attr(field, &field_name, &field.ty);
     }
 
     // Default column definition based on field type
     map_rust_type_to_sql_column(&field.ty, &field_name)
 }
 
-// Parse orso_column attribute with support for foreign keys and compression
-fn parse_orso_column_attr(
-    attr: &syn::Attribute,
-    field_name: &str,
-    field_type: &syn::Type,
-) -> String {
-    let mut column_type = None;
-    let mut is_foreign_key = false;
-    let mut foreign_table = None;
-    let mut unique = false;
-    let mut primary_key = false;
-    let mut is_compressed = false;
-
-    let mut is_created_at = false;
-    let mut is_updated_at = false;
-
-    let _ = attr.parse_nested_meta(|meta| {
-        if meta.path.is_ident("ref") {
-            is_foreign_key = true;
-            if let Ok(value) = meta.value() {
-                let lit: Lit = value.parse()?;
-                if let Lit::Str(lit_str) = lit {
-                    foreign_table = Some(lit_str.value());
+// Options collected from an #[orso_column(...)] attribute
+#[derive(Default)]
+struct ColumnOptions {
+    column_type: Option<String>,
+    is_foreign_key: bool,
+    foreign_table: Option<String>,
+    unique: bool,
+    primary_key: bool,
+    uuid_blob: bool,
+    compress: bool,
+    // String stored as a compressed BLOB (see orso::Utils::compress_text)
+    compress_text: bool,
+    created_at: bool,
+    updated_at: bool,
+    created_by: bool,
+    updated_by: bool,
+    schema_version: bool,
+    generated: Option<String>,
+    stored: bool,
+    empty_as_null: bool,
+    // NOT NULL even on an Option field, which then needs `default`
+    not_null: bool,
+    // SQL expression for the column's DEFAULT
+    default: Option<String>,
+    // CHECK (col IN (0, 1)) on a bool column
+    check_bool: bool,
+    fts: bool,
+    default_now: bool,
+    touch_on_update: bool,
+    // Malformed attribute, reported on the field
+    error: Option<syn::Error>,
+}
+
+// Parse the orso_column attribute of a field (if any) into ColumnOptions
+fn parse_column_options(field: &syn::Field) -> ColumnOptions {
+    let mut options = ColumnOptions::default();
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("orso_column") {
+            continue;
+        }
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("ref") {
+                options.is_foreign_key = true;
+                if let Ok(value) = meta.value() {
+                    let lit: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = lit {
+                        options.foreign_table = Some(lit_str.value());
+                    }
                 }
-            }
-        } else if meta.path.is_ident("type") {
-            if let Ok(value) = meta.value() {
-                let lit: Lit = value.parse()?;
-                if let Lit::Str(lit_str) = lit {
-                    column_type = Some(lit_str.value());
+            } else if meta.path.is_ident("type") {
+                if let Ok(value) = meta.value() {
+                    let lit: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = lit {
+                        options.column_type = Some(lit_str.value());
+                    }
                 }
+            } else if meta.path.is_ident("unique") {
+                options.unique = true;
+            } else if meta.path.is_ident("primary_key") {
+                options.primary_key = true;
+            } else if meta.path.is_ident("uuid_blob") {
+                options.uuid_blob = true;
+            } else if meta.path.is_ident("created_at") {
+                options.created_at = true;
+            } else if meta.path.is_ident("updated_at") {
+                options.updated_at = true;
+            } else if meta.path.is_ident("created_by") {
+                options.created_by = true;
+            } else if meta.path.is_ident("updated_by") {
+                options.updated_by = true;
+            } else if meta.path.is_ident("schema_version") {
+                options.schema_version = true;
+            } else if meta.path.is_ident("compress") {
+                options.compress = true;
+            } else if meta.path.is_ident("compress_text") {
+                options.compress_text = true;
+            } else if meta.path.is_ident("generated") {
+                if let Ok(value) = meta.value() {
+                    let lit: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = lit {
+                        options.generated = Some(lit_str.value());
+                    }
+                }
+            } else if meta.path.is_ident("stored") {
+                options.stored = true;
+            } else if meta.path.is_ident("empty_as_null") {
+                options.empty_as_null = true;
+            } else if meta.path.is_ident("not_null") {
+                options.not_null = true;
+            } else if meta.path.is_ident("check_bool") {
+                options.check_bool = true;
+            } else if meta.path.is_ident("default") {
+                let lit: Lit = meta.value()?.parse()?;
+                let Lit::Str(lit_str) = lit else {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "default expects a string holding an SQL expression, e.g. default = \"0\"",
+                    ));
+                };
+                options.default = Some(lit_str.value());
+            } else if meta.path.is_ident("fts") {
+                options.fts = true;
+            } else if meta.path.is_ident("default_now") {
+                options.default_now = true;
+            } else if meta.path.is_ident("touch_on_update") {
+                options.touch_on_update = true;
             }
-        } else if meta.path.is_ident("unique") {
-            unique = true;
-        } else if meta.path.is_ident("primary_key") {
-            primary_key = true;
-        } else if meta.path.is_ident("created_at") {
-            is_created_at = true;
-        } else if meta.path.is_ident("updated_at") {
-            is_updated_at = true;
-        } else if meta.path.is_ident("compress") {
-            is_compressed = true;
+            Ok(())
+        });
+        if let Err(error) = parsed {
+            options.error.get_or_insert(error);
         }
-        Ok(())
-    });
+    }
+
+    options
+}
+
+// Parse orso_column attribute with support for foreign keys and compression
+fn parse_orso_column_attr(field: &syn::Field, field_name: &str, field_type: &syn::Type) -> String {
+    let ColumnOptions {
+        column_type,
+        is_foreign_key,
+        foreign_table,
+        unique,
+        primary_key,
+        uuid_blob,
+        compress: is_compressed,
+        compress_text,
+        created_at: is_created_at,
+        updated_at: is_updated_at,
+        generated,
+        stored,
+        default_now,
+        touch_on_update,
+        not_null,
+        default,
+        check_bool,
+        ..
+    } = parse_column_options(field);
 
     // Generate column definition
     // For compressed fields, we always use BLOB type
-    let base_type = if is_compressed {
+    let base_type = if is_compressed || compress_text || (primary_key && uuid_blob) {
         "BLOB".to_string()
     } else if is_foreign_key {
         "TEXT".to_string() // Foreign keys are always TEXT (UUID)
@@ -1354,15 +2054,25 @@ fn parse_orso_column_attr(
 
     let mut column_def = format!("{} {}", field_name, base_type);
 
+    if let Some(expression) = generated {
+        let storage = if stored { "STORED" } else { "VIRTUAL" };
+        column_def.push_str(&format!(
+            " GENERATED ALWAYS AS ({}) {}",
+            expression, storage
+        ));
+    }
     if primary_key {
         column_def.push_str(" PRIMARY KEY");
         // Add default for primary key if it's TEXT type
         if base_type == "TEXT" {
             column_def.push_str(" DEFAULT (lower(hex(randomblob(16))))");
+        } else if uuid_blob {
+            column_def.push_str(" DEFAULT (randomblob(16))");
         }
     }
-    // Add NOT NULL for non-Option types (except primary keys which are already handled)
-    if !is_option_type(field_type) && !primary_key {
+    // Add NOT NULL for non-Option types and not_null fields (except primary keys which
+    // are already handled)
+    if (!is_option_type(field_type) || not_null) && !primary_key {
         column_def.push_str(" NOT NULL");
     }
     if unique {
@@ -1371,10 +2081,15 @@ fn parse_orso_column_attr(
     if let Some(ref_table) = foreign_table {
         column_def.push_str(&format!(" REFERENCES {}(id)", ref_table));
     }
+    if check_bool {
+        column_def.push_str(&format!(" CHECK ({} IN (0, 1))", field_name));
+    }
 
     // Add defaults for timestamp columns
-    if is_created_at || is_updated_at {
+    if is_created_at || is_updated_at || default_now || touch_on_update {
         column_def.push_str(" DEFAULT (strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now'))");
+    } else if let Some(default) = default {
+        column_def.push_str(&format!(" DEFAULT ({})", default));
     }
 
     column_def
@@ -1403,6 +2118,8 @@ fn map_rust_type_to_sql_type(rust_type: &syn::Type) -> String {
                 "i64" | "i32" | "i16" | "i8" => "INTEGER".to_string(),
                 "u64" | "u32" | "u16" | "u8" => "INTEGER".to_string(),
                 "f64" | "f32" => "REAL".to_string(),
+                // Exact decimal strings, beyond a 64-bit INTEGER (see orso::as_text)
+                "i128" | "u128" => "TEXT".to_string(),
                 "bool" => "INTEGER".to_string(), // SQLite stores booleans as integers
                 "Option" => {
                     // Handle Option<T> types
@@ -1460,6 +2177,135 @@ fn map_field_type(rust_type: &syn::Type, _field: &syn::Field) -> proc_macro2::To
     quote! { orso::FieldType::Text }
 }
 
+// Name of the element type of a Vec<T> (e.g. "f64" for Vec<f64>)
+fn vec_element_type(rust_type: &syn::Type) -> Option<String> {
+    if let syn::Type::Path(type_path) = rust_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "Vec" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(syn::Type::Path(inner))) =
+                        args.args.first()
+                    {
+                        return inner.path.segments.last().map(|s| s.ident.to_string());
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Collections serde writes as JSON arrays/objects (Vec<String>, HashMap<String, String>,
+// ...), optionally wrapped in Option; stored as JSON TEXT unless compressed
+fn is_json_collection_type(rust_type: &syn::Type) -> bool {
+    let rust_type = option_inner_type(rust_type).unwrap_or(rust_type);
+    if let syn::Type::Path(type_path) = rust_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            return [
+                "Vec", "VecDeque", "HashMap", "BTreeMap", "HashSet", "BTreeSet",
+            ]
+            .iter()
+            .any(|collection| segment.ident == collection);
+        }
+    }
+    false
+}
+
+// Inner type of an Option<T>, if the type is one
+fn option_inner_type(rust_type: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(type_path) = rust_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                        return Some(inner);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Check if a type is Option<DateTime<Utc>>, with or without the chrono:: paths
+fn is_option_datetime_utc(rust_type: &syn::Type) -> bool {
+    let Some(syn::Type::Path(inner)) = option_inner_type(rust_type) else {
+        return false;
+    };
+    let Some(segment) = inner.path.segments.last() else {
+        return false;
+    };
+    if segment.ident != "DateTime" {
+        return false;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => matches!(
+            args.args.first(),
+            Some(syn::GenericArgument::Type(syn::Type::Path(tz)))
+                if tz.path.segments.last().is_some_and(|tz| tz.ident == "Utc")
+        ),
+        _ => false,
+    }
+}
+
+// Check if a type is bool or Option<bool>
+fn is_bool_type(rust_type: &syn::Type) -> bool {
+    let rust_type = option_inner_type(rust_type).unwrap_or(rust_type);
+    matches!(rust_type, syn::Type::Path(type_path) if type_path.path.is_ident("bool"))
+}
+
+// Check if a type is String (not an Option of one)
+fn is_string_type(rust_type: &syn::Type) -> bool {
+    matches!(
+        rust_type,
+        syn::Type::Path(type_path)
+            if type_path.path.segments.last().is_some_and(|segment| segment.ident == "String")
+    )
+}
+
+// Check if a type is i128/u128 or an Option of one
+fn is_wide_integer_type(rust_type: &syn::Type) -> bool {
+    let rust_type = option_inner_type(rust_type).unwrap_or(rust_type);
+    matches!(
+        rust_type,
+        syn::Type::Path(type_path)
+            if type_path.path.is_ident("i128") || type_path.path.is_ident("u128")
+    )
+}
+
+// Whether the field has its own serde (de)serializer:
+// #[serde(with | serialize_with | deserialize_with = "...")]
+fn serde_with(field: &syn::Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Meta, Comma>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|meta| {
+            ["with", "serialize_with", "deserialize_with"]
+                .iter()
+                .any(|name| meta.path().is_ident(name))
+        })
+}
+
+// Whether the field is #[serde(flatten)], which takes the keys no other field claims
+fn serde_flatten(field: &syn::Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Meta, Comma>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|meta| meta.path().is_ident("flatten"))
+}
+
 // Check if a type is Option<T>
 fn is_option_type(rust_type: &syn::Type) -> bool {
     if let syn::Type::Path(type_path) = rust_type {
@@ -1470,108 +2316,512 @@ fn is_option_type(rust_type: &syn::Type) -> bool {
     false
 }
 
+// Metadata collected from all struct fields
+#[derive(Default)]
+struct FieldMetadata {
+    field_names: Vec<proc_macro2::TokenStream>,
+    column_definitions: Vec<proc_macro2::TokenStream>,
+    field_types: Vec<proc_macro2::TokenStream>,
+    nullable_flags: Vec<bool>,
+    primary_key_field: Option<proc_macro2::Ident>,
+    primary_key_type: Option<syn::Type>,
+    // Primary key stored as a 16-byte BLOB UUID
+    primary_key_uuid_blob: bool,
+    created_at_field: Option<proc_macro2::Ident>,
+    updated_at_field: Option<proc_macro2::Ident>,
+    created_by_field: Option<proc_macro2::Ident>,
+    updated_by_field: Option<proc_macro2::Ident>,
+    // Stamped with the model's schema version on every write
+    schema_version_field: Option<proc_macro2::Ident>,
+    unique_fields: Vec<proc_macro2::Ident>,
+    // `ref = "table"` columns and the table they point at
+    foreign_keys: Vec<(proc_macro2::Ident, String)>,
+    // Generated columns: (field, expression, stored)
+    generated_fields: Vec<(proc_macro2::Ident, String, bool)>,
+    empty_as_null_fields: Vec<proc_macro2::Ident>,
+    // Option fields forced NOT NULL; None is left to the column DEFAULT
+    not_null_default_fields: Vec<proc_macro2::Ident>,
+    // Uncompressed collection fields, stored as JSON text
+    json_fields: Vec<proc_macro2::Ident>,
+    // Text columns indexed in the companion FTS5 table
+    fts_fields: Vec<proc_macro2::Ident>,
+    // Timestamps filled by the database: on insert, and on every update
+    default_now_fields: Vec<proc_macro2::Ident>,
+    touch_on_update_fields: Vec<proc_macro2::Ident>,
+    // Boolean columns carrying a CHECK (col IN (0, 1)) constraint
+    check_bool_fields: Vec<proc_macro2::Ident>,
+    compressed_fields: Vec<bool>,
+    // Compressed float vectors, encoded straight from the struct field so that
+    // NaN/Infinity survive (JSON has no representation for them)
+    compressed_float_fields: Vec<(proc_macro2::Ident, proc_macro2::Ident)>,
+    // Compressed Vec<DateTime<Utc>> fields, stored as compressed epoch milliseconds
+    compressed_time_fields: Vec<proc_macro2::Ident>,
+    // compress_text String fields, stored as compressed UTF-8 BLOBs
+    compressed_text_fields: Vec<proc_macro2::Ident>,
+    // Per field, whether it is a compress_text field
+    compressed_text_flags: Vec<bool>,
+    // i128/u128 fields (or Options of them) without their own serde helper, stored as
+    // decimal TEXT: field, integer type and whether it is an Option
+    wide_integer_fields: Vec<(proc_macro2::Ident, syn::Type, bool)>,
+    // Field and serialized names; from_map drops any other column. None when a field is
+    // #[serde(flatten)] and needs the leftover keys
+    known_columns: Option<Vec<String>>,
+    // Fields deserialized with their plain type, checked one by one when from_map fails
+    checked_fields: Vec<(proc_macro2::Ident, syn::Type)>,
+    // Misused attributes, reported as compile errors on the offending field
+    errors: Vec<syn::Error>,
+}
+
 // Extract field metadata from all struct fields
-fn extract_field_metadata_original(
-    fields: &Punctuated<syn::Field, Comma>,
-) -> (
-    Vec<proc_macro2::TokenStream>,
-    Vec<proc_macro2::TokenStream>,
-    Vec<proc_macro2::TokenStream>,
-    Vec<bool>,
-    Option<proc_macro2::Ident>,
-    Option<proc_macro2::Ident>,
-    Option<proc_macro2::Ident>,
-    Vec<proc_macro2::Ident>,
-    Vec<bool>, // Compression flags
-) {
-    let mut field_names = Vec::new();
-    let mut column_defs = Vec::new();
-    let mut field_types = Vec::new();
-    let mut nullable_flags = Vec::new();
-    let mut primary_key_field: Option<proc_macro2::Ident> = None;
-    let mut created_at_field: Option<proc_macro2::Ident> = None;
-    let mut updated_at_field: Option<proc_macro2::Ident> = None;
-    let mut unique_fields = Vec::new();
-    let mut compressed_fields = Vec::new(); // New vector for compression flags
+fn extract_field_metadata_original(fields: &Punctuated<syn::Field, Comma>) -> FieldMetadata {
+    let mut metadata = FieldMetadata {
+        known_columns: Some(Vec::new()),
+        ..FieldMetadata::default()
+    };
+    // Serialized key -> field that produced it; two fields sharing a key would overwrite
+    // each other in to_map
+    let mut serialized_names: std::collections::HashMap<String, proc_macro2::Ident> =
+        std::collections::HashMap::new();
 
     for field in fields {
         if let Some(field_name) = &field.ident {
-            // Check for special attributes
-            let mut is_primary_key = false;
-            let mut is_created_at = false;
-            let mut is_updated_at = false;
-            let mut is_unique = false;
-            let mut is_compressed = false; // Track compression
-
-            for attr in &field.attrs {
-                if attr.path().is_ident("orso_column") {
-                    let _ = attr.parse_nested_meta(|meta| {
-                        if meta.path.is_ident("primary_key") {
-                            is_primary_key = true;
-                            primary_key_field = Some(field_name.clone());
-                        } else if meta.path.is_ident("created_at") {
-                            is_created_at = true;
-                            created_at_field = Some(field_name.clone());
-                        } else if meta.path.is_ident("updated_at") {
-                            is_updated_at = true;
-                            updated_at_field = Some(field_name.clone());
-                        } else if meta.path.is_ident("unique") {
-                            is_unique = true;
-                        } else if meta.path.is_ident("compress") {
-                            is_compressed = true;
-                        }
-                        Ok(())
-                    });
+            let serialized = serde_rename(field).unwrap_or_else(|| field_name.to_string());
+            if serde_flatten(field) {
+                metadata.known_columns = None;
+            } else if let Some(known) = &mut metadata.known_columns {
+                known.push(field_name.to_string());
+                if *field_name != serialized {
+                    known.push(serialized.clone());
                 }
             }
+            if let Some(previous) = serialized_names.get(&serialized) {
+                metadata.errors.push(syn::Error::new_spanned(
+                    field_name,
+                    format!(
+                        "field `{field_name}` serializes to column `{serialized}`, already used by field `{previous}`"
+                    ),
+                ));
+            } else {
+                serialized_names.insert(serialized, field_name.clone());
+            }
 
-            if is_unique {
-                unique_fields.push(field_name.clone());
+            // Check for special attributes
+            let mut options = parse_column_options(field);
+            metadata.errors.extend(options.error.take());
+
+            if options.primary_key {
+                metadata.primary_key_field = Some(field_name.clone());
+                metadata.primary_key_type = Some(field.ty.clone());
+                metadata.primary_key_uuid_blob = options.uuid_blob;
+            }
+            // get_created_at/set_updated_at read and write these as Option<DateTime<Utc>>
+            for (set, attribute) in [
+                (options.created_at, "created_at"),
+                (options.updated_at, "updated_at"),
+            ] {
+                if set && !is_option_datetime_utc(&field.ty) {
+                    metadata.errors.push(syn::Error::new_spanned(
+                        &field.ty,
+                        format!(
+                            "#[orso_column({attribute})] requires a field of type Option<DateTime<Utc>>"
+                        ),
+                    ));
+                }
+            }
+            if options.created_at {
+                metadata.created_at_field = Some(field_name.clone());
+            }
+            if options.updated_at {
+                metadata.updated_at_field = Some(field_name.clone());
+            }
+            if options.created_by {
+                metadata.created_by_field = Some(field_name.clone());
+            }
+            if options.updated_by {
+                metadata.updated_by_field = Some(field_name.clone());
+            }
+            if options.schema_version {
+                metadata.schema_version_field = Some(field_name.clone());
+            }
+            if options.unique {
+                metadata.unique_fields.push(field_name.clone());
+            }
+            if let Some(table) = &options.foreign_table {
+                metadata
+                    .foreign_keys
+                    .push((field_name.clone(), table.clone()));
+            }
+            if options.empty_as_null {
+                metadata.empty_as_null_fields.push(field_name.clone());
+            }
+            if is_wide_integer_type(&field.ty) && !serde_with(field) {
+                let integer = option_inner_type(&field.ty).unwrap_or(&field.ty).clone();
+                metadata.wide_integer_fields.push((
+                    field_name.clone(),
+                    integer,
+                    is_option_type(&field.ty),
+                ));
+            }
+            if options.compress_text {
+                if is_string_type(&field.ty) {
+                    metadata.compressed_text_fields.push(field_name.clone());
+                } else {
+                    metadata.errors.push(syn::Error::new_spanned(
+                        &field.ty,
+                        "#[orso_column(compress_text)] requires a String field",
+                    ));
+                }
+            }
+            if options.check_bool {
+                if is_bool_type(&field.ty) {
+                    metadata.check_bool_fields.push(field_name.clone());
+                } else {
+                    metadata.errors.push(syn::Error::new_spanned(
+                        &field.ty,
+                        "#[orso_column(check_bool)] requires a bool or Option<bool> field",
+                    ));
+                }
+            }
+            let option_not_null = options.not_null && is_option_type(&field.ty);
+            if option_not_null {
+                if options.default.is_none() {
+                    metadata.errors.push(syn::Error::new_spanned(
+                        &field.ty,
+                        "#[orso_column(not_null)] on an Option field requires default = \"...\"",
+                    ));
+                }
+                metadata.not_null_default_fields.push(field_name.clone());
+            }
+            if options.fts {
+                metadata.fts_fields.push(field_name.clone());
+            }
+            if options.created_at
+                || options.updated_at
+                || options.default_now
+                || options.touch_on_update
+            {
+                metadata.default_now_fields.push(field_name.clone());
+            }
+            if options.updated_at || options.touch_on_update {
+                metadata.touch_on_update_fields.push(field_name.clone());
+            }
+            if let Some(expression) = &options.generated {
+                metadata.generated_fields.push((
+                    field_name.clone(),
+                    expression.clone(),
+                    options.stored,
+                ));
             }
 
             // Process ALL fields - no skipping based on field names
 
             let field_name_token = quote! { stringify!(#field_name) };
-            field_names.push(field_name_token);
+            metadata.field_names.push(field_name_token);
 
             // Parse column attributes for foreign key references (inline REFERENCES)
             let column_def = parse_field_column_definition(field);
-            column_defs.push(quote! { #column_def.to_string() });
+            metadata
+                .column_definitions
+                .push(quote! { #column_def.to_string() });
 
             // Enhanced type mapping based on field type and attributes
             let field_type = map_field_type(&field.ty, field);
-            field_types.push(field_type);
-
-            // Check if field is Option<T> (nullable)
-            let is_nullable = is_option_type(&field.ty);
-            nullable_flags.push(is_nullable);
+            metadata.field_types.push(field_type);
+
+            // Check if field is Option<T> (nullable), unless forced NOT NULL
+            let is_nullable = is_option_type(&field.ty) && !option_not_null;
+            metadata.nullable_flags.push(is_nullable);
+
+            // Fields with custom serde handling can't be checked against their bare type
+            let has_serde_attr = field.attrs.iter().any(|attr| attr.path().is_ident("serde"));
+            if !has_serde_attr {
+                metadata
+                    .checked_fields
+                    .push((field_name.clone(), field.ty.clone()));
+            }
 
             // Store compression flag
-            compressed_fields.push(is_compressed);
+            metadata.compressed_fields.push(options.compress);
+            metadata
+                .compressed_text_flags
+                .push(options.compress_text && is_string_type(&field.ty));
+            if !options.compress && is_json_collection_type(&field.ty) {
+                metadata.json_fields.push(field_name.clone());
+            }
+            if options.compress {
+                if let Some(element) = vec_element_type(&field.ty) {
+                    if element == "f64" || element == "f32" {
+                        metadata
+                            .compressed_float_fields
+                            .push((field_name.clone(), format_ident!("{}", element)));
+                    } else if element == "DateTime" {
+                        metadata.compressed_time_fields.push(field_name.clone());
+                    }
+                }
+            }
         }
     }
 
-    (
-        field_names,
-        column_defs,
-        field_types,
-        nullable_flags,
-        primary_key_field,
-        created_at_field,
-        updated_at_field,
-        unique_fields,
-        compressed_fields, // Return compression flags
-    )
+    metadata
 }
 
-// Extract table name from struct attributes
-fn extract_orso_table_name(attrs: &[Attribute]) -> Option<String> {
+// Name set by #[serde(rename = "...")] or #[serde(rename(serialize = "..."))]
+fn serde_rename(field: &syn::Field) -> Option<String> {
+    let mut rename = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("serde") {
+            continue;
+        }
+        let Ok(metas) = attr.parse_args_with(Punctuated::<syn::Meta, Comma>::parse_terminated)
+        else {
+            continue;
+        };
+        for meta in metas {
+            match meta {
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("rename") => {
+                    if let syn::Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(lit_str),
+                        ..
+                    }) = &name_value.value
+                    {
+                        rename = Some(lit_str.value());
+                    }
+                }
+                syn::Meta::List(list) if list.path.is_ident("rename") => {
+                    let _ = list.parse_nested_meta(|nested| {
+                        if nested.path.is_ident("serialize") {
+                            let lit: syn::LitStr = nested.value()?.parse()?;
+                            rename = Some(lit.value());
+                        } else if let Ok(value) = nested.value() {
+                            let _: syn::LitStr = value.parse()?;
+                        }
+                        Ok(())
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    rename
+}
+
+// One #[orso_index(("col", ...), order = "desc", where = "...", unique, name = "...")]
+#[derive(Default)]
+struct IndexSpec {
+    columns: Vec<String>,
+    descending: bool,
+    unique: bool,
+    predicate: Option<String>,
+    name: Option<String>,
+}
+
+fn parse_orso_index(attr: &Attribute, struct_fields: &[String]) -> syn::Result<IndexSpec> {
+    use syn::ext::IdentExt;
+
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let mut spec = IndexSpec::default();
+        let columns: Vec<syn::LitStr> = if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            Punctuated::<syn::LitStr, Comma>::parse_terminated(&content)?
+                .into_iter()
+                .collect()
+        } else {
+            vec![input.parse()?]
+        };
+        if columns.is_empty() {
+            return Err(input.error("orso_index needs at least one column"));
+        }
+        for column in &columns {
+            if !struct_fields.contains(&column.value()) {
+                return Err(syn::Error::new_spanned(
+                    column,
+                    format!("orso_index column `{}` is not a field", column.value()),
+                ));
+            }
+        }
+        spec.columns = columns.iter().map(|column| column.value()).collect();
+
+        while !input.is_empty() {
+            input.parse::<Comma>()?;
+            if input.is_empty() {
+                break;
+            }
+            // `where` is a keyword, so read option names with parse_any
+            let key = input.call(syn::Ident::parse_any)?;
+            if key == "unique" {
+                spec.unique = true;
+                continue;
+            }
+            input.parse::<syn::Token![=]>()?;
+            let value: syn::LitStr = input.parse()?;
+            match key.to_string().as_str() {
+                "order" => match value.value().to_ascii_lowercase().as_str() {
+                    "asc" => spec.descending = false,
+                    "desc" => spec.descending = true,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "orso_index order must be \"asc\" or \"desc\"",
+                        ))
+                    }
+                },
+                "where" => spec.predicate = Some(value.value()),
+                "name" => spec.name = Some(value.value()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "unknown orso_index option (expected order, where, unique or name)",
+                    ))
+                }
+            }
+        }
+        Ok(spec)
+    })
+}
+
+// Extract table name from struct attributes: #[orso_table("name")] or
+// #[orso_table(naming = "snake_case" | "snake_case_plural" | "as_is")]. Without either the
+// name is the lowercased struct name.
+// Options from #[orso_table("name" | naming = "...", order_by = "...")]
+#[derive(Default)]
+struct TableOptions {
+    table_name: String,
+    // Default ORDER BY, checked against the fields once they are known
+    order_by: Option<syn::LitStr>,
+}
+
+fn parse_orso_table(name: &syn::Ident, attrs: &[Attribute]) -> syn::Result<TableOptions> {
+    let mut options = TableOptions {
+        table_name: name.to_string().to_lowercase(),
+        order_by: None,
+    };
     for attr in attrs {
-        if attr.path().is_ident("orso_table") {
-            if let Ok(Lit::Str(lit_str)) = attr.parse_args::<Lit>() {
-                return Some(lit_str.value());
+        if !attr.path().is_ident("orso_table") {
+            continue;
+        }
+        let mut naming: Option<syn::LitStr> = None;
+        attr.parse_args_with(|input: syn::parse::ParseStream| {
+            if input.peek(syn::LitStr) {
+                options.table_name = input.parse::<syn::LitStr>()?.value();
+                if !input.is_empty() {
+                    input.parse::<Comma>()?;
+                }
             }
+            while !input.is_empty() {
+                let key: syn::Ident = input.parse()?;
+                input.parse::<syn::Token![=]>()?;
+                let value: syn::LitStr = input.parse()?;
+                if key == "naming" {
+                    naming = Some(value);
+                } else if key == "order_by" {
+                    options.order_by = Some(value);
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "expected a table name, naming = \"...\" or order_by = \"...\"",
+                    ));
+                }
+                if !input.is_empty() {
+                    input.parse::<Comma>()?;
+                }
+            }
+            Ok(())
+        })?;
+        if let Some(naming) = naming {
+            options.table_name = apply_naming(&name.to_string(), &naming)?;
         }
     }
-    None
+    Ok(options)
+}
+
+// Table name for a struct name under #[orso_table(naming = "...")]
+fn apply_naming(name: &str, naming: &syn::LitStr) -> syn::Result<String> {
+    match naming.value().as_str() {
+        "as_is" => Ok(name.to_string()),
+        "snake_case" => Ok(snake_case(name)),
+        "snake_case_plural" => Ok(pluralize(&snake_case(name))),
+        _ => Err(syn::Error::new_spanned(
+            naming,
+            "unknown naming strategy; expected snake_case, snake_case_plural or as_is",
+        )),
+    }
+}
+
+// "created_at desc, id" -> [("created_at", true), ("id", false)]
+fn parse_order_by(
+    order_by: &syn::LitStr,
+    fields: &[String],
+    compressed: &[String],
+) -> syn::Result<Vec<(String, bool)>> {
+    let mut sorts = Vec::new();
+    for term in order_by.value().split(',') {
+        let mut words = term.split_whitespace();
+        let (Some(column), direction, None) = (words.next(), words.next(), words.next()) else {
+            return Err(syn::Error::new_spanned(
+                order_by,
+                "order_by expects \"column [asc|desc], ...\"",
+            ));
+        };
+        if !fields.iter().any(|field| field == column) {
+            return Err(syn::Error::new_spanned(
+                order_by,
+                format!("order_by column `{column}` is not a field of this struct"),
+            ));
+        }
+        if compressed.iter().any(|field| field == column) {
+            return Err(syn::Error::new_spanned(
+                order_by,
+                format!("order_by column `{column}` is compressed and can't be sorted on"),
+            ));
+        }
+        let descending = match direction.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(other) => {
+                return Err(syn::Error::new_spanned(
+                    order_by,
+                    format!("unknown sort direction `{other}`; expected asc or desc"),
+                ))
+            }
+        };
+        sorts.push((column.to_string(), descending));
+    }
+    Ok(sorts)
+}
+
+// TestUser -> test_user, HTTPRequestLog -> http_request_log
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+// English plural of the last word: category -> categories, address -> addresses
+fn pluralize(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix('y') {
+        if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) {
+            return format!("{stem}ies");
+        }
+    }
+    if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        return format!("{name}es");
+    }
+    format!("{name}s")
 }
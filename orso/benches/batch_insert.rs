@@ -0,0 +1,89 @@
+// Wall-clock and allocation figures for `batch_insert` of wide rows.
+// Run with: cargo bench -p orso --bench batch_insert
+use orso::{migration, Database, DatabaseConfig, Migrations, Orso};
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const ROWS: i64 = 5_000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("wide_rows")]
+struct WideRow {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    symbol: String,
+    venue: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: i64,
+    trades: i64,
+    vwap: f64,
+    bid: f64,
+    ask: f64,
+    spread: f64,
+    halted: bool,
+    #[orso_column(created_at)]
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[orso_column(updated_at)]
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn row(i: i64) -> WideRow {
+    WideRow {
+        symbol: format!("SYM{i}"),
+        venue: "XNAS".to_string(),
+        open: i as f64,
+        high: i as f64 + 1.0,
+        low: i as f64 - 1.0,
+        close: i as f64 + 0.5,
+        volume: i * 100,
+        trades: i,
+        vwap: i as f64 + 0.25,
+        bid: i as f64,
+        ask: i as f64 + 0.1,
+        spread: 0.1,
+        ..Default::default()
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::init(DatabaseConfig::memory()).await?;
+    Migrations::init(&db, &[migration!(WideRow)]).await?;
+    let rows: Vec<WideRow> = (0..ROWS).map(row).collect();
+
+    let started = std::time::Instant::now();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    WideRow::batch_insert(&rows, &db).await?;
+    let allocated = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    let elapsed = started.elapsed();
+
+    assert_eq!(WideRow::count(&db).await?, ROWS as u64);
+    println!(
+        "batch_insert of {ROWS} rows: {elapsed:?} ({:?} per row), {} allocations per row",
+        elapsed / ROWS as u32,
+        allocated / ROWS as usize
+    );
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! Serde helpers that store a value as its text form, for types a JSON number can't hold
+//! exactly, such as `i128`/`u128`. The derive already stores plain `i128`/`u128` fields as
+//! exact decimal TEXT in `to_map`/`from_map`; these helpers make the struct's own serde
+//! impl (e.g. NDJSON exports, API responses) use the text form too:
+//!
+//! ```ignore
+//! #[serde(with = "orso::as_text")]
+//! quantity: i128,
+//! #[serde(with = "orso::as_text::option")]
+//! limit: Option<u128>,
+//! ```
+use serde::{de, Deserialize, Deserializer, Serializer};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+/// Parse the text form; plain numbers (e.g. written before the field used this helper) are
+/// accepted too
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(TextVisitor(PhantomData))
+}
+
+struct TextVisitor<T>(PhantomData<T>);
+
+impl<T> TextVisitor<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn parse<E: de::Error>(text: &str) -> Result<T, E> {
+        text.parse()
+            .map_err(|e| E::custom(format!("invalid value '{text}': {e}")))
+    }
+}
+
+impl<T> de::Visitor<'_> for TextVisitor<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string or number")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        Self::parse(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        Self::parse(&value.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        Self::parse(&value.to_string())
+    }
+
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<T, E> {
+        Self::parse(&value.to_string())
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<T, E> {
+        Self::parse(&value.to_string())
+    }
+}
+
+/// `as_text` for `Option<T>` fields; `None` is NULL
+pub mod option {
+    use super::*;
+
+    struct Text<T>(T);
+
+    impl<'de, T> Deserialize<'de> for Text<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(Text)
+        }
+    }
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<Text<T>>::deserialize(deserializer)?.map(|text| text.0))
+    }
+}
+
+/// Value of a TEXT column holding an `i128`/`u128` field, as read by the derive's
+/// `from_map`; `None` for NULL or a missing column
+#[doc(hidden)]
+pub fn from_column<T>(field: &str, value: Option<crate::Value>) -> crate::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = match &value {
+        None | Some(crate::Value::Null) => return Ok(None),
+        Some(crate::Value::Text(text)) => text.clone(),
+        // Stored before the column held text, while the value fit an INTEGER
+        Some(crate::Value::Integer(i)) => i.to_string(),
+        Some(_) => String::new(),
+    };
+    text.parse()
+        .map(Some)
+        .map_err(|e| crate::Error::FieldDeserialization {
+            field: field.to_string(),
+            value: value.unwrap_or(crate::Value::Null),
+            message: format!("invalid value '{text}': {e}"),
+        })
+}
@@ -1,15 +1,103 @@
-use crate::Error;
+use crate::query_log::QueryLog;
+use crate::{Error, FromValue, Transaction, TransactionMode};
 use anyhow::Result;
-use libsql::{Builder, Database as LibsqlDatabase, Rows};
+use libsql::{Builder, Database as LibsqlDatabase};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use std::time::Duration;
+use tracing::{debug, Instrument};
 
 #[cfg(feature = "sqlite")]
 use rusqlite::Connection as RusqliteConnection;
-#[cfg(feature = "sqlite")]
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 #[cfg(feature = "sqlite")]
 use std::sync::Mutex;
+use std::sync::{Arc, RwLock};
+
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+/// Base delay between busy retries; grows linearly with the attempt number
+const BUSY_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Boxed future, as returned by a `TokenProvider`
+pub type DbFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Async callback producing a fresh auth token for remote/sync modes
+#[derive(Clone)]
+pub struct TokenProvider(Arc<dyn Fn() -> DbFuture<'static, Result<String, Error>> + Send + Sync>);
+
+impl TokenProvider {
+    pub fn new<F, Fut>(provider: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<String, Error>> + Send + 'static,
+    {
+        Self(Arc::new(move || Box::pin(provider())))
+    }
+
+    pub async fn token(&self) -> Result<String, Error> {
+        (self.0)().await
+    }
+}
+
+impl std::fmt::Debug for TokenProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TokenProvider(..)")
+    }
+}
+
+// Providers compare by identity
+impl PartialEq for TokenProvider {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TokenProvider {}
+
+// Settable pragmas accepted by `DatabaseConfig::with_pragmas`
+const KNOWN_PRAGMAS: &[&str] = &[
+    "analysis_limit",
+    "application_id",
+    "auto_vacuum",
+    "automatic_index",
+    "busy_timeout",
+    "cache_size",
+    "cache_spill",
+    "case_sensitive_like",
+    "cell_size_check",
+    "checkpoint_fullfsync",
+    "defer_foreign_keys",
+    "foreign_keys",
+    "fullfsync",
+    "hard_heap_limit",
+    "ignore_check_constraints",
+    "journal_mode",
+    "journal_size_limit",
+    "legacy_alter_table",
+    "locking_mode",
+    "max_page_count",
+    "mmap_size",
+    "page_size",
+    "query_only",
+    "read_uncommitted",
+    "recursive_triggers",
+    "reverse_unordered_selects",
+    "secure_delete",
+    "soft_heap_limit",
+    "synchronous",
+    "temp_store",
+    "threads",
+    "trusted_schema",
+    "user_version",
+    "wal_autocheckpoint",
+];
+
+/// Rows fetched per round-trip by `RecordStream` unless configured otherwise
+pub const DEFAULT_FETCH_BATCH_SIZE: usize = 500;
+
+fn default_fetch_batch_size() -> usize {
+    DEFAULT_FETCH_BATCH_SIZE
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DatabaseConfig {
@@ -17,6 +105,21 @@ pub struct DatabaseConfig {
     pub local_db_path: String,
     pub db_url: String,
     pub db_token: String,
+    /// Extra attempts for writes that fail with SQLITE_BUSY/SQLITE_LOCKED (0 disables)
+    #[serde(default)]
+    pub busy_retries: u32,
+    /// Interrupt statements still running after this long (`None` disables)
+    #[serde(default)]
+    pub interrupt_after: Option<Duration>,
+    /// Rows requested per round-trip by streaming reads
+    #[serde(default = "default_fetch_batch_size")]
+    pub fetch_batch_size: usize,
+    /// `PRAGMA name = value` settings applied, in order, whenever a connection is opened
+    #[serde(default)]
+    pub pragmas: Vec<(String, String)>,
+    /// Called for a new `db_token` before reconnecting after a connection or auth failure
+    #[serde(skip)]
+    pub token_provider: Option<TokenProvider>,
 }
 
 impl DatabaseConfig {
@@ -26,6 +129,11 @@ impl DatabaseConfig {
             local_db_path,
             db_url,
             db_token,
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
         }
     }
 
@@ -35,6 +143,22 @@ impl DatabaseConfig {
             local_db_path: String::new(),
             db_url: String::new(),
             db_token: String::new(),
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
+        }
+    }
+
+    /// An in-memory database shared by every connection opened with the same `name` in
+    /// this process (`file:name?mode=memory&cache=shared`), unlike `memory()` where each
+    /// connection gets its own empty database. The data lives until the last connection
+    /// to it closes.
+    pub fn memory_shared(name: impl Into<String>) -> Self {
+        Self {
+            local_db_path: name.into(),
+            ..Self::memory()
         }
     }
 
@@ -44,6 +168,11 @@ impl DatabaseConfig {
             local_db_path: db_path.into(),
             db_url: String::new(),
             db_token: String::new(),
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
         }
     }
 
@@ -54,6 +183,11 @@ impl DatabaseConfig {
             local_db_path: db_path.into(),
             db_url: String::new(),
             db_token: String::new(),
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
         }
     }
 
@@ -63,6 +197,11 @@ impl DatabaseConfig {
             local_db_path: String::new(),
             db_url: db_url.into(),
             db_token: db_token.into(),
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
         }
     }
 
@@ -76,6 +215,11 @@ impl DatabaseConfig {
             local_db_path: local_db_path.into(),
             db_url: db_url.into(),
             db_token: db_token.into(),
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
         }
     }
 
@@ -89,7 +233,144 @@ impl DatabaseConfig {
             local_db_path: local_db_path.into(),
             db_url: db_url.into(),
             db_token: db_token.into(),
+            busy_retries: 0,
+            interrupt_after: None,
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            pragmas: Vec::new(),
+            token_provider: None,
+        }
+    }
+
+    /// Retry writes that hit a locked database up to `attempts` more times
+    pub fn with_busy_retries(mut self, attempts: u32) -> Self {
+        self.busy_retries = attempts;
+        self
+    }
+
+    /// Have SQLite abort any statement still running after `timeout`, including CPU-bound
+    /// work inside the engine; the operation fails with `Error::Interrupted`
+    pub fn with_interrupt_after(mut self, timeout: Duration) -> Self {
+        self.interrupt_after = Some(timeout);
+        self
+    }
+
+    /// Refresh `db_token` from `provider` whenever the connection has to be reopened
+    /// (e.g. after the token expired)
+    pub fn with_token_provider(mut self, provider: TokenProvider) -> Self {
+        self.token_provider = Some(provider);
+        self
+    }
+
+    /// Rows a streaming read fetches per round-trip (clamped to at least 1)
+    pub fn with_fetch_batch_size(mut self, rows: usize) -> Self {
+        self.fetch_batch_size = rows.max(1);
+        self
+    }
+
+    /// PRAGMA settings (e.g. `("cache_size", "-64000")`) run in order on every new
+    /// connection. Unknown pragma names fail `Database::init` rather than being ignored.
+    pub fn with_pragmas(mut self, pragmas: Vec<(String, String)>) -> Self {
+        self.pragmas = pragmas;
+        self
+    }
+}
+
+/// Fluent construction of a `DatabaseConfig`; `build` checks that the fields the
+/// chosen mode needs are set
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseConfigBuilder {
+    mode: Option<TursoMode>,
+    local_db_path: String,
+    db_url: String,
+    db_token: String,
+    busy_retries: u32,
+    interrupt_after: Option<Duration>,
+    fetch_batch_size: Option<usize>,
+    pragmas: Vec<(String, String)>,
+    token_provider: Option<TokenProvider>,
+}
+
+impl DatabaseConfig {
+    pub fn builder() -> DatabaseConfigBuilder {
+        DatabaseConfigBuilder::default()
+    }
+}
+
+impl DatabaseConfigBuilder {
+    pub fn mode(mut self, mode: TursoMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn local_db_path(mut self, path: impl Into<String>) -> Self {
+        self.local_db_path = path.into();
+        self
+    }
+
+    pub fn db_url(mut self, url: impl Into<String>) -> Self {
+        self.db_url = url.into();
+        self
+    }
+
+    pub fn db_token(mut self, token: impl Into<String>) -> Self {
+        self.db_token = token.into();
+        self
+    }
+
+    pub fn busy_retries(mut self, attempts: u32) -> Self {
+        self.busy_retries = attempts;
+        self
+    }
+
+    pub fn interrupt_after(mut self, timeout: Duration) -> Self {
+        self.interrupt_after = Some(timeout);
+        self
+    }
+
+    pub fn fetch_batch_size(mut self, rows: usize) -> Self {
+        self.fetch_batch_size = Some(rows.max(1));
+        self
+    }
+
+    pub fn pragma(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.pragmas.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn token_provider(mut self, provider: TokenProvider) -> Self {
+        self.token_provider = Some(provider);
+        self
+    }
+
+    pub fn build(self) -> Result<DatabaseConfig, Error> {
+        let mode = self
+            .mode
+            .ok_or_else(|| Error::Config("Database mode is required".to_string()))?;
+
+        let needs_path = matches!(mode, TursoMode::Local | TursoMode::Sync | TursoMode::Embed);
+        let needs_remote = matches!(mode, TursoMode::Remote | TursoMode::Sync | TursoMode::Embed);
+        if needs_path && self.local_db_path.is_empty() {
+            return Err(Error::Config(format!(
+                "{mode:?} mode requires a local database path"
+            )));
         }
+        if needs_remote && (self.db_url.is_empty() || self.db_token.is_empty()) {
+            return Err(Error::Config(format!(
+                "{mode:?} mode requires a database URL and token"
+            )));
+        }
+
+        Ok(DatabaseConfig {
+            mode,
+            local_db_path: self.local_db_path,
+            db_url: self.db_url,
+            db_token: self.db_token,
+            busy_retries: self.busy_retries,
+            interrupt_after: self.interrupt_after,
+            fetch_batch_size: self.fetch_batch_size.unwrap_or(DEFAULT_FETCH_BATCH_SIZE),
+            pragmas: self.pragmas,
+            token_provider: self.token_provider,
+        })
     }
 }
 
@@ -105,13 +386,71 @@ pub enum TursoMode {
 
 #[derive(Debug)]
 pub struct Database {
-    pub db: libsql::Database,
-    pub conn: libsql::Connection,
+    // Client and connection, replaced together when the connection is reopened
+    db: RwLock<Arc<LibsqlDatabase>>,
+    conn: RwLock<libsql::Connection>,
+    // Bumped by every reconnect, so callers that saw the same failure reopen only once
+    generation: AtomicU64,
+    // Token the current connection was opened with; refreshed by the token provider
+    db_token: RwLock<String>,
+    // Statements hold it shared; an open `Transaction` holds it exclusively, so statements
+    // from elsewhere wait for it to finish instead of running inside it
+    gate: tokio::sync::RwLock<()>,
     pub mode: TursoMode,
+    pub busy_retries: u32,
+    pub interrupt_after: Option<Duration>,
+    /// Configuration the connection was opened with, reused by `reconnect`
+    pub config: DatabaseConfig,
+    // Errors returned in place of the next statements' results, to simulate lost connections
+    #[cfg(test)]
+    injected_failures: std::sync::Mutex<Vec<libsql::Error>>,
     #[cfg(feature = "sqlite")]
     pub sqlite_conn: Option<Arc<Mutex<RusqliteConnection>>>,
 }
 
+/// Rows returned by `Database::query`. Dereferences to `libsql::Rows`; `next` also calls
+/// off the statement's `interrupt_after` deadline once the last row has been read.
+#[derive(Debug)]
+pub struct Rows {
+    inner: libsql::Rows,
+    interrupt: Option<InterruptGuard>,
+}
+
+impl Rows {
+    /// The next row, or `None` once the statement is done
+    pub async fn next(&mut self) -> Result<Option<libsql::Row>, libsql::Error> {
+        let row = self.inner.next().await;
+        if !matches!(row, Ok(Some(_))) {
+            self.interrupt = None;
+        }
+        row
+    }
+}
+
+impl std::ops::Deref for Rows {
+    type Target = libsql::Rows;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl std::ops::DerefMut for Rows {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+// Pending interrupt for one statement, aborted when the statement is done with
+#[derive(Debug)]
+struct InterruptGuard(tokio::task::JoinHandle<()>);
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 impl TursoMode {
     /// Returns the TursoMode based on the TURSO_MODE environment variable.
     pub fn from_env() -> Self {
@@ -132,16 +471,10 @@ impl TursoMode {
 
 impl Database {
     pub async fn init(config: DatabaseConfig) -> Result<Self> {
-        let db = Self::client(config.clone()).await?;
-        let conn = db.connect().map_err(|e| Error::Connection(e))?;
+        let (db, conn) = Self::open(&config).await?;
         let mode = config.mode.clone();
-
-        // Enable foreign key constraints for SQLite
-        conn.execute("PRAGMA foreign_keys = ON", ())
-            .await
-            .map_err(|e| Error::Connection(e))?;
-
-        debug!("Turso database connection established with foreign keys enabled");
+        let busy_retries = config.busy_retries;
+        let interrupt_after = config.interrupt_after;
         
         #[cfg(feature = "sqlite")]
         let sqlite_conn = if matches!(config.mode, TursoMode::Local) && cfg!(feature = "sqlite") {
@@ -151,15 +484,188 @@ impl Database {
             None
         };
 
-        Ok(Self { 
-            db, 
-            conn, 
+        Ok(Self {
+            db: RwLock::new(Arc::new(db)),
+            conn: RwLock::new(conn),
+            generation: AtomicU64::new(0),
+            db_token: RwLock::new(config.db_token.clone()),
+            gate: tokio::sync::RwLock::new(()),
             mode,
+            busy_retries,
+            interrupt_after,
+            config,
+            #[cfg(test)]
+            injected_failures: std::sync::Mutex::new(Vec::new()),
             #[cfg(feature = "sqlite")]
             sqlite_conn,
         })
     }
     
+    // Build the client and its connection, with foreign keys enabled
+    async fn open(config: &DatabaseConfig) -> Result<(LibsqlDatabase, libsql::Connection), Error> {
+        let db = Self::client(config.clone()).await?;
+        let conn = db.connect().map_err(|e| Error::Connection(e))?;
+
+        // Enable foreign key constraints for SQLite
+        conn.execute("PRAGMA foreign_keys = ON", ())
+            .await
+            .map_err(|e| Error::Connection(e))?;
+
+        for (name, value) in &config.pragmas {
+            let sql = Self::pragma_sql(name, value)?;
+            // Some pragmas (journal_mode, ...) answer with a row, which execute rejects
+            conn.query(&sql, ()).await?;
+            debug!(pragma = %name, value = %value, "Applied pragma");
+        }
+
+        debug!("Turso database connection established with foreign keys enabled");
+        Ok((db, conn))
+    }
+
+    // Checked `PRAGMA name = value`; values are limited to plain words and numbers since
+    // they can't be bound as parameters
+    fn pragma_sql(name: &str, value: &str) -> Result<String, Error> {
+        let name = name.trim().to_ascii_lowercase();
+        if !KNOWN_PRAGMAS.contains(&name.as_str()) {
+            return Err(Error::Config(format!("Unknown pragma '{name}'")));
+        }
+        let value = value.trim();
+        let plain = !value.is_empty()
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !plain {
+            return Err(Error::Config(format!(
+                "Invalid value '{value}' for pragma '{name}'"
+            )));
+        }
+        Ok(format!("PRAGMA {name} = {value}"))
+    }
+
+    /// The current connection. Clones share it; after a reconnect this returns the new one.
+    pub fn conn(&self) -> libsql::Connection {
+        self.conn
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// The token the current connection was opened with
+    pub fn db_token(&self) -> String {
+        self.db_token
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replace the client and connection with fresh ones built from `config`, fetching a
+    /// new token first when a token provider is configured. `query`, `execute` and
+    /// `execute_write` call this themselves when the connection is lost.
+    pub async fn reconnect(&self) -> Result<(), Error> {
+        let _gate = self.gate.read().await;
+        self.reconnect_from(self.generation.load(Ordering::SeqCst))
+            .await
+    }
+
+    // Reconnect unless someone else already has since `generation` was read
+    async fn reconnect_from(&self, generation: u64) -> Result<(), Error> {
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return Ok(());
+        }
+        let mut config = self.config.clone();
+        config.db_token = self.db_token();
+        if let Some(provider) = &config.token_provider {
+            config.db_token = provider.token().await?;
+            debug!("Refreshed database token");
+        }
+        let (db, conn) = Self::open(&config).await?;
+        if self
+            .generation
+            .compare_exchange(
+                generation,
+                generation + 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .is_err()
+        {
+            return Ok(());
+        }
+        *self
+            .db
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(db);
+        *self
+            .conn
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = conn;
+        *self
+            .db_token
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = config.db_token;
+        debug!("Database reconnected");
+        Ok(())
+    }
+
+    // Run `statement` on the current connection. If it fails because the connection is
+    // gone, reopen it and run the statement once more, unless a transaction was open on
+    // the lost connection (its earlier statements are gone with it). A write whose result
+    // was lost in transit may therefore be applied twice.
+    async fn on_connection<T, F, Fut>(
+        &self,
+        mut statement: F,
+    ) -> Result<(T, Option<InterruptGuard>), libsql::Error>
+    where
+        F: FnMut(libsql::Connection) -> Fut,
+        Fut: std::future::Future<Output = Result<T, libsql::Error>>,
+    {
+        let _gate = self.gate.read().await;
+        let generation = self.generation.load(Ordering::SeqCst);
+        let conn = self.conn();
+        let in_transaction = !conn.is_autocommit();
+        let interrupt = self.arm_interrupt(&conn);
+        let result = match self.injected_failure() {
+            Some(err) => Err(err),
+            None => statement(conn).await,
+        };
+        match result {
+            Err(e) if !in_transaction && crate::error::is_connection_failure(&e) => {
+                drop(interrupt);
+                debug!(error = %e, "Connection lost, reconnecting");
+                if let Err(reconnect_err) = self.reconnect_from(generation).await {
+                    debug!(error = %reconnect_err, "Reconnect failed");
+                    return Err(e);
+                }
+                let conn = self.conn();
+                let interrupt = self.arm_interrupt(&conn);
+                Ok((statement(conn).await?, interrupt))
+            }
+            result => Ok((result?, interrupt)),
+        }
+    }
+
+    #[cfg(test)]
+    fn injected_failure(&self) -> Option<libsql::Error> {
+        self.injected_failures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop()
+    }
+
+    #[cfg(not(test))]
+    fn injected_failure(&self) -> Option<libsql::Error> {
+        None
+    }
+
+    // Make the next statement fail with `err` as if it came from the connection
+    #[cfg(test)]
+    pub(crate) fn inject_failure(&self, err: libsql::Error) {
+        self.injected_failures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(0, err);
+    }
+
     // Initialize Turso client with ConfigManager integration - uses defaults if not in config.yaml
     async fn client(config: DatabaseConfig) -> Result<LibsqlDatabase, Error> {
         let local_db_path = config.local_db_path;
@@ -168,6 +674,14 @@ impl Database {
         let mode = config.mode;
 
         let db = match mode {
+            // A name selects a shared-cache database (see `DatabaseConfig::memory_shared`)
+            TursoMode::Memory if !local_db_path.is_empty() => {
+                let uri = format!("file:{local_db_path}?mode=memory&cache=shared");
+                Builder::new_local(uri)
+                    .build()
+                    .await
+                    .map_err(Error::Connection)?
+            }
             TursoMode::Memory => Builder::new_local(":memory:")
                 .build()
                 .await
@@ -194,7 +708,12 @@ impl Database {
 
     pub async fn sync(&self) -> Result<()> {
         if self.mode == TursoMode::Sync {
-            self.db.sync().await.map_err(|e| Error::Connection(e))?;
+            let db = self
+                .db
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone();
+            db.sync().await.map_err(|e| Error::Connection(e))?;
             debug!("Turso database synced successfully");
         }
         Ok(())
@@ -205,11 +724,241 @@ impl Database {
         sql: &str,
         params: Vec<libsql::Value>,
     ) -> Result<Rows, libsql::Error> {
-        self.conn.query(sql, params).await
+        let log = QueryLog::start(sql, &params);
+        let result = self
+            .on_connection(|conn| {
+                let params = params.clone();
+                async move { conn.query(sql, params).await }
+            })
+            .instrument(log.span())
+            .await
+            .map(|(inner, interrupt)| Rows { inner, interrupt });
+        log.finish(result)
     }
 
     pub async fn execute(&self, sql: &str) -> Result<u64, libsql::Error> {
-        self.conn.execute(sql, ()).await
+        let log = QueryLog::start(sql, &[]);
+        let result = self
+            .on_connection(|conn| async move { conn.execute(sql, ()).await })
+            .instrument(log.span())
+            .await
+            .map(|(changed, _)| changed);
+        log.finish(result)
+    }
+
+    // Schedule an interrupt `interrupt_after` from now, called off when the returned guard
+    // is dropped. The guard belongs to one statement: rows keep it until they're exhausted.
+    fn arm_interrupt(&self, conn: &libsql::Connection) -> Option<InterruptGuard> {
+        let timeout = self.interrupt_after?;
+        let conn = conn.clone();
+        Some(InterruptGuard(tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            debug!("Statement exceeded interrupt_after, interrupting");
+            let _ = conn.interrupt();
+        })))
+    }
+
+    /// Whether an error is SQLite reporting the database as busy or locked
+    pub fn is_busy_error(err: &libsql::Error) -> bool {
+        match err {
+            libsql::Error::SqliteFailure(code, _) => {
+                matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED)
+            }
+            other => other.to_string().contains("database is locked"),
+        }
+    }
+
+    /// Execute a write statement, retrying on SQLITE_BUSY/SQLITE_LOCKED up to `busy_retries` times
+    pub async fn execute_write(
+        &self,
+        sql: &str,
+        params: Vec<libsql::Value>,
+    ) -> Result<u64, libsql::Error> {
+        let log = QueryLog::start(sql, &params);
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .on_connection(|conn| {
+                    let params = params.clone();
+                    async move { conn.execute(sql, params).await }
+                })
+                .instrument(log.span())
+                .await
+                .map(|(changed, _)| changed);
+            match result {
+                Err(e) if attempt < self.busy_retries && Self::is_busy_error(&e) => {
+                    attempt += 1;
+                    debug!(attempt, "Database busy, retrying write");
+                    tokio::time::sleep(BUSY_RETRY_DELAY * attempt).await;
+                }
+                result => return log.finish(result),
+            }
+        }
+    }
+
+    /// Execute a batch of write statements with the same busy retry and reconnect as
+    /// `execute_write`
+    pub async fn execute_batch_write(&self, sql: &str) -> Result<(), libsql::Error> {
+        let log = QueryLog::start(sql, &[]);
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .on_connection(|conn| async move {
+                    let in_transaction = !conn.is_autocommit();
+                    let result = conn.execute_batch(sql).await.map(|_| ());
+                    // Don't leave a transaction opened by a busy batch behind for the retry
+                    if let Err(e) = &result {
+                        if Self::is_busy_error(e) && !in_transaction && !conn.is_autocommit() {
+                            conn.execute("ROLLBACK", ()).await?;
+                        }
+                    }
+                    result
+                })
+                .instrument(log.span())
+                .await
+                .map(|((), _)| ());
+            match result {
+                Err(e) if attempt < self.busy_retries && Self::is_busy_error(&e) => {
+                    attempt += 1;
+                    debug!(attempt, "Database busy, retrying batch");
+                    tokio::time::sleep(BUSY_RETRY_DELAY * attempt).await;
+                }
+                result => return log.finish(result),
+            }
+        }
+    }
+
+    /// Run a query returning a single value, e.g. `SELECT MAX(created_at) FROM users`,
+    /// converted to `T`. `None` when there is no row or the value is NULL; more than one
+    /// column or row is an error.
+    pub async fn query_scalar<T: FromValue>(
+        &self,
+        sql: &str,
+        params: Vec<libsql::Value>,
+    ) -> Result<Option<T>, Error> {
+        let mut rows = self.query(sql, params).await?;
+        let Some(row) = rows.next().await? else {
+            return Ok(None);
+        };
+        if row.column_count() != 1 {
+            return Err(Error::Query(format!(
+                "query_scalar expects one column, got {}",
+                row.column_count()
+            )));
+        }
+        let value = crate::Utils::libsql_value_to_value(&row.get_value(0)?);
+        let column = row.column_name(0).unwrap_or_default().to_string();
+        if rows.next().await?.is_some() {
+            return Err(Error::Query(
+                "query_scalar expects one row, got several".to_string(),
+            ));
+        }
+
+        Option::<T>::from_value(&value).map_err(|message| Error::FieldDeserialization {
+            field: column,
+            value,
+            message,
+        })
+    }
+
+    /// Begin a deferred transaction
+    pub async fn transaction(&self) -> Result<Transaction<'_>, Error> {
+        self.transaction_with_mode(TransactionMode::Deferred).await
+    }
+
+    /// Begin a read transaction: a deferred transaction whose snapshot is taken right away,
+    /// so every finder run through `tx.db()` sees the database as of this call until the
+    /// handle is committed or dropped. Writes made meanwhile by other connections stay
+    /// invisible to it; in WAL mode they go ahead, in rollback-journal mode they wait for
+    /// the snapshot to close. Statements through this `Database` itself wait, as for any
+    /// transaction.
+    pub async fn read_transaction(&self) -> Result<Transaction<'_>, Error> {
+        let tx = self
+            .transaction_with_mode(TransactionMode::Deferred)
+            .await?;
+        // A deferred BEGIN takes no lock; the first read is what starts the snapshot
+        let mut rows = tx
+            .db()
+            .query("SELECT count(*) FROM sqlite_schema", vec![])
+            .await?;
+        while rows.next().await?.is_some() {}
+        Ok(tx)
+    }
+
+    /// Begin a transaction with the given `BEGIN` mode, retrying on SQLITE_BUSY/SQLITE_LOCKED
+    /// up to `busy_retries` times (`Immediate` waits here for other writers to finish).
+    ///
+    /// The transaction has the connection to itself: until it is committed, rolled back or
+    /// dropped, statements run through this `Database` (rather than `tx.db()`) wait, as
+    /// does beginning another transaction. Running them from the task holding the
+    /// transaction therefore never returns.
+    pub async fn transaction_with_mode(
+        &self,
+        mode: TransactionMode,
+    ) -> Result<Transaction<'_>, Error> {
+        let exclusive = self.gate.write().await;
+        let mut attempt = 0;
+        loop {
+            let conn = self.conn();
+            match conn.transaction_with_behavior(mode.into()).await {
+                Err(e) if attempt < self.busy_retries && Self::is_busy_error(&e) => {
+                    attempt += 1;
+                    debug!(attempt, "Database busy, retrying BEGIN");
+                    tokio::time::sleep(BUSY_RETRY_DELAY * attempt).await;
+                }
+                result => {
+                    let scope = self.transaction_scope(conn);
+                    return Ok(Transaction::new(exclusive, scope, result?, mode));
+                }
+            }
+        }
+    }
+
+    // The database as seen from inside a transaction on `conn`: same settings, but
+    // statements skip the gate the transaction holds
+    fn transaction_scope(&self, conn: libsql::Connection) -> Database {
+        Database {
+            db: RwLock::new(
+                self.db
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .clone(),
+            ),
+            conn: RwLock::new(conn),
+            generation: AtomicU64::new(self.generation.load(Ordering::SeqCst)),
+            db_token: RwLock::new(self.db_token()),
+            gate: tokio::sync::RwLock::new(()),
+            mode: self.mode.clone(),
+            busy_retries: self.busy_retries,
+            interrupt_after: self.interrupt_after,
+            config: self.config.clone(),
+            #[cfg(test)]
+            injected_failures: std::sync::Mutex::new(Vec::new()),
+            #[cfg(feature = "sqlite")]
+            sqlite_conn: self.sqlite_conn.clone(),
+        }
+    }
+
+    /// Run `EXPLAIN QUERY PLAN` for a statement and return the plan, one step per line,
+    /// indented by nesting depth.
+    pub async fn explain(&self, sql: &str, params: Vec<libsql::Value>) -> Result<String, Error> {
+        let mut rows = self
+            .query(&format!("EXPLAIN QUERY PLAN {sql}"), params)
+            .await?;
+
+        let mut depths: std::collections::HashMap<i64, usize> = std::collections::HashMap::new();
+        let mut lines = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let id: i64 = row.get(0)?;
+            let parent: i64 = row.get(1)?;
+            let detail: String = row.get(3)?;
+
+            let depth = depths.get(&parent).map_or(0, |d| d + 1);
+            depths.insert(id, depth);
+            lines.push(format!("{}{}", "  ".repeat(depth), detail));
+        }
+
+        Ok(lines.join("\n"))
     }
     
     // New method for SQLite operations
@@ -1,73 +1,147 @@
 // Error handling
 
-use std::fmt;
-
-// Custom error type
-#[derive(Debug)]
+// Custom error type. Errors converted from libsql, serde_json, anyhow (codec) and I/O
+// keep the original as their `source()`.
+#[derive(Debug, thiserror::Error)]
 pub enum Error {
     /// Database connection error
-    Connection(libsql::Error),
+    #[error("Connection error: {0}")]
+    Connection(#[source] libsql::Error),
     /// SQL execution error
-    Sql(String),
+    #[error("SQL error: {0}")]
+    Sql(#[source] libsql::Error),
     /// Serialization/deserialization error
+    #[error("Serialization error: {0}")]
     Serialization(String),
+    /// JSON encoding/decoding error
+    #[error("Serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A column value that doesn't fit its struct field
+    #[error("Serialization error: field `{field}` can't be read from {value:?}: {message}")]
+    FieldDeserialization {
+        field: String,
+        value: crate::Value,
+        message: String,
+    },
     /// Validation error
+    #[error("Validation error: {0}")]
     Validation(String),
     /// Not found error
+    #[error("Not found: {0}")]
     NotFound(String),
     /// Pagination error
+    #[error("Pagination error: {0}")]
     Pagination(String),
     /// Query building error
+    #[error("Query error: {0}")]
     Query(String),
-    /// Worker environment error
-    AnyhowError(String),
+    /// Codec (compression) error
+    #[error("Anyhow error: {0}")]
+    AnyhowError(#[from] anyhow::Error),
     /// Database error
+    #[error("Database error: {0}")]
     DatabaseError(String),
+    /// I/O error, e.g. from an export writer or import reader
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
     /// Generic error
+    #[error("Error: {0}")]
     Generic(String),
     /// Configuration error
+    #[error("Configuration error: {0}")]
     Config(String),
     /// Operations error
+    #[error("Operations error: {0}")]
     Operations(String),
+    /// Statement interrupted after exceeding `interrupt_after`
+    #[error("Statement interrupted")]
+    Interrupted,
+    /// A write hit a UNIQUE or PRIMARY KEY constraint; `column` is the constrained
+    /// column (comma-separated for a composite key)
+    #[error("Unique constraint violated on {column}")]
+    UniqueViolation {
+        column: String,
+        #[source]
+        source: libsql::Error,
+    },
 }
 
-impl std::error::Error for Error {}
-
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            Error::Connection(msg) => write!(f, "Connection error: {msg}"),
-            Error::Sql(msg) => write!(f, "SQL error: {msg}"),
-            Error::Serialization(msg) => write!(f, "Serialization error: {msg}"),
-            Error::Validation(msg) => write!(f, "Validation error: {msg}"),
-            Error::NotFound(msg) => write!(f, "Not found: {msg}"),
-            Error::Pagination(msg) => write!(f, "Pagination error: {msg}"),
-            Error::Query(msg) => write!(f, "Query error: {msg}"),
-            Error::AnyhowError(msg) => write!(f, "Anyhow error: {msg}"),
-            Error::DatabaseError(msg) => write!(f, "Database error: {msg}"),
-            Error::Generic(msg) => write!(f, "Error: {msg}"),
-            Error::Config(msg) => write!(f, "Configuration error: {}", msg),
-            Error::Operations(msg) => write!(f, "Operations error: {}", msg),
-        }
+impl Error {
+    /// Whether the error means the connection itself is unusable (dropped stream,
+    /// failed connect, rejected token), as opposed to a problem with the statement
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, Error::Connection(_))
     }
 }
 
 impl From<libsql::Error> for Error {
     fn from(err: libsql::Error) -> Self {
-        Error::Sql(err.to_string())
+        match err {
+            // SQLITE_INTERRUPT
+            libsql::Error::SqliteFailure(code, _) if code & 0xff == 9 => Error::Interrupted,
+            // SQLITE_CONSTRAINT, reported by SQLite for UNIQUE and PRIMARY KEY alike
+            libsql::Error::SqliteFailure(code, ref message)
+            | libsql::Error::RemoteSqliteFailure(code, _, ref message)
+                if code & 0xff == 19 && unique_columns(message).is_some() =>
+            {
+                Error::UniqueViolation {
+                    column: unique_columns(message).unwrap_or_default(),
+                    source: err,
+                }
+            }
+            // Remote servers report statement errors as Hrana stream errors carrying the
+            // SQLite message
+            libsql::Error::Hrana(ref e) if unique_columns(&e.to_string()).is_some() => {
+                Error::UniqueViolation {
+                    column: unique_columns(&e.to_string()).unwrap_or_default(),
+                    source: err,
+                }
+            }
+            err if is_connection_failure(&err) => Error::Connection(err),
+            err => Error::Sql(err),
+        }
     }
 }
 
-impl From<serde_json::Error> for Error {
-    fn from(err: serde_json::Error) -> Self {
-        Error::Serialization(err.to_string())
+// Whether the connection itself is unusable, so reopening it may help
+pub(crate) fn is_connection_failure(err: &libsql::Error) -> bool {
+    match err {
+        libsql::Error::ConnectionFailed(_) => true,
+        // Remote transport failures; statement errors also arrive as Hrana stream errors
+        libsql::Error::Hrana(e) => {
+            let message = e.to_string();
+            ["stream closed", "http error"]
+                .iter()
+                .any(|kind| message.starts_with(kind))
+                // The server rejected the token: "api error: `status=401, body=...`"
+                || rejected_token(&message)
+        }
+        _ => false,
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(err: std::io::Error) -> Self {
-        Error::Generic(err.to_string())
-    }
+// HTTP 401/403 from the Hrana API, which a fresh token may fix
+fn rejected_token(message: &str) -> bool {
+    message
+        .strip_prefix("api error: `")
+        .is_some_and(|rest| rest.starts_with("status=401") || rest.starts_with("status=403"))
+}
+
+// Columns named by "UNIQUE constraint failed: users.tenant, users.email", without the table;
+// a Hrana stream error quotes the message, so the list ends at the closing quote
+fn unique_columns(message: &str) -> Option<String> {
+    let (_, columns) = message.split_once("UNIQUE constraint failed: ")?;
+    let columns = columns
+        .split(['"', '`', '\\', '\n'])
+        .next()
+        .unwrap_or_default();
+    Some(
+        columns
+            .split(", ")
+            .map(|column| column.rsplit_once('.').map_or(column, |(_, name)| name))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
 }
 
 impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
@@ -76,10 +150,4 @@ impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
     }
 }
 
-impl From<anyhow::Error> for Error {
-    fn from(err: anyhow::Error) -> Self {
-        Error::AnyhowError(err.to_string())
-    }
-}
-
 pub type Result<T> = std::result::Result<T, Error>;
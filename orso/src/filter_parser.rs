@@ -0,0 +1,380 @@
+// Parser for the small filter expression language behind `FilterOperator::parse`
+use crate::{Error, Filter, FilterOperator, FilterValue, Operator, Result, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Integer(i64),
+    Real(f64),
+    Symbol(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    End,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{word}`"),
+            Token::Text(text) => write!(f, "'{text}'"),
+            Token::Integer(i) => write!(f, "{i}"),
+            Token::Real(r) => write!(f, "{r}"),
+            Token::Symbol(symbol) => write!(f, "`{symbol}`"),
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+            Token::Comma => write!(f, "`,`"),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+// Deepest nesting of parentheses and NOTs accepted, so a hostile expression can't
+// exhaust the stack
+const MAX_DEPTH: usize = 64;
+
+fn parse_error(position: usize, message: impl std::fmt::Display) -> Error {
+    Error::Query(format!(
+        "Filter parse error at position {position}: {message}"
+    ))
+}
+
+// Split the expression into tokens, each paired with its byte offset
+fn tokenize(expr: &str) -> Result<Vec<(Token, usize)>> {
+    let bytes = expr.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+        match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                pos += 1;
+                continue;
+            }
+            b'(' => {
+                tokens.push((Token::LParen, start));
+                pos += 1;
+            }
+            b')' => {
+                tokens.push((Token::RParen, start));
+                pos += 1;
+            }
+            b',' => {
+                tokens.push((Token::Comma, start));
+                pos += 1;
+            }
+            b'=' => {
+                tokens.push((Token::Symbol("="), start));
+                pos += 1;
+            }
+            b'!' | b'<' | b'>' => {
+                let two = expr.get(pos..pos + 2).unwrap_or("");
+                let symbol = match two {
+                    "!=" => "!=",
+                    "<>" => "<>",
+                    "<=" => "<=",
+                    ">=" => ">=",
+                    _ if c == b'<' => "<",
+                    _ if c == b'>' => ">",
+                    _ => return Err(parse_error(start, "unexpected `!`")),
+                };
+                tokens.push((Token::Symbol(symbol), start));
+                pos += symbol.len();
+            }
+            b'\'' => {
+                // Single-quoted text; '' is an escaped quote
+                let mut text = String::new();
+                pos += 1;
+                loop {
+                    match expr[pos..].find('\'') {
+                        Some(offset) => {
+                            text.push_str(&expr[pos..pos + offset]);
+                            pos += offset + 1;
+                            if bytes.get(pos) == Some(&b'\'') {
+                                text.push('\'');
+                                pos += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                        None => return Err(parse_error(start, "unterminated string literal")),
+                    }
+                }
+                tokens.push((Token::Text(text), start));
+            }
+            b'0'..=b'9' | b'-' | b'.' => {
+                pos += 1;
+                while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                    pos += 1;
+                }
+                let literal = &expr[start..pos];
+                let token = if let Ok(i) = literal.parse::<i64>() {
+                    Token::Integer(i)
+                } else if let Ok(r) = literal.parse::<f64>() {
+                    Token::Real(r)
+                } else {
+                    return Err(parse_error(start, format!("invalid number `{literal}`")));
+                };
+                tokens.push((token, start));
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                tokens.push((Token::Word(expr[start..pos].to_string()), start));
+            }
+            _ => {
+                let ch = expr[start..].chars().next().unwrap_or_default();
+                return Err(parse_error(start, format!("unexpected character `{ch}`")));
+            }
+        }
+    }
+
+    tokens.push((Token::End, expr.len()));
+    Ok(tokens)
+}
+
+/// Recursive-descent parser; OR binds loosest, then AND, then NOT
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    columns: &'a [&'a str],
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.index].1
+    }
+
+    fn advance(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.index].clone();
+        if self.index < self.tokens.len() - 1 {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.at_keyword(keyword) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(parse_error(
+                self.position(),
+                format!("expected {keyword}, found {}", self.peek()),
+            ))
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        if *self.peek() == expected {
+            self.advance();
+            Ok(())
+        } else {
+            Err(parse_error(
+                self.position(),
+                format!("expected {expected}, found {}", self.peek()),
+            ))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<FilterOperator> {
+        let mut terms = vec![self.parse_and()?];
+        while self.eat_keyword("OR") {
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            FilterOperator::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<FilterOperator> {
+        let mut terms = vec![self.parse_unary()?];
+        while self.eat_keyword("AND") {
+            terms.push(self.parse_unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            FilterOperator::And(terms)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterOperator> {
+        if !self.at_keyword("NOT") && *self.peek() != Token::LParen {
+            return self.parse_condition();
+        }
+        if self.depth == MAX_DEPTH {
+            return Err(parse_error(
+                self.position(),
+                format!("expression nested deeper than {MAX_DEPTH} levels"),
+            ));
+        }
+        self.depth += 1;
+        let filter = if self.eat_keyword("NOT") {
+            FilterOperator::Not(Box::new(self.parse_unary()?))
+        } else {
+            self.advance();
+            let inner = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            inner
+        };
+        self.depth -= 1;
+        Ok(filter)
+    }
+
+    fn parse_condition(&mut self) -> Result<FilterOperator> {
+        let (token, position) = self.advance();
+        let column = match token {
+            Token::Word(word) => word,
+            other => {
+                return Err(parse_error(
+                    position,
+                    format!("expected a column, found {other}"),
+                ))
+            }
+        };
+        if !self.columns.contains(&column.as_str()) {
+            return Err(parse_error(position, format!("unknown column `{column}`")));
+        }
+
+        let position = self.position();
+        let filter = match self.advance().0 {
+            Token::Symbol(symbol) => {
+                let operator = match symbol {
+                    "=" => Operator::Eq,
+                    "!=" | "<>" => Operator::Ne,
+                    "<" => Operator::Lt,
+                    "<=" => Operator::Le,
+                    ">" => Operator::Gt,
+                    _ => Operator::Ge,
+                };
+                Filter::new(column, operator, FilterValue::Single(self.parse_literal()?))
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("IS") => {
+                if self.eat_keyword("NOT") {
+                    self.expect_keyword("NULL")?;
+                    Filter::is_not_null(column)
+                } else {
+                    self.expect_keyword("NULL")?;
+                    Filter::is_null(column)
+                }
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("NOT") => {
+                let position = self.position();
+                match self.advance().0 {
+                    Token::Word(word) if word.eq_ignore_ascii_case("LIKE") => Filter::new(
+                        column,
+                        Operator::NotLike,
+                        FilterValue::Single(self.parse_literal()?),
+                    ),
+                    Token::Word(word) if word.eq_ignore_ascii_case("IN") => {
+                        Filter::new(column, Operator::NotIn, self.parse_list()?)
+                    }
+                    Token::Word(word) if word.eq_ignore_ascii_case("BETWEEN") => {
+                        Filter::new(column, Operator::NotBetween, self.parse_range()?)
+                    }
+                    other => {
+                        return Err(parse_error(
+                            position,
+                            format!("expected LIKE, IN or BETWEEN after NOT, found {other}"),
+                        ))
+                    }
+                }
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("LIKE") => Filter::new(
+                column,
+                Operator::Like,
+                FilterValue::Single(self.parse_literal()?),
+            ),
+            Token::Word(word) if word.eq_ignore_ascii_case("IN") => {
+                Filter::new(column, Operator::In, self.parse_list()?)
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("BETWEEN") => {
+                Filter::new(column, Operator::Between, self.parse_range()?)
+            }
+            other => {
+                return Err(parse_error(
+                    position,
+                    format!("expected an operator, found {other}"),
+                ))
+            }
+        };
+        Ok(FilterOperator::Single(filter))
+    }
+
+    fn parse_literal(&mut self) -> Result<Value> {
+        let (token, position) = self.advance();
+        match token {
+            Token::Text(text) => Ok(Value::Text(text)),
+            Token::Integer(i) => Ok(Value::Integer(i)),
+            Token::Real(r) => Ok(Value::Real(r)),
+            Token::Word(word) if word.eq_ignore_ascii_case("TRUE") => Ok(Value::Boolean(true)),
+            Token::Word(word) if word.eq_ignore_ascii_case("FALSE") => Ok(Value::Boolean(false)),
+            Token::Word(word) if word.eq_ignore_ascii_case("NULL") => Ok(Value::Null),
+            other => Err(parse_error(
+                position,
+                format!("expected a value, found {other}"),
+            )),
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<FilterValue> {
+        self.expect(Token::LParen)?;
+        let mut values = vec![self.parse_literal()?];
+        while *self.peek() == Token::Comma {
+            self.advance();
+            values.push(self.parse_literal()?);
+        }
+        self.expect(Token::RParen)?;
+        Ok(FilterValue::Multiple(values))
+    }
+
+    fn parse_range(&mut self) -> Result<FilterValue> {
+        let min = self.parse_literal()?;
+        self.expect_keyword("AND")?;
+        let max = self.parse_literal()?;
+        Ok(FilterValue::Range(min, max))
+    }
+}
+
+pub(crate) fn parse(expr: &str, columns: &[&str]) -> Result<FilterOperator> {
+    let mut parser = Parser {
+        tokens: tokenize(expr)?,
+        index: 0,
+        columns,
+        depth: 0,
+    };
+    let filter = parser.parse_or()?;
+    if *parser.peek() != Token::End {
+        return Err(parse_error(
+            parser.position(),
+            format!("unexpected {}", parser.peek()),
+        ));
+    }
+    Ok(filter)
+}
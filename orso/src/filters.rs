@@ -34,6 +34,34 @@ pub enum FilterValue {
     Multiple(Vec<Value>),
     /// Range values (for BETWEEN, NOT BETWEEN operators)
     Range(Value, Value),
+    /// Values selected by a subquery (for IN, NOT IN operators)
+    Subquery(Subquery),
+}
+
+/// A single-column `SELECT` on another table, used as the right-hand side of IN/NOT IN
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subquery {
+    /// Table to select from
+    pub table: String,
+    /// Column to select
+    pub column: String,
+    /// Optional filter on the subquery table
+    pub filter: Option<Box<FilterOperator>>,
+}
+
+impl Subquery {
+    /// Create a subquery selecting `column` from `table`
+    pub fn new(
+        table: impl Into<String>,
+        column: impl Into<String>,
+        filter: Option<FilterOperator>,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            column: column.into(),
+            filter: filter.map(Box::new),
+        }
+    }
 }
 
 impl Filter {
@@ -107,6 +135,57 @@ impl Filter {
         )
     }
 
+    /// Create a LIKE filter from a user-typed glob: `*` matches any run of characters and
+    /// `?` a single one, while `%`, `_` and `\` in the input are matched literally
+    pub fn new_wildcard(column: impl Into<String>, pattern: &str) -> Self {
+        let mut like = String::with_capacity(pattern.len() + 4);
+        for c in pattern.chars() {
+            match c {
+                '*' => like.push('%'),
+                '?' => like.push('_'),
+                '%' | '_' | '\\' => {
+                    like.push('\\');
+                    like.push(c);
+                }
+                c => like.push(c),
+            }
+        }
+        Self::new(
+            column,
+            Operator::LikeEscaped,
+            FilterValue::Single(Value::Text(like)),
+        )
+    }
+
+    /// OR of several comparisons on one column, e.g. `age = 30 OR age > 50` from
+    /// `[(Eq, 30), (Gt, 50)]`. No conditions matches no rows. IN and BETWEEN take more
+    /// than one value and are rejected; use `in_values` or `between` for those.
+    pub fn any_of(
+        column: impl Into<String>,
+        conditions: Vec<(Operator, impl Into<Value>)>,
+    ) -> Result<FilterOperator> {
+        let column = column.into();
+        if conditions.is_empty() {
+            return Ok(FilterOperator::Custom("0".to_string()));
+        }
+        conditions
+            .into_iter()
+            .map(|(operator, value)| match operator {
+                Operator::In | Operator::NotIn | Operator::Between | Operator::NotBetween => {
+                    Err(crate::Error::Validation(format!(
+                        "any_of on {column} takes single-value comparisons, not {operator}"
+                    )))
+                }
+                _ => Ok(FilterOperator::Single(Self::new_simple(
+                    column.clone(),
+                    operator,
+                    value,
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(FilterOperator::Or)
+    }
+
     /// Create an IN filter
     pub fn in_values(column: impl Into<String>, values: Vec<impl Into<Value>>) -> Self {
         let values = values.into_iter().map(|v| v.into()).collect();
@@ -119,6 +198,16 @@ impl Filter {
         Self::new(column, Operator::NotIn, FilterValue::Multiple(values))
     }
 
+    /// Create an IN filter against a subquery
+    pub fn new_in_subquery(column: impl Into<String>, subquery: Subquery) -> Self {
+        Self::new(column, Operator::In, FilterValue::Subquery(subquery))
+    }
+
+    /// Create a NOT IN filter against a subquery
+    pub fn new_not_in_subquery(column: impl Into<String>, subquery: Subquery) -> Self {
+        Self::new(column, Operator::NotIn, FilterValue::Subquery(subquery))
+    }
+
     /// Create an IS NULL filter
     pub fn is_null(column: impl Into<String>) -> Self {
         Self::new(column, Operator::IsNull, FilterValue::Single(Value::Null))
@@ -176,6 +265,16 @@ impl FilterOperator {
         FilterOperator::Not(Box::new(filter))
     }
 
+    /// Parse a filter expression such as `age > 30 AND name LIKE '%bob%'`.
+    ///
+    /// Supports `=`, `!=`/`<>`, `<`, `<=`, `>`, `>=`, `[NOT] LIKE`, `[NOT] IN (...)`,
+    /// `[NOT] BETWEEN .. AND ..`, `IS [NOT] NULL`, `AND`/`OR`/`NOT` and parentheses, with
+    /// quoted text, numbers, `TRUE`/`FALSE` and `NULL` as values. Columns must be listed in
+    /// `model_columns`; errors report the byte position of the offending token.
+    pub fn parse(expr: &str, model_columns: &[&str]) -> Result<FilterOperator> {
+        crate::filter_parser::parse(expr, model_columns)
+    }
+
     /// Add a filter to an AND group
     pub fn and_with(self, other: FilterOperator) -> Self {
         match self {
@@ -416,6 +515,9 @@ impl FilterOperations {
                     FilterValue::Single(value) => {
                         sql.push('?');
                         params.push(Utils::value_to_libsql_value(value));
+                        if matches!(filter.operator, Operator::LikeEscaped) {
+                            sql.push_str(" ESCAPE '\\'");
+                        }
                     }
                     FilterValue::Multiple(values) => {
                         sql.push('(');
@@ -433,6 +535,18 @@ impl FilterOperations {
                         params.push(Utils::value_to_libsql_value(min));
                         params.push(Utils::value_to_libsql_value(max));
                     }
+                    FilterValue::Subquery(subquery) => {
+                        sql.push_str(&format!(
+                            "(SELECT {} FROM {}",
+                            subquery.column, subquery.table
+                        ));
+                        if let Some(inner) = &subquery.filter {
+                            let (inner_sql, inner_params) = Self::build_filter_operator(inner)?;
+                            sql.push_str(&format!(" WHERE {inner_sql}"));
+                            params.extend(inner_params);
+                        }
+                        sql.push(')');
+                    }
                 }
             }
         }
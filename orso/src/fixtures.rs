@@ -0,0 +1,24 @@
+// Test data helpers, compiled only with the `testing` feature
+use crate::operations::CrudOperations;
+use crate::{Database, Orso, Result};
+
+/// Seeds and clears tables so tests don't repeat insert/cleanup boilerplate
+pub struct Fixtures;
+
+impl Fixtures {
+    /// Create the model's table if it's missing, then insert `records` in one batch
+    pub async fn seed<T: Orso>(db: &Database, records: &[T]) -> Result<()> {
+        T::ensure_table(db).await?;
+        T::batch_create(records, db).await
+    }
+
+    /// Delete every row of the model's table, keeping the table. Returns rows deleted.
+    pub async fn reset<T: Orso>(db: &Database) -> Result<u64> {
+        Self::reset_table(db, T::table_name()).await
+    }
+
+    pub async fn reset_table(db: &Database, table_name: &str) -> Result<u64> {
+        CrudOperations::validate_table_name(table_name)?;
+        Ok(db.execute(&format!("DELETE FROM {table_name}")).await?)
+    }
+}
@@ -1,11 +1,19 @@
+pub mod as_text;
 pub mod database;
 pub mod error;
+mod filter_parser;
 pub mod filters;
+#[cfg(feature = "testing")]
+pub mod fixtures;
 pub mod macros;
 pub mod migrations;
 pub mod operations;
 pub mod pagination;
 pub mod query;
+mod query_log;
+pub mod stream;
+pub mod tracked;
+pub mod transaction;
 pub mod traits;
 pub mod types;
 pub mod utils;
@@ -27,13 +35,22 @@ pub use chrono;
 pub use cydec::{FloatingCodec, IntegerCodec};
 pub use database::*;
 pub use error::{Error, Result};
-pub use filters::{Filter, FilterOperations, FilterOperator, FilterValue, SearchFilter, Sort};
-pub use migrations::{MigrationEntry, MigrationResult, MigrationTrait, Migrations};
-pub use orso_macros::{orso_column, orso_table, Orso};
+pub use filters::{
+    Filter, FilterOperations, FilterOperator, FilterValue, SearchFilter, Sort, Subquery,
+};
+#[cfg(feature = "testing")]
+pub use fixtures::Fixtures;
+pub use migrations::{
+    ColumnAspect, MigrationEntry, MigrationResult, MigrationTrait, Migrations, PendingChange,
+};
+pub use orso_macros::{orso_column, orso_index, orso_table, Orso};
 pub use pagination::{CursorPaginatedResult, CursorPagination, PaginatedResult, Pagination};
 pub use query::{QueryBuilder, QueryResult};
+pub use tracked::Tracked;
+pub use transaction::{Transaction, TransactionMode};
 pub use serde::{Deserialize, Serialize};
-pub use traits::{FieldType, Orso};
+pub use stream::RecordStream;
+pub use traits::{FieldType, GeneratedColumn, Orso, TableIndex};
 pub use types::*;
 pub use utils::Utils;
 pub use uuid::Uuid;
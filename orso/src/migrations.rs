@@ -1,5 +1,11 @@
 // Migration system with zero-loss schema changes
-use crate::{Orso, database::Database, error::Error, traits::FieldType};
+use crate::{
+    database::Database,
+    error::Error,
+    operations::CrudOperations,
+    traits::{FieldType, GeneratedColumn},
+    Orso, Transaction,
+};
 // use chrono::{DateTime, Utc}; // Reserved for future migration timestamp features
 // use serde::{Deserialize, Serialize}; // Reserved for future migration serialization
 use std::collections::HashMap;
@@ -9,6 +15,8 @@ pub struct MigrationConfig {
     max_backups_per_table: Option<u8>,
     backup_retention_days: Option<u8>,
     backup_suffix: Option<String>,
+    preserve_extra_columns: Option<bool>,
+    updated_at_trigger: Option<bool>,
 }
 
 impl Default for MigrationConfig {
@@ -17,6 +25,8 @@ impl Default for MigrationConfig {
             max_backups_per_table: Some(5),
             backup_retention_days: Some(30),
             backup_suffix: Some("migration".to_string()),
+            preserve_extra_columns: Some(false),
+            updated_at_trigger: Some(false),
         }
     }
 }
@@ -34,6 +44,30 @@ impl MigrationConfig {
     pub fn suffix(&self) -> &str {
         self.backup_suffix.as_deref().unwrap_or("migration")
     }
+
+    pub fn preserve_extra_columns(&self) -> bool {
+        self.preserve_extra_columns.unwrap_or(false)
+    }
+
+    /// Keep table columns that no longer have a struct field (e.g. ones still read by
+    /// other systems) instead of dropping them on rebuild. Kept columns move to the end
+    /// of the table and become nullable, since the model can no longer write them.
+    pub fn with_preserve_extra_columns(mut self, preserve: bool) -> Self {
+        self.preserve_extra_columns = Some(preserve);
+        self
+    }
+
+    pub fn updated_at_trigger(&self) -> bool {
+        self.updated_at_trigger.unwrap_or(false)
+    }
+
+    /// Maintain `updated_at` (and `touch_on_update` fields) with an `AFTER UPDATE` trigger,
+    /// so rows updated by raw SQL or other applications are stamped too. Turning it off
+    /// again drops the trigger on the next migration.
+    pub fn with_updated_at_trigger(mut self, enabled: bool) -> Self {
+        self.updated_at_trigger = Some(enabled);
+        self
+    }
 }
 
 pub struct Migrations;
@@ -64,6 +98,172 @@ impl Migrations {
 
         Ok(results)
     }
+
+    /// Run the migrations inside a transaction the caller opened, so schema changes commit
+    /// or roll back together with the caller's data changes. SQLite DDL is transactional,
+    /// but `PRAGMA foreign_keys` is ignored inside a transaction, so foreign key enforcement
+    /// stays as it was when the transaction began; a zero-loss rebuild also holds the write
+    /// lock until the caller commits.
+    /// Usage: Migrations::apply_in_tx(&tx, &[migration!(User)]).await?; tx.commit().await?
+    pub async fn apply_in_tx(
+        tx: &Transaction<'_>,
+        migrations: &[Box<dyn MigrationTrait>],
+    ) -> Result<Vec<MigrationResult>, Error> {
+        Self::apply_in_tx_with_config(tx, migrations, &MigrationConfig::default()).await
+    }
+
+    /// `apply_in_tx` with a custom config
+    pub async fn apply_in_tx_with_config(
+        tx: &Transaction<'_>,
+        migrations: &[Box<dyn MigrationTrait>],
+        config: &MigrationConfig,
+    ) -> Result<Vec<MigrationResult>, Error> {
+        // Statements on the transaction's connection run inside it
+        Self::init_with_config(tx.db(), migrations, config).await
+    }
+
+    /// The schema statements `init` would run against `db`, without running them: the
+    /// `CREATE TABLE` for missing tables, the zero-loss rebuild for changed ones, and the
+    /// FTS index, `orso_index` and `updated_at` trigger statements that aren't current.
+    /// Backup pruning isn't included.
+    /// Usage: let statements = Migrations::diff_sql(&db, &[migration!(User)]).await?
+    pub async fn diff_sql(
+        db: &Database,
+        migrations: &[Box<dyn MigrationTrait>],
+    ) -> Result<Vec<String>, Error> {
+        Self::diff_sql_with_config(db, migrations, &MigrationConfig::default()).await
+    }
+
+    /// `diff_sql` with the backup naming of a custom config
+    pub async fn diff_sql_with_config(
+        db: &Database,
+        migrations: &[Box<dyn MigrationTrait>],
+        config: &MigrationConfig,
+    ) -> Result<Vec<String>, Error> {
+        let mut statements = Vec::new();
+
+        for migration in migrations {
+            statements.extend(migration.migration_statements(db, config).await?);
+        }
+
+        Ok(statements)
+    }
+
+    /// Fast startup path: create each model's table and indexes if they're missing, without
+    /// comparing or rebuilding existing schemas. Use when the schema is known to be current.
+    /// Usage: Migrations::ensure_all(&db, &[migration!(User), migration!(Post)]).await?
+    pub async fn ensure_all(
+        db: &Database,
+        migrations: &[Box<dyn MigrationTrait>],
+    ) -> Result<(), Error> {
+        // One connection runs one statement at a time, so there's nothing to overlap
+        for migration in migrations {
+            migration.ensure_exists(db).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Fail if any model's schema differs from its live table, listing every difference.
+    /// Meant for startup after `init`, or in place of it where the schema must not be
+    /// migrated (read-only deployments). Nothing is changed.
+    /// Usage: Migrations::assert_in_sync(&db, &[migration!(User), migration!(Post)]).await?
+    pub async fn assert_in_sync(
+        db: &Database,
+        migrations: &[Box<dyn MigrationTrait>],
+    ) -> Result<(), Error> {
+        Self::assert_in_sync_with_config(db, migrations, &MigrationConfig::default()).await
+    }
+
+    /// `assert_in_sync` against the schema `init_with_config` would produce, e.g. not
+    /// counting columns kept by `preserve_extra_columns` as drift
+    pub async fn assert_in_sync_with_config(
+        db: &Database,
+        migrations: &[Box<dyn MigrationTrait>],
+        config: &MigrationConfig,
+    ) -> Result<(), Error> {
+        let mut drift = Vec::new();
+        for migration in migrations {
+            let (table_name, changes) = migration.pending_changes(db, config).await?;
+            if !changes.is_empty() {
+                let changes: Vec<String> = changes.iter().map(ToString::to_string).collect();
+                drift.push(format!("{}: {}", table_name, changes.join(", ")));
+            }
+        }
+
+        if drift.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(format!(
+                "Schema out of sync with models: {}",
+                drift.join("; ")
+            )))
+        }
+    }
+
+    /// Names of the tables touched by a migration run, in migration order
+    /// Usage: let tables = Migrations::migrated_tables(&results);
+    pub fn migrated_tables(results: &[MigrationResult]) -> Vec<String> {
+        results.iter().map(|r| r.table_name.clone()).collect()
+    }
+
+    /// Name of the monthly shard of `base_name` that `date` falls in, e.g. `bars_2025_01`
+    pub fn month_shard_name(base_name: &str, date: &impl chrono::Datelike) -> String {
+        format!("{}_{:04}_{:02}", base_name, date.year(), date.month())
+    }
+
+    /// Create the monthly shard of `base_name` for `date` from the model's schema, with its
+    /// indexes and FTS index, if it doesn't exist yet, and return its name. Safe to call
+    /// repeatedly and from concurrent writers, since creation is `CREATE TABLE IF NOT
+    /// EXISTS`. An explicitly named index can only exist on one shard (index names are
+    /// global), so models with one fail from the second shard on.
+    /// Usage: let table = Migrations::ensure_month_shard::<Bar>(&db, "bars", &now).await?;
+    pub async fn ensure_month_shard<T>(
+        db: &Database,
+        base_name: &str,
+        date: &impl chrono::Datelike,
+    ) -> Result<String, Error>
+    where
+        T: Orso,
+    {
+        if base_name.trim().is_empty() {
+            return Err(Error::Validation(
+                "Shard base table name must not be empty".to_string(),
+            ));
+        }
+
+        let table_name = Self::month_shard_name(base_name, date);
+        CrudOperations::validate_table_name(&table_name)?;
+        ensure_table_exists_with_name::<T>(db, &table_name).await?;
+
+        Ok(table_name)
+    }
+
+    /// Create an empty table named `new_table` with the model's columns and indexes, e.g. a
+    /// staging table or a new shard. Fails if the table already exists, or if the model has
+    /// an explicitly named index (index names are global, so it can't be created twice).
+    /// Usage: Migrations::create_like::<Bar>(&db, "bars_staging").await?;
+    pub async fn create_like<T>(db: &Database, new_table: &str) -> Result<(), Error>
+    where
+        T: Orso,
+    {
+        if new_table.trim().is_empty() {
+            return Err(Error::Validation(
+                "Table name must not be empty".to_string(),
+            ));
+        }
+        if check_table_exists(db, new_table).await? {
+            return Err(Error::Validation(format!(
+                "Table {new_table} already exists"
+            )));
+        }
+        if let Some(name) = T::indexes().iter().find_map(|index| index.name) {
+            return Err(Error::Validation(format!(
+                "Index {name} has an explicit name and can't be created for {new_table}"
+            )));
+        }
+        ensure_table_exists_with_name::<T>(db, new_table).await
+    }
 }
 
 // Trait for migrations to avoid generic constraints
@@ -74,12 +274,31 @@ pub trait MigrationTrait: Send + Sync {
         db: &Database,
         config: &MigrationConfig,
     ) -> Result<MigrationResult, Error>;
+
+    /// Create the table and its indexes if missing, leaving an existing table as is
+    async fn ensure_exists(&self, db: &Database) -> Result<(), Error>;
+
+    /// Statements `run_migration` would execute, without executing them
+    async fn migration_statements(
+        &self,
+        db: &Database,
+        config: &MigrationConfig,
+    ) -> Result<Vec<String>, Error>;
+
+    /// The table name and its differences from the model (see `pending_changes`)
+    async fn pending_changes(
+        &self,
+        db: &Database,
+        config: &MigrationConfig,
+    ) -> Result<(String, Vec<PendingChange>), Error>;
 }
 
 // Migration entry for the init system
 pub struct MigrationEntry<T: Orso + Default> {
     _phantom: std::marker::PhantomData<T>,
     custom_table_name: Option<String>,
+    // Overrides MigrationConfig::preserve_extra_columns for this table
+    preserve_extra_columns: Option<bool>,
 }
 
 impl<T: Orso + Default> MigrationEntry<T> {
@@ -87,6 +306,7 @@ impl<T: Orso + Default> MigrationEntry<T> {
         Self {
             _phantom: std::marker::PhantomData,
             custom_table_name: None,
+            preserve_extra_columns: None,
         }
     }
 
@@ -94,6 +314,21 @@ impl<T: Orso + Default> MigrationEntry<T> {
         Self {
             _phantom: std::marker::PhantomData,
             custom_table_name: Some(table_name),
+            preserve_extra_columns: None,
+        }
+    }
+
+    /// Keep or drop orphaned columns for this table regardless of the config passed to
+    /// `init_with_config` (see `MigrationConfig::with_preserve_extra_columns`)
+    pub fn preserve_extra_columns(mut self, preserve: bool) -> Self {
+        self.preserve_extra_columns = Some(preserve);
+        self
+    }
+
+    fn resolve_config(&self, config: &MigrationConfig) -> MigrationConfig {
+        match self.preserve_extra_columns {
+            Some(preserve) => config.clone().with_preserve_extra_columns(preserve),
+            None => config.clone(),
         }
     }
 }
@@ -105,12 +340,39 @@ impl<T: Orso + Default + Send + Sync> MigrationTrait for MigrationEntry<T> {
         db: &Database,
         config: &MigrationConfig,
     ) -> Result<MigrationResult, Error> {
+        let config = &self.resolve_config(config);
         if let Some(custom_name) = &self.custom_table_name {
             ensure_table_with_name::<T>(db, custom_name, config).await
         } else {
             ensure_table::<T>(db, config).await
         }
     }
+
+    async fn ensure_exists(&self, db: &Database) -> Result<(), Error> {
+        let table_name = self.custom_table_name.as_deref().unwrap_or(T::table_name());
+        ensure_table_exists_with_name::<T>(db, table_name).await
+    }
+
+    async fn migration_statements(
+        &self,
+        db: &Database,
+        config: &MigrationConfig,
+    ) -> Result<Vec<String>, Error> {
+        let table_name = self.custom_table_name.as_deref().unwrap_or(T::table_name());
+        let config = &self.resolve_config(config);
+        migration_statements_with_name::<T>(db, table_name, config).await
+    }
+
+    async fn pending_changes(
+        &self,
+        db: &Database,
+        config: &MigrationConfig,
+    ) -> Result<(String, Vec<PendingChange>), Error> {
+        let table_name = self.custom_table_name.as_deref().unwrap_or(T::table_name());
+        let config = &self.resolve_config(config);
+        let changes = pending_changes_with_name::<T>(db, table_name, config).await?;
+        Ok((table_name.to_string(), changes))
+    }
 }
 
 // migration! macro creates boxed MigrationEntry
@@ -139,17 +401,111 @@ pub struct ColumnInfo {
     pub is_primary_key: bool,
     pub foreign_key_reference: Option<String>,
     pub has_default: bool,
+    /// The column's DEFAULT expression, as written in its definition
+    pub default: Option<String>,
     pub is_compressed: bool, // Track if this column should be compressed
+    pub generated: Option<GeneratedColumn>,
+    /// Carries the `CHECK (col IN (0, 1))` constraint of a `check_bool` field
+    pub check_bool: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct SchemaComparison {
     pub needs_migration: bool,
-    pub changes: Vec<String>,
+    pub changes: Vec<PendingChange>,
     pub current_columns: Vec<ColumnInfo>,
     pub expected_columns: Vec<ColumnInfo>,
 }
 
+/// One difference between a model and its table, as reported by `pending_changes`.
+/// Column differences other than a missing column are resolved by rebuilding the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingChange {
+    /// The table doesn't exist yet
+    MissingTable {
+        table: String,
+    },
+    ColumnCount {
+        current: usize,
+        expected: usize,
+    },
+    /// A model field has no column
+    MissingColumn {
+        column: String,
+    },
+    /// A column has no model field
+    ExtraColumn {
+        column: String,
+    },
+    /// A column's definition differs from the field's; `current` and `expected` describe
+    /// the `aspect` on each side
+    ColumnDiffers {
+        column: String,
+        aspect: ColumnAspect,
+        current: String,
+        expected: String,
+    },
+    /// A declared index doesn't exist
+    MissingIndex {
+        name: String,
+    },
+    /// A declared index exists with another definition
+    IndexDiffers {
+        name: String,
+    },
+}
+
+/// The part of a column definition a `PendingChange::ColumnDiffers` is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnAspect {
+    Type,
+    Nullability,
+    Position,
+    Unique,
+    PrimaryKey,
+    Compression,
+    Generated,
+    Default,
+    Check,
+}
+
+impl std::fmt::Display for PendingChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PendingChange::MissingTable { table } => write!(f, "Missing table: {table}"),
+            PendingChange::ColumnCount { current, expected } => {
+                write!(f, "Column count differs: {current} vs {expected}")
+            }
+            PendingChange::MissingColumn { column } => write!(f, "Missing column: {column}"),
+            PendingChange::ExtraColumn { column } => write!(f, "Extra column: {column}"),
+            PendingChange::ColumnDiffers {
+                column,
+                aspect,
+                current,
+                expected,
+            } => write!(f, "{aspect} mismatch for {column}: {current} vs {expected}"),
+            PendingChange::MissingIndex { name } => write!(f, "Missing index: {name}"),
+            PendingChange::IndexDiffers { name } => write!(f, "Index definition differs: {name}"),
+        }
+    }
+}
+
+impl std::fmt::Display for ColumnAspect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ColumnAspect::Type => "Type",
+            ColumnAspect::Nullability => "Nullability",
+            ColumnAspect::Position => "Position",
+            ColumnAspect::Unique => "Unique constraint",
+            ColumnAspect::PrimaryKey => "Primary key",
+            ColumnAspect::Compression => "Compression",
+            ColumnAspect::Generated => "Generated column",
+            ColumnAspect::Default => "Default",
+            ColumnAspect::Check => "CHECK constraint",
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum MigrationAction {
     TableCreated,
@@ -159,6 +515,7 @@ pub enum MigrationAction {
 
 #[derive(Debug, Clone)]
 pub struct MigrationResult {
+    pub table_name: String,
     pub action: MigrationAction,
     pub backup_table: Option<String>,
     pub rows_migrated: Option<u64>,
@@ -181,6 +538,26 @@ pub async fn ensure_table_with_name<T>(
     table_name: &str,
     config: &MigrationConfig,
 ) -> Result<MigrationResult, Error>
+where
+    T: Orso + Default,
+{
+    CrudOperations::validate_table_name(table_name)?;
+    let result = ensure_table_schema::<T>(db, table_name, config).await?;
+    // A new or rebuilt table starts without rows in the index
+    let rebuild = !matches!(result.action, MigrationAction::SchemaMatched);
+    ensure_fts_index::<T>(db, table_name, rebuild).await?;
+    // A rebuild leaves the indexes on the backup table
+    let statements = index_statements::<T>(db, table_name, result.backup_table.as_deref()).await?;
+    execute_index_statements(db, statements).await?;
+    ensure_updated_at_trigger::<T>(db, table_name, config.updated_at_trigger()).await?;
+    Ok(result)
+}
+
+async fn ensure_table_schema<T>(
+    db: &Database,
+    table_name: &str,
+    config: &MigrationConfig,
+) -> Result<MigrationResult, Error>
 where
     T: Orso + Default,
 {
@@ -192,20 +569,19 @@ where
 
     if !table_exists {
         // Enable foreign key constraints for SQLite
-        db.conn
-            .execute("PRAGMA foreign_keys = ON", ())
+        db.execute_write("PRAGMA foreign_keys = ON", vec![])
             .await
             .map_err(|e| Error::DatabaseError(format!("Failed to enable foreign keys: {}", e)))?;
 
         // Create new table using custom SQL generation with table name override
         let create_sql = generate_migration_sql_with_custom_name::<T>(table_name);
 
-        db.conn
-            .execute(&create_sql, ())
+        db.execute_write(&create_sql, vec![])
             .await
             .map_err(|e| Error::DatabaseError(format!("Failed to create table: {}", e)))?;
 
         return Ok(MigrationResult {
+            table_name: table_name.to_string(),
             action: MigrationAction::TableCreated,
             backup_table: None,
             rows_migrated: None,
@@ -215,10 +591,12 @@ where
 
     // Step 3: Compare current vs expected schema
     let current_schema = get_current_table_schema(db, table_name).await?;
+    let expected_schema = with_preserved_columns(expected_schema, &current_schema, config);
     let comparison = compare_schemas(&current_schema, &expected_schema);
 
     if !comparison.needs_migration {
         return Ok(MigrationResult {
+            table_name: table_name.to_string(),
             action: MigrationAction::SchemaMatched,
             backup_table: None,
             rows_migrated: None,
@@ -230,6 +608,416 @@ where
     perform_zero_loss_migration(db, table_name, &comparison, config).await
 }
 
+/// `CREATE TABLE IF NOT EXISTS` for the model under `table_name`, plus its indexes.
+/// Unlike `ensure_table_with_name`, an existing table is never compared or rebuilt.
+pub async fn ensure_table_exists_with_name<T>(db: &Database, table_name: &str) -> Result<(), Error>
+where
+    T: Orso,
+{
+    CrudOperations::validate_table_name(table_name)?;
+    let create_sql = generate_migration_sql_with_custom_name::<T>(table_name);
+    db.execute_write(&create_sql, vec![]).await?;
+    ensure_fts_index::<T>(db, table_name, false).await?;
+    ensure_indexes::<T>(db, table_name).await
+}
+
+/// Differences between the model's schema and its table, without changing anything.
+/// Empty when the table is in sync.
+pub async fn pending_changes<T>(db: &Database) -> Result<Vec<PendingChange>, Error>
+where
+    T: Orso,
+{
+    pending_changes_with_name::<T>(db, T::table_name(), &MigrationConfig::default()).await
+}
+
+/// Differences between the schema a migration with `config` would produce and the table
+pub async fn pending_changes_with_name<T>(
+    db: &Database,
+    table_name: &str,
+    config: &MigrationConfig,
+) -> Result<Vec<PendingChange>, Error>
+where
+    T: Orso,
+{
+    CrudOperations::validate_table_name(table_name)?;
+    if !check_table_exists(db, table_name).await? {
+        return Ok(vec![PendingChange::MissingTable {
+            table: table_name.to_string(),
+        }]);
+    }
+
+    let expected_schema = infer_schema_from_orso::<T>()?;
+    let current_schema = get_current_table_schema(db, table_name).await?;
+    let expected_schema = with_preserved_columns(expected_schema, &current_schema, config);
+    let comparison = compare_schemas(&current_schema, &expected_schema);
+
+    let mut changes = comparison.changes;
+    for index in T::indexes() {
+        let name = index.index_name(table_name);
+        match stored_index_sql(db, &name).await? {
+            None => changes.push(PendingChange::MissingIndex { name }),
+            Some(sql) if !index_sql_matches(&sql, &index.create_sql(table_name)) => {
+                changes.push(PendingChange::IndexDiffers { name })
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(changes)
+}
+
+/// Statements that migrating `table_name` to the model's schema would run; empty when
+/// the table is in sync
+pub async fn migration_statements_with_name<T>(
+    db: &Database,
+    table_name: &str,
+    config: &MigrationConfig,
+) -> Result<Vec<String>, Error>
+where
+    T: Orso,
+{
+    CrudOperations::validate_table_name(table_name)?;
+    let mut statements = if !check_table_exists(db, table_name).await? {
+        vec![
+            "PRAGMA foreign_keys = ON".to_string(),
+            generate_migration_sql_with_custom_name::<T>(table_name),
+        ]
+    } else {
+        let expected_schema = infer_schema_from_orso::<T>()?;
+        let current_schema = get_current_table_schema(db, table_name).await?;
+        let expected_schema = with_preserved_columns(expected_schema, &current_schema, config);
+        let comparison = compare_schemas(&current_schema, &expected_schema);
+        if comparison.needs_migration {
+            let names = RebuildNames::new(table_name, config);
+            zero_loss_migration_sql(table_name, &names, &comparison).to_vec()
+        } else {
+            vec![]
+        }
+    };
+
+    // A new or rebuilt table starts without its indexes and triggers
+    let table_changed = !statements.is_empty();
+    let rebuilt_from = table_changed.then_some(table_name);
+    statements.extend(index_statements::<T>(db, table_name, rebuilt_from).await?);
+    statements.extend(fts_index_changes::<T>(db, table_name, table_changed).await?);
+    statements.extend(
+        updated_at_trigger_changes::<T>(db, table_name, config.updated_at_trigger(), table_changed)
+            .await?,
+    );
+    Ok(statements)
+}
+
+/// Name of the FTS5 table that indexes a table's `#[orso_column(fts)]` columns
+pub fn fts_table_name(table_name: &str) -> String {
+    format!("{}_fts", table_name)
+}
+
+/// Create the FTS5 companion table for the model's `fts` columns and the triggers that
+/// keep it in sync with `table_name`. The triggers are always recreated (a zero-loss
+/// migration leaves them on the backup table); the index is rebuilt from the base table
+/// when it's new or `rebuild` is set. Does nothing for models without `fts` columns.
+pub async fn ensure_fts_index<T>(
+    db: &Database,
+    table_name: &str,
+    rebuild: bool,
+) -> Result<(), Error>
+where
+    T: Orso,
+{
+    if T::fts_fields().is_empty() {
+        return Ok(());
+    }
+
+    let fts_table = fts_table_name(table_name);
+    let existed = check_table_exists(db, &fts_table).await?;
+    for sql in fts_index_statements::<T>(table_name) {
+        db.execute_write(&sql, vec![])
+            .await
+            .map_err(|e| Error::DatabaseError(format!("Failed to set up FTS index: {}", e)))?;
+    }
+
+    if rebuild || !existed {
+        db.execute_write(&fts_rebuild_sql(&fts_table), vec![])
+            .await
+            .map_err(|e| Error::DatabaseError(format!("Failed to rebuild FTS index: {}", e)))?;
+    }
+
+    Ok(())
+}
+
+// The FTS5 table over the model's `fts` columns and the triggers that sync it with
+// `table_name`
+fn fts_index_statements<T>(table_name: &str) -> Vec<String>
+where
+    T: Orso,
+{
+    let columns = T::fts_fields();
+    let fts_table = fts_table_name(table_name);
+    let column_list = columns.join(", ");
+    let new_values = columns
+        .iter()
+        .map(|c| format!("new.{}", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let old_values = columns
+        .iter()
+        .map(|c| format!("old.{}", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let insert_new = format!(
+        "INSERT INTO {fts}(rowid, {cols}) VALUES (new.rowid, {new_values});",
+        fts = fts_table,
+        cols = column_list,
+    );
+    let delete_old = format!(
+        "INSERT INTO {fts}({fts}, rowid, {cols}) VALUES ('delete', old.rowid, {old_values});",
+        fts = fts_table,
+        cols = column_list,
+    );
+
+    vec![
+        format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING fts5({}, content='{}', content_rowid='rowid')",
+            fts_table, column_list, table_name
+        ),
+        format!("DROP TRIGGER IF EXISTS {}_ai", fts_table),
+        format!("DROP TRIGGER IF EXISTS {}_ad", fts_table),
+        format!("DROP TRIGGER IF EXISTS {}_au", fts_table),
+        format!(
+            "CREATE TRIGGER {}_ai AFTER INSERT ON {} BEGIN {} END",
+            fts_table, table_name, insert_new
+        ),
+        format!(
+            "CREATE TRIGGER {}_ad AFTER DELETE ON {} BEGIN {} END",
+            fts_table, table_name, delete_old
+        ),
+        format!(
+            "CREATE TRIGGER {}_au AFTER UPDATE ON {} BEGIN {} {} END",
+            fts_table, table_name, delete_old, insert_new
+        ),
+    ]
+}
+
+fn fts_rebuild_sql(fts_table: &str) -> String {
+    format!("INSERT INTO {0}({0}) VALUES ('rebuild')", fts_table)
+}
+
+// The FTS statements `ensure_fts_index` would run: none while the FTS table and its
+// triggers on `table_name` are current
+async fn fts_index_changes<T>(
+    db: &Database,
+    table_name: &str,
+    table_changed: bool,
+) -> Result<Vec<String>, Error>
+where
+    T: Orso,
+{
+    if T::fts_fields().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let fts_table = fts_table_name(table_name);
+    let existed = check_table_exists(db, &fts_table).await?;
+    let statements = fts_index_statements::<T>(table_name);
+    let mut current = existed && !table_changed;
+    for create_sql in statements
+        .iter()
+        .filter(|sql| sql.starts_with("CREATE TRIGGER"))
+    {
+        if !current {
+            break;
+        }
+        let name = create_sql.split_whitespace().nth(2).unwrap_or_default();
+        current = matches!(
+            stored_trigger(db, name).await?,
+            Some((table, sql)) if table == table_name && index_sql_matches(&sql, create_sql)
+        );
+    }
+    if current {
+        return Ok(vec![]);
+    }
+
+    let mut changes = statements;
+    if table_changed || !existed {
+        changes.push(fts_rebuild_sql(&fts_table));
+    }
+    Ok(changes)
+}
+
+fn updated_at_trigger_name(table_name: &str) -> String {
+    format!("{}_touch_updated_at", table_name)
+}
+
+/// Create (or with `enabled` false, drop) the trigger that stamps the model's
+/// `touch_on_update_fields` after any UPDATE that leaves them unchanged. Updates that set
+/// the fields themselves, like the ORM's, keep their values. The trigger is always
+/// recreated, since a rebuild leaves the old one on the backup table.
+pub async fn ensure_updated_at_trigger<T>(
+    db: &Database,
+    table_name: &str,
+    enabled: bool,
+) -> Result<(), Error>
+where
+    T: Orso,
+{
+    for sql in updated_at_trigger_statements::<T>(table_name, enabled) {
+        db.execute(&sql).await.map_err(|e| {
+            Error::DatabaseError(format!("Failed to set up updated_at trigger: {}", e))
+        })?;
+    }
+    Ok(())
+}
+
+fn updated_at_trigger_statements<T>(table_name: &str, enabled: bool) -> Vec<String>
+where
+    T: Orso,
+{
+    let trigger = updated_at_trigger_name(table_name);
+    let mut statements = vec![format!("DROP TRIGGER IF EXISTS {}", trigger)];
+
+    let fields = T::touch_on_update_fields();
+    if enabled && !fields.is_empty() {
+        let unchanged = fields
+            .iter()
+            .map(|f| format!("NEW.{f} IS OLD.{f}"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let sets = fields
+            .iter()
+            .map(|f| format!("{f} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        statements.push(format!(
+            "CREATE TRIGGER {trigger} AFTER UPDATE ON {table_name} FOR EACH ROW WHEN {unchanged} \
+             BEGIN UPDATE {table_name} SET {sets} WHERE rowid = NEW.rowid; END"
+        ));
+    }
+    statements
+}
+
+// The trigger statements `ensure_updated_at_trigger` would run: none when the live
+// trigger (or its absence) already matches
+async fn updated_at_trigger_changes<T>(
+    db: &Database,
+    table_name: &str,
+    enabled: bool,
+    table_changed: bool,
+) -> Result<Vec<String>, Error>
+where
+    T: Orso,
+{
+    let statements = updated_at_trigger_statements::<T>(table_name, enabled);
+    let stored = stored_trigger(db, &updated_at_trigger_name(table_name)).await?;
+    let current = match (statements.get(1), stored) {
+        (None, None) => true,
+        (Some(create_sql), Some((table, sql))) => {
+            !table_changed && table == table_name && index_sql_matches(&sql, create_sql)
+        }
+        _ => false,
+    };
+    Ok(if current { vec![] } else { statements })
+}
+
+// Table and SQL of the trigger named `name`
+async fn stored_trigger(db: &Database, name: &str) -> Result<Option<(String, String)>, Error> {
+    let mut rows = db
+        .query(
+            "SELECT tbl_name, sql FROM sqlite_master WHERE type = 'trigger' AND name = ?",
+            vec![libsql::Value::Text(name.to_string())],
+        )
+        .await?;
+    match rows.next().await? {
+        Some(row) => Ok(Some((row.get::<String>(0)?, row.get::<String>(1)?))),
+        None => Ok(None),
+    }
+}
+
+/// Create the model's `#[orso_index]` indexes on `table_name`. An index whose stored
+/// definition differs (columns, order, `WHERE`, uniqueness, or still pointing at a backup
+/// table after a rebuild) is dropped and recreated. Indexes no longer declared are left.
+pub async fn ensure_indexes<T>(db: &Database, table_name: &str) -> Result<(), Error>
+where
+    T: Orso,
+{
+    let statements = index_statements::<T>(db, table_name, None).await?;
+    execute_index_statements(db, statements).await
+}
+
+// The statements creating the model's indexes that are missing or stale. Indexes still
+// on `rebuilt_from` (the backup of a rebuild, or `table_name` itself when planning one)
+// are recreated; an index of that name on any other table is an error.
+async fn index_statements<T>(
+    db: &Database,
+    table_name: &str,
+    rebuilt_from: Option<&str>,
+) -> Result<Vec<String>, Error>
+where
+    T: Orso,
+{
+    let mut statements = Vec::new();
+    for index in T::indexes() {
+        let name = index.index_name(table_name);
+        let create_sql = index.create_sql(table_name);
+        match stored_index_sql(db, &name).await? {
+            Some(sql) if rebuilt_from.is_none() && index_sql_matches(&sql, &create_sql) => continue,
+            Some(_) => {
+                // An explicit name is global, so it may already index another table
+                let owner = index_table(db, &name).await?;
+                if owner.as_deref() != Some(table_name) && owner.as_deref() != rebuilt_from {
+                    return Err(Error::Validation(format!(
+                        "Index {} already exists on table {}",
+                        name,
+                        owner.unwrap_or_default()
+                    )));
+                }
+                statements.push(format!("DROP INDEX {}", name));
+            }
+            None => {}
+        }
+        statements.push(create_sql);
+    }
+    Ok(statements)
+}
+
+async fn execute_index_statements(db: &Database, statements: Vec<String>) -> Result<(), Error> {
+    for sql in statements {
+        db.execute_write(&sql, vec![])
+            .await
+            .map_err(|e| Error::DatabaseError(format!("Failed to create index: {}", e)))?;
+    }
+    Ok(())
+}
+
+async fn stored_index_sql(db: &Database, index_name: &str) -> Result<Option<String>, Error> {
+    let mut rows = db
+        .query(
+            "SELECT sql FROM sqlite_master WHERE type = 'index' AND name = ?",
+            vec![libsql::Value::Text(index_name.to_string())],
+        )
+        .await?;
+    match rows.next().await? {
+        Some(row) => Ok(row.get::<Option<String>>(0)?),
+        None => Ok(None),
+    }
+}
+
+async fn index_table(db: &Database, index_name: &str) -> Result<Option<String>, Error> {
+    let mut rows = db
+        .query(
+            "SELECT tbl_name FROM sqlite_master WHERE type = 'index' AND name = ?",
+            vec![libsql::Value::Text(index_name.to_string())],
+        )
+        .await?;
+    match rows.next().await? {
+        Some(row) => Ok(Some(row.get::<String>(0)?)),
+        None => Ok(None),
+    }
+}
+
+fn index_sql_matches(stored: &str, expected: &str) -> bool {
+    normalize_expression(stored).eq_ignore_ascii_case(&normalize_expression(expected))
+}
+
 fn generate_migration_sql_with_custom_name<T>(table_name: &str) -> String
 where
     T: Orso,
@@ -278,8 +1066,13 @@ where
     let field_types = T::field_types();
     let field_nullable = T::field_nullable();
     let field_compressed = T::field_compressed();
+    let field_compressed_text = T::field_compressed_text();
     let unique_fields = T::unique_fields();
+    let generated_fields: HashMap<&str, GeneratedColumn> =
+        T::generated_fields().into_iter().collect();
     let primary_key_field = T::primary_key_field();
+    let check_bool_fields = T::check_bool_fields();
+    let model_sql = T::migration_sql();
 
     if field_names.len() != field_types.len() || field_names.len() != field_nullable.len() {
         return Err(Error::DatabaseError(
@@ -300,13 +1093,17 @@ where
         // Determine if this is the primary key
         let is_primary_key = *name == primary_key_field;
         
-        // For compressed fields, we use BLOB type
-        let sql_type = if *compressed {
+        // For compressed fields and uuid_blob keys, we use BLOB type
+        let uuid_blob_key = is_primary_key && T::primary_key_uuid_blob();
+        let compressed = *compressed || field_compressed_text.get(i) == Some(&true);
+        let sql_type = if compressed || uuid_blob_key {
             "BLOB".to_string()
         } else {
             field_type_to_sqlite_type(field_type)
         };
 
+        let default = column_definition(&model_sql, name).and_then(extract_default_expression);
+
         columns.push(ColumnInfo {
             name: name.to_string(),
             sql_type,
@@ -315,8 +1112,12 @@ where
             is_unique: is_unique || is_primary_key, // Primary keys are implicitly unique
             is_primary_key,
             foreign_key_reference: None, // Would need to add this to Orso trait
-            has_default: false, // Would depend on field type and attributes
-            is_compressed: *compressed, // Track compression status
+            has_default: default.is_some(),
+            default,
+            // Track compression status; the live schema reads every BLOB column as compressed
+            is_compressed: compressed || uuid_blob_key,
+            generated: generated_fields.get(name).cloned(),
+            check_bool: check_bool_fields.contains(name),
         });
     }
 
@@ -342,8 +1143,7 @@ async fn check_table_exists(db: &Database, table_name: &str) -> Result<bool, Err
     );
 
     let mut rows = db
-        .conn
-        .query(&query, ())
+        .query(&query, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to check table existence: {}", e)))?;
 
@@ -361,17 +1161,17 @@ async fn get_current_table_schema(
     db: &Database,
     table_name: &str,
 ) -> Result<Vec<ColumnInfo>, Error> {
-    // First get basic column info
-    let query = format!("PRAGMA table_info({})", table_name);
+    // First get basic column info (table_xinfo also lists generated columns)
+    let query = format!("PRAGMA table_xinfo({})", table_name);
 
     let mut rows = db
-        .conn
-        .query(&query, ())
+        .query(&query, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to get table info: {}", e)))?;
 
     let mut columns = Vec::new();
     let mut column_info_map = std::collections::HashMap::new();
+    let table_sql = get_table_sql(db, table_name).await?;
 
     while let Some(row) = rows
         .next()
@@ -396,6 +1196,21 @@ async fn get_current_table_schema(
         let pk: i32 = row
             .get(5)
             .map_err(|e| Error::DatabaseError(e.to_string()))?;
+        // 2 = generated VIRTUAL, 3 = generated STORED
+        let hidden: i32 = row
+            .get(6)
+            .map_err(|e| Error::DatabaseError(e.to_string()))?;
+        let generated = if hidden == 2 || hidden == 3 {
+            Some(GeneratedColumn {
+                expression: table_sql
+                    .as_deref()
+                    .and_then(|sql| extract_generated_expression(sql, &name))
+                    .unwrap_or_default(),
+                stored: hidden == 3,
+            })
+        } else {
+            None
+        };
 
         let column_info = ColumnInfo {
             name: name.clone(),
@@ -406,7 +1221,15 @@ async fn get_current_table_schema(
             is_primary_key: pk != 0,
             foreign_key_reference: None, // Will be updated later
             has_default: default_value.is_some(),
+            default: table_sql
+                .as_deref()
+                .and_then(|sql| column_definition(sql, &name))
+                .and_then(extract_default_expression),
             is_compressed: type_name.to_uppercase() == "BLOB", // Heuristic: BLOB columns are probably compressed
+            generated,
+            check_bool: table_sql
+                .as_deref()
+                .is_some_and(|sql| has_bool_check(sql, &name)),
         };
 
         column_info_map.insert(name.clone(), column_info.clone());
@@ -419,8 +1242,7 @@ async fn get_current_table_schema(
     // Get index information to determine unique constraints
     let index_query = format!("PRAGMA index_list({})", table_name);
     let mut index_rows = db
-        .conn
-        .query(&index_query, ())
+        .query(&index_query, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to get index list: {}", e)))?;
 
@@ -435,13 +1257,17 @@ async fn get_current_table_schema(
         let is_unique_index: i32 = row
             .get(2)
             .map_err(|e| Error::DatabaseError(e.to_string()))?;
+        // Indexes from CREATE INDEX (origin "c", e.g. `orso_index(..., unique)`) aren't
+        // column constraints
+        let origin: String = row
+            .get(3)
+            .map_err(|e| Error::DatabaseError(e.to_string()))?;
 
-        if is_unique_index != 0 {
+        if is_unique_index != 0 && origin != "c" {
             // Get column names for this unique index
             let index_info_query = format!("PRAGMA index_info({})", index_name);
             let mut index_info_rows = db
-                .conn
-                .query(&index_info_query, ())
+                .query(&index_info_query, vec![])
                 .await
                 .map_err(|e| Error::DatabaseError(format!("Failed to get index info: {}", e)))?;
 
@@ -465,8 +1291,7 @@ async fn get_current_table_schema(
     // Get foreign key information
     let fk_query = format!("PRAGMA foreign_key_list({})", table_name);
     let mut fk_rows = db
-        .conn
-        .query(&fk_query, ())
+        .query(&fk_query, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to get foreign key list: {}", e)))?;
 
@@ -499,17 +1324,165 @@ async fn get_current_table_schema(
     Ok(columns)
 }
 
+async fn get_table_sql(db: &Database, table_name: &str) -> Result<Option<String>, Error> {
+    let mut rows = db
+        .query(
+            "SELECT sql FROM sqlite_master WHERE type='table' AND name = ?",
+            vec![libsql::Value::Text(table_name.to_string())],
+        )
+        .await
+        .map_err(|e| Error::DatabaseError(format!("Failed to get table sql: {}", e)))?;
+
+    match rows
+        .next()
+        .await
+        .map_err(|e| Error::DatabaseError(e.to_string()))?
+    {
+        Some(row) => row.get(0).map_err(|e| Error::DatabaseError(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+// Pull the `AS (...)` expression of a generated column out of its CREATE TABLE statement
+fn extract_generated_expression(create_sql: &str, column: &str) -> Option<String> {
+    let definition = column_definition(create_sql, column)?;
+    let upper = definition.to_uppercase();
+    let open = upper.find(" AS (").map(|i| i + " AS ".len())?;
+    let mut depth = 0;
+    for (i, c) in definition[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(definition[open + 1..open + i].trim().to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// The DEFAULT expression of a column definition: a parenthesized expression or a literal
+fn extract_default_expression(definition: &str) -> Option<String> {
+    let upper = definition.to_ascii_uppercase();
+    let start = upper.find(" DEFAULT ")? + " DEFAULT ".len();
+    let rest = definition[start..].trim_start();
+    if !rest.starts_with('(') {
+        return rest.split_whitespace().next().map(str::to_string);
+    }
+    let mut depth = 0;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(rest[..=i].to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Whether the column's definition carries the `check_bool` constraint
+fn has_bool_check(create_sql: &str, column: &str) -> bool {
+    column_definition(create_sql, column).is_some_and(|definition| {
+        let compact: String = definition
+            .chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '"' | '`'))
+            .collect::<String>()
+            .to_uppercase();
+        compact.contains(&format!("CHECK({}IN(0,1))", column.to_uppercase()))
+    })
+}
+
+// A column's definition within its CREATE TABLE statement
+fn column_definition<'a>(create_sql: &'a str, column: &str) -> Option<&'a str> {
+    let body = &create_sql[create_sql.find('(')? + 1..];
+
+    // Split the column list on top-level commas
+    let mut definitions = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in body.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                definitions.push(&body[start..i]);
+                break;
+            }
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                definitions.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    definitions.into_iter().find(|def| {
+        def.split_whitespace()
+            .next()
+            .map(|name| name.trim_matches(|c| c == '"' || c == '`' || c == '[' || c == ']'))
+            == Some(column)
+    })
+}
+
+fn normalize_expression(expression: &str) -> String {
+    expression.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// With `preserve_extra_columns`, table columns the model doesn't know about become part of
+// the expected schema: appended after the model's columns and nullable
+fn with_preserved_columns(
+    mut expected: Vec<ColumnInfo>,
+    current: &[ColumnInfo],
+    config: &MigrationConfig,
+) -> Vec<ColumnInfo> {
+    if !config.preserve_extra_columns() {
+        return expected;
+    }
+    let extra: Vec<ColumnInfo> = current
+        .iter()
+        .filter(|column| !expected.iter().any(|e| e.name == column.name))
+        .cloned()
+        .collect();
+    for mut column in extra {
+        column.position = expected.len() as i32;
+        column.nullable = true;
+        column.is_primary_key = false;
+        expected.push(column);
+    }
+    expected
+}
+
+fn column_differs(
+    column: &ColumnInfo,
+    aspect: ColumnAspect,
+    current: String,
+    expected: String,
+) -> PendingChange {
+    PendingChange::ColumnDiffers {
+        column: column.name.clone(),
+        aspect,
+        current,
+        expected,
+    }
+}
+
 fn compare_schemas(current: &[ColumnInfo], expected: &[ColumnInfo]) -> SchemaComparison {
     let mut changes = Vec::new();
     let mut needs_migration = false;
 
     // Check if schemas are identical
     if current.len() != expected.len() {
-        changes.push(format!(
-            "Column count differs: {} vs {}",
-            current.len(),
-            expected.len()
-        ));
+        changes.push(PendingChange::ColumnCount {
+            current: current.len(),
+            expected: expected.len(),
+        });
         needs_migration = true;
     }
 
@@ -524,44 +1497,94 @@ fn compare_schemas(current: &[ColumnInfo], expected: &[ColumnInfo]) -> SchemaCom
         match current_map.get(&expected_col.name) {
             Some(current_col) => {
                 if current_col.sql_type != expected_col.sql_type {
-                    changes.push(format!(
-                        "Type mismatch for {}: {} vs {}",
-                        expected_col.name, current_col.sql_type, expected_col.sql_type
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Type,
+                        current_col.sql_type.to_string(),
+                        expected_col.sql_type.to_string(),
                     ));
                     needs_migration = true;
                 }
                 if current_col.nullable != expected_col.nullable {
-                    changes.push(format!(
-                        "Nullability mismatch for {}: {} vs {}",
-                        expected_col.name, current_col.nullable, expected_col.nullable
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Nullability,
+                        current_col.nullable.to_string(),
+                        expected_col.nullable.to_string(),
                     ));
                     needs_migration = true;
                 }
                 if current_col.position != expected_col.position {
-                    changes.push(format!(
-                        "Position mismatch for {}: {} vs {}",
-                        expected_col.name, current_col.position, expected_col.position
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Position,
+                        current_col.position.to_string(),
+                        expected_col.position.to_string(),
                     ));
                     needs_migration = true;
                 }
                 if current_col.is_unique != expected_col.is_unique {
-                    changes.push(format!(
-                        "Unique constraint mismatch for {}: {} vs {}",
-                        expected_col.name, current_col.is_unique, expected_col.is_unique
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Unique,
+                        current_col.is_unique.to_string(),
+                        expected_col.is_unique.to_string(),
                     ));
                     needs_migration = true;
                 }
                 if current_col.is_primary_key != expected_col.is_primary_key {
-                    changes.push(format!(
-                        "Primary key mismatch for {}: {} vs {}",
-                        expected_col.name, current_col.is_primary_key, expected_col.is_primary_key
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::PrimaryKey,
+                        current_col.is_primary_key.to_string(),
+                        expected_col.is_primary_key.to_string(),
                     ));
                     needs_migration = true;
                 }
                 if current_col.is_compressed != expected_col.is_compressed {
-                    changes.push(format!(
-                        "Compression mismatch for {}: {} vs {}",
-                        expected_col.name, current_col.is_compressed, expected_col.is_compressed
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Compression,
+                        current_col.is_compressed.to_string(),
+                        expected_col.is_compressed.to_string(),
+                    ));
+                    needs_migration = true;
+                }
+                let generated_matches = match (&current_col.generated, &expected_col.generated) {
+                    (None, None) => true,
+                    (Some(current), Some(expected)) => {
+                        current.stored == expected.stored
+                            && normalize_expression(&current.expression)
+                                == normalize_expression(&expected.expression)
+                    }
+                    _ => false,
+                };
+                if !generated_matches {
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Generated,
+                        format!("{:?}", current_col.generated),
+                        format!("{:?}", expected_col.generated),
+                    ));
+                    needs_migration = true;
+                }
+                if current_col.default.as_deref().map(normalize_expression)
+                    != expected_col.default.as_deref().map(normalize_expression)
+                {
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Default,
+                        format!("{:?}", current_col.default),
+                        format!("{:?}", expected_col.default),
+                    ));
+                    needs_migration = true;
+                }
+                if current_col.check_bool != expected_col.check_bool {
+                    changes.push(column_differs(
+                        expected_col,
+                        ColumnAspect::Check,
+                        current_col.check_bool.to_string(),
+                        expected_col.check_bool.to_string(),
                     ));
                     needs_migration = true;
                 }
@@ -569,7 +1592,9 @@ fn compare_schemas(current: &[ColumnInfo], expected: &[ColumnInfo]) -> SchemaCom
                 // additional Orso trait methods that we haven't added yet
             }
             None => {
-                changes.push(format!("Missing column: {}", expected_col.name));
+                changes.push(PendingChange::MissingColumn {
+                    column: expected_col.name.clone(),
+                });
                 needs_migration = true;
             }
         }
@@ -578,7 +1603,9 @@ fn compare_schemas(current: &[ColumnInfo], expected: &[ColumnInfo]) -> SchemaCom
     // Check for extra columns
     for current_col in current {
         if !expected_map.contains_key(&current_col.name) {
-            changes.push(format!("Extra column: {}", current_col.name));
+            changes.push(PendingChange::ExtraColumn {
+                column: current_col.name.clone(),
+            });
             needs_migration = true;
         }
     }
@@ -597,55 +1624,36 @@ async fn perform_zero_loss_migration(
     comparison: &SchemaComparison,
     config: &MigrationConfig,
 ) -> Result<MigrationResult, Error> {
-    // Generate unique backup table name with timestamp hash
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    let backup_name = format!("{}_{}_{}", table_name, config.suffix(), timestamp);
+    let names = RebuildNames::new(table_name, config);
+    let backup_name = names.backup.clone();
+    let [create_sql, copy_sql, rename_to_backup, rename_to_original] =
+        zero_loss_migration_sql(table_name, &names, comparison);
 
     // Step 1: Create new table with correct schema
-    let temp_table_name = format!("{}_temp_{}", table_name, timestamp);
-    let create_sql = generate_create_table_sql(&temp_table_name, &comparison.expected_columns);
-
-    db.conn
-        .execute(&create_sql, ())
+    db.execute_write(&create_sql, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to create temp table: {}", e)))?;
 
     // Step 2: Copy data from old table to new table (preserving row order)
-    let copy_sql = generate_data_migration_sql(
-        table_name,
-        &temp_table_name,
-        &comparison.current_columns,
-        &comparison.expected_columns,
-    );
-
     let _rows_affected = db
-        .conn
-        .execute(&copy_sql, ())
+        .execute_write(&copy_sql, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to migrate data: {}", e)))?;
 
     // Step 3: Rename original table to backup
-    let rename_to_backup = format!("ALTER TABLE {} RENAME TO {}", table_name, backup_name);
-    db.conn
-        .execute(&rename_to_backup, ())
+    db.execute_write(&rename_to_backup, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to create backup: {}", e)))?;
 
     // Step 4: Rename new table to original name
-    let rename_to_original = format!("ALTER TABLE {} RENAME TO {}", temp_table_name, table_name);
-    db.conn
-        .execute(&rename_to_original, ())
+    db.execute_write(&rename_to_original, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to rename new table: {}", e)))?;
 
     // Step 5: Verify migration success
     let verification_sql = format!("SELECT COUNT(*) FROM {}", table_name);
     let mut rows = db
-        .conn
-        .query(&verification_sql, ())
+        .query(&verification_sql, vec![])
         .await
         .map_err(|e| Error::DatabaseError(format!("Failed to verify migration: {}", e)))?;
 
@@ -663,16 +1671,55 @@ async fn perform_zero_loss_migration(
     check_backups_retention(db, table_name, config).await?;
 
     Ok(MigrationResult {
+        table_name: table_name.to_string(),
         action: MigrationAction::DataMigrated {
             from: backup_name.clone(),
             to: table_name.to_string(),
         },
         backup_table: Some(backup_name),
         rows_migrated: Some(row_count as u64),
-        schema_changes: comparison.changes.clone(),
+        schema_changes: comparison.changes.iter().map(ToString::to_string).collect(),
     })
 }
 
+// Temp and backup table names for one zero-loss rebuild, stamped with the current time
+struct RebuildNames {
+    temp: String,
+    backup: String,
+}
+
+impl RebuildNames {
+    fn new(table_name: &str, config: &MigrationConfig) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Self {
+            temp: format!("{}_temp_{}", table_name, timestamp),
+            backup: format!("{}_{}_{}", table_name, config.suffix(), timestamp),
+        }
+    }
+}
+
+// Create the new table, copy rows across, then swap it in and keep the old one as backup
+fn zero_loss_migration_sql(
+    table_name: &str,
+    names: &RebuildNames,
+    comparison: &SchemaComparison,
+) -> [String; 4] {
+    [
+        generate_create_table_sql(&names.temp, &comparison.expected_columns),
+        generate_data_migration_sql(
+            table_name,
+            &names.temp,
+            &comparison.current_columns,
+            &comparison.expected_columns,
+        ),
+        format!("ALTER TABLE {} RENAME TO {}", table_name, names.backup),
+        format!("ALTER TABLE {} RENAME TO {}", names.temp, table_name),
+    ]
+}
+
 fn generate_create_table_sql(table_name: &str, columns: &[ColumnInfo]) -> String {
     let mut column_defs = Vec::new();
     let mut table_constraints = Vec::new();
@@ -680,6 +1727,18 @@ fn generate_create_table_sql(table_name: &str, columns: &[ColumnInfo]) -> String
     for column in columns {
         let mut def = format!("\"{}\" {}", column.name, column.sql_type);
 
+        if let Some(generated) = &column.generated {
+            let storage = if generated.stored {
+                "STORED"
+            } else {
+                "VIRTUAL"
+            };
+            def.push_str(&format!(
+                " GENERATED ALWAYS AS ({}) {}",
+                generated.expression, storage
+            ));
+        }
+
         if !column.nullable {
             def.push_str(" NOT NULL");
         }
@@ -696,7 +1755,13 @@ fn generate_create_table_sql(table_name: &str, columns: &[ColumnInfo]) -> String
             def.push_str(" PRIMARY KEY");
         }
 
-        // Column defaults are now handled by the macro's column definition
+        if column.check_bool {
+            def.push_str(&format!(" CHECK (\"{}\" IN (0, 1))", column.name));
+        }
+
+        if let Some(default) = &column.default {
+            def.push_str(&format!(" DEFAULT {}", default));
+        }
 
         column_defs.push(def);
     }
@@ -721,9 +1786,15 @@ fn generate_data_migration_sql(
     let source_map: HashMap<String, &ColumnInfo> =
         source_columns.iter().map(|c| (c.name.clone(), c)).collect();
 
+    // Generated columns are computed by the new table and can't be inserted into
+    let target_columns: Vec<&ColumnInfo> = target_columns
+        .iter()
+        .filter(|c| c.generated.is_none())
+        .collect();
+
     let mut select_columns = Vec::new();
 
-    for target_col in target_columns {
+    for target_col in &target_columns {
         if let Some(_source_col) = source_map.get(&target_col.name) {
             // Column exists in both, copy directly
             select_columns.push(format!("\"{}\"", target_col.name));
@@ -787,7 +1858,7 @@ async fn check_backups_retention(
 
         if should_delete {
             let drop_sql = format!("DROP TABLE IF EXISTS \"{}\"", old_table.name);
-            db.conn.execute(&drop_sql, ()).await.map_err(|e| {
+            db.execute(&drop_sql).await.map_err(|e| {
                 Error::DatabaseError(format!("Failed to drop old migration table: {}", e))
             })?;
 
@@ -820,10 +1891,10 @@ async fn get_all_migration_tables(
         pattern
     );
 
-    let mut rows =
-        db.conn.query(&query, ()).await.map_err(|e| {
-            Error::DatabaseError(format!("Failed to query migration tables: {}", e))
-        })?;
+    let mut rows = db
+        .query(&query, vec![])
+        .await
+        .map_err(|e| Error::DatabaseError(format!("Failed to query migration tables: {}", e)))?;
 
     let mut migration_tables = Vec::new();
 
@@ -1,10 +1,21 @@
+use crate::stream::ROWID_COLUMN;
+use crate::utils::{ORSO_HEADER_LEN, TEXT_HEADER_LEN};
 use crate::{
-    Aggregate, Database, Error, FilterOperator, PaginatedResult, Pagination, QueryBuilder, Result,
-    SearchFilter, Sort, SortOrder, Utils,
+    Aggregate, ColumnCompressionReport, ConflictStrategy, CursorPaginatedResult, CursorPagination,
+    Database, Error, ExportCompression, FieldType, Filter, FilterOperations, FilterOperator,
+    PaginatedResult, Pagination, QueryBuilder, RecordStream, Result, SearchFilter, Sort, SortOrder,
+    TableStats, TransactionMode, Utils, WriteContext,
 };
+use std::borrow::Cow;
 use std::collections::HashMap;
 use tracing::{debug, info, trace, warn};
 
+/// Upper bound on bound parameters per batch upsert statement (SQLite's historical default)
+const BATCH_UPSERT_MAX_PARAMS: usize = 999;
+
+// Alias of the window count read by `find_where_with_count`; stripped before `from_map`
+const TOTAL_COLUMN: &str = "__orso_total";
+
 /// CRUD operations for database models
 pub struct CrudOperations;
 
@@ -21,15 +32,335 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
-        let map = model.to_map()?;
-        let columns: Vec<String> = map.keys().cloned().collect();
+        Self::validate_table_name(table_name)?;
+        let map = model.to_map_cow()?;
+        Self::insert_map_with_table::<T>(map, db, table_name).await
+    }
+
+    /// Insert a row given as stored column values, e.g. one read from another table with
+    /// `row_to_map`. Compressed columns take their encoded blob as is, so rows can move
+    /// between tables or shards without decompressing and recompressing. Generated columns
+    /// in the map are skipped; SQLite recomputes them.
+    pub async fn insert_raw<T>(map: HashMap<String, crate::Value>, db: &Database) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::insert_raw_with_table::<T>(map, db, T::table_name()).await
+    }
+
+    pub async fn insert_raw_with_table<T>(
+        mut map: HashMap<String, crate::Value>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        for (name, _) in T::generated_fields() {
+            map.remove(name);
+        }
+
+        let field_names = T::field_names();
+        let compressed = T::compressed_field_names();
+        for (column, value) in &map {
+            if !field_names.contains(&column.as_str()) {
+                return Err(Error::Validation(format!(
+                    "Unknown column '{column}' for table {table_name}"
+                )));
+            }
+            let is_compressed = compressed.contains(&column.as_str());
+            if is_compressed && !matches!(value, crate::Value::Blob(_) | crate::Value::Null) {
+                return Err(Error::Validation(format!(
+                    "Column '{column}' is compressed; insert_raw expects its encoded blob"
+                )));
+            }
+        }
+
+        let map = map.into_iter().map(|(k, v)| (Cow::Owned(k), v)).collect();
+        Self::insert_map_with_table::<T>(map, db, table_name).await
+    }
+
+    /// Copy every row of `source_table` into the model's table, renaming columns per
+    /// `column_map` (source name -> target name). Other source columns keep their name if
+    /// the model has it and are dropped otherwise. Rows are decoded into the model before
+    /// inserting, so compressed columns are re-encoded for the target schema. The source is
+    /// read `fetch_batch_size` rows at a time and the copy runs in one transaction (the
+    /// caller's, when `db` is a transaction's), so a failing row leaves the target as it
+    /// was. Returns the number of rows copied.
+    pub async fn migrate_from<T>(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        Self::migrate_from_with_table::<T>(source_table, column_map, db, T::table_name()).await
+    }
+
+    pub async fn migrate_from_with_table<T>(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        Self::migrate_from_with_transform_and_table::<T, _>(
+            source_table,
+            column_map,
+            |_: &mut HashMap<String, crate::Value>| Ok(()),
+            db,
+            table_name,
+        )
+        .await
+    }
+
+    /// `migrate_from` with `transform` applied to each row once its columns are renamed,
+    /// before it's decoded into the model. The row still holds every source column, so a
+    /// model field can be derived from columns the model doesn't have.
+    pub async fn migrate_from_with_transform<T, F>(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        transform: F,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        F: FnMut(&mut HashMap<String, crate::Value>) -> Result<()>,
+    {
+        Self::migrate_from_with_transform_and_table::<T, F>(
+            source_table,
+            column_map,
+            transform,
+            db,
+            T::table_name(),
+        )
+        .await
+    }
+
+    pub async fn migrate_from_with_transform_and_table<T, F>(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        mut transform: F,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        F: FnMut(&mut HashMap<String, crate::Value>) -> Result<()>,
+    {
+        Self::validate_table_name(source_table)?;
+        Self::validate_table_name(table_name)?;
+        let field_names = T::field_names();
+        for target in column_map.values() {
+            if !field_names.contains(target) {
+                return Err(Error::Validation(format!(
+                    "Unknown column '{target}' for table {table_name}"
+                )));
+            }
+        }
+
+        // One transaction for the whole copy, unless the caller already has one open
+        if !db.conn().is_autocommit() {
+            return Self::migrate_rows::<T, F>(
+                source_table,
+                &column_map,
+                &mut transform,
+                db,
+                table_name,
+            )
+            .await;
+        }
+        let tx = db.transaction_with_mode(TransactionMode::Immediate).await?;
+        let copied = Self::migrate_rows::<T, F>(
+            source_table,
+            &column_map,
+            &mut transform,
+            tx.db(),
+            table_name,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(copied)
+    }
+
+    // Copy the source table in rowid order, `fetch_batch_size` rows at a time
+    async fn migrate_rows<T, F>(
+        source_table: &str,
+        column_map: &HashMap<&str, &str>,
+        transform: &mut F,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        F: FnMut(&mut HashMap<String, crate::Value>) -> Result<()>,
+    {
+        let field_names = T::field_names();
+        let batch_size = db.config.fetch_batch_size.max(1);
+        let mut last_rowid = None;
+        let mut copied = 0;
+        loop {
+            let mut sql = format!("SELECT rowid AS {ROWID_COLUMN}, * FROM {source_table}");
+            let mut params = Vec::new();
+            if let Some(last_rowid) = last_rowid {
+                sql.push_str(" WHERE rowid > ?");
+                params.push(libsql::Value::Integer(last_rowid));
+            }
+            sql.push_str(&format!(" ORDER BY rowid LIMIT {batch_size}"));
+
+            let mut rows = db.query(&sql, params).await?;
+            let mut models = Vec::with_capacity(batch_size);
+            while let Some(row) = rows.next().await? {
+                last_rowid = Some(row.get::<i64>(0)?);
+                let mut map: HashMap<String, crate::Value> = Self::row_to_map(&row)?
+                    .into_iter()
+                    .filter(|(column, _)| column != ROWID_COLUMN)
+                    .map(|(column, value)| {
+                        let target = column_map.get(column.as_str()).map(|t| t.to_string());
+                        (target.unwrap_or(column), value)
+                    })
+                    .collect();
+                transform(&mut map)?;
+                map.retain(|column, _| field_names.contains(&column.as_str()));
+                models.push(T::from_map(map)?);
+            }
+
+            Self::batch_insert_with_table(&models, db, table_name).await?;
+            copied += models.len() as u64;
+            if models.len() < batch_size {
+                break;
+            }
+        }
+
+        info!(
+            source = source_table,
+            target = table_name,
+            rows = copied,
+            "Migrated rows between tables"
+        );
+        Ok(copied)
+    }
+
+    /// Insert a new record, stamping audit columns from the write context
+    pub async fn insert_with_context<T>(
+        model: &T,
+        context: &WriteContext,
+        db: &Database,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::insert_with_context_and_table(model, context, db, T::table_name()).await
+    }
+
+    pub async fn insert_with_context_and_table<T>(
+        model: &T,
+        context: &WriteContext,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let mut map = model.to_map_cow()?;
+        if let Some(user_id) = &context.user_id {
+            for field in [T::created_by_field(), T::updated_by_field()]
+                .into_iter()
+                .flatten()
+            {
+                map.insert(Cow::Borrowed(field), crate::Value::Text(user_id.clone()));
+            }
+        }
+        Self::insert_map_with_table::<T>(map, db, table_name).await
+    }
+
+    async fn insert_map_with_table<T>(
+        map: HashMap<Cow<'static, str>, crate::Value>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::insert_map_with_strategy::<T>(map, ConflictStrategy::Fail, db, table_name).await?;
+        Ok(())
+    }
+
+    /// Insert a new record, resolving collisions with existing rows per `strategy`.
+    /// Returns the number of rows written (0 when an `Ignore` insert was skipped).
+    pub async fn insert_with_strategy<T>(
+        model: &T,
+        strategy: ConflictStrategy,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        Self::insert_with_strategy_and_table(model, strategy, db, T::table_name()).await
+    }
+
+    pub async fn insert_with_strategy_and_table<T>(
+        model: &T,
+        strategy: ConflictStrategy,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let map = model.to_map_cow()?;
+        Self::insert_map_with_strategy::<T>(map, strategy, db, table_name).await
+    }
+
+    async fn insert_map_with_strategy<T>(
+        map: HashMap<Cow<'static, str>, crate::Value>,
+        strategy: ConflictStrategy,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        let columns: Vec<&str> = map.keys().map(|column| column.as_ref()).collect();
         let values: Vec<String> = map.keys().map(|_| "?".to_string()).collect();
 
+        let (verb, on_conflict) = match strategy {
+            ConflictStrategy::Fail => ("INSERT", String::new()),
+            ConflictStrategy::Ignore => ("INSERT OR IGNORE", String::new()),
+            ConflictStrategy::Replace => ("INSERT OR REPLACE", String::new()),
+            ConflictStrategy::Update => {
+                let unique_columns = T::unique_fields();
+                if unique_columns.is_empty() {
+                    return Err(Error::Validation(
+                        "No unique columns defined with orso_column(unique) for upsert".to_string(),
+                    ));
+                }
+                let update_sets = Self::conflict_update_sets::<T>(&columns, &unique_columns);
+                let action = if update_sets.is_empty() {
+                    "DO NOTHING".to_string()
+                } else {
+                    format!("DO UPDATE SET {}", update_sets.join(", "))
+                };
+                (
+                    "INSERT",
+                    format!(" ON CONFLICT ({}) {}", unique_columns.join(", "), action),
+                )
+            }
+        };
+
         let sql = format!(
-            "INSERT INTO {} ({}) VALUES ({})",
+            "{} INTO {} ({}) VALUES ({}){}",
+            verb,
             table_name,
             columns.join(", "),
-            values.join(", ")
+            values.join(", "),
+            on_conflict
         );
 
         debug!(sql = %sql, "Executing SQL");
@@ -37,10 +368,42 @@ impl CrudOperations {
         let params: Vec<libsql::Value> =
             map.values().map(|v| T::value_to_libsql_value(v)).collect();
 
-        db.conn.execute(&sql, params).await?;
+        let affected = db.execute_write(&sql, params).await?;
 
         debug!(table = table_name, "Successfully created record");
-        Ok(())
+        Ok(affected)
+    }
+
+    // SET list for an upsert's DO UPDATE: every inserted non-unique column takes the new
+    // value, and touch-on-update fields take the current time
+    fn conflict_update_sets<T>(columns: &[impl AsRef<str>], unique_columns: &[&str]) -> Vec<String>
+    where
+        T: crate::Orso,
+    {
+        let touched = T::touch_on_update_fields();
+        let mut update_sets: Vec<String> = columns
+            .iter()
+            .map(|col| col.as_ref())
+            .filter(|col| !unique_columns.contains(col)) // Don't update unique columns
+            .map(|col| {
+                // For touch-on-update fields, use database function instead of excluded value
+                if touched.contains(&col) {
+                    format!("{} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')", col)
+                } else {
+                    format!("{} = excluded.{}", col, col)
+                }
+            })
+            .collect();
+        // Touch-on-update fields left out of the insert (null on the model) still get touched
+        if !update_sets.is_empty() {
+            update_sets.extend(
+                touched
+                    .iter()
+                    .filter(|field| !columns.iter().any(|col| col.as_ref() == **field))
+                    .map(|field| format!("{} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')", field)),
+            );
+        }
+        update_sets
     }
 
     /// Insert or update a record based on whether it has a primary key
@@ -59,6 +422,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         if let Some(id) = model.get_primary_key() {
             // Check if record exists
             match Self::find_by_id_with_table::<T>(&id, db, table_name).await? {
@@ -94,6 +458,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let unique_columns: Vec<&str> = T::unique_fields();
         if unique_columns.is_empty() {
             return Err(Error::Validation(
@@ -129,7 +494,7 @@ impl CrudOperations {
         info!(table = table_name, "Checking for existing record");
         debug!(sql = %sql, "Executing upsert query");
 
-        let mut rows = db.conn.query(&sql, where_params).await?;
+        let mut rows = db.query(&sql, where_params).await?;
 
         if let Some(row) = rows.next().await? {
             // Record exists, update it
@@ -146,141 +511,760 @@ impl CrudOperations {
         }
     }
 
-    /// Insert multiple records using Turso batch operations for optimal performance
-    pub async fn batch_create<T>(models: &[T], db: &Database) -> Result<()>
+    /// Insert multiple records using Turso batch operations for optimal performance
+    pub async fn batch_create<T>(models: &[T], db: &Database) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::batch_insert_with_table(models, db, T::table_name()).await
+    }
+
+    pub async fn batch_insert_with_table<T>(
+        models: &[T],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        if models.is_empty() {
+            return Ok(());
+        }
+
+        // Use proper parameterized queries instead of building SQL strings
+        for model in models {
+            let map = model.to_map_cow()?;
+            let columns: Vec<&str> = map.keys().map(|column| column.as_ref()).collect();
+            let placeholders: Vec<String> = columns.iter().map(|_| "?".to_string()).collect();
+            let params: Vec<libsql::Value> =
+                map.values().map(|v| T::value_to_libsql_value(v)).collect();
+
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table_name,
+                columns.join(", "),
+                placeholders.join(", ")
+            );
+
+            db.execute_write(&sql, params).await?;
+        }
+        Ok(())
+    }
+
+    /// Insert each record on its own, so a failing row doesn't stop the rest. Returns one
+    /// outcome per record, in order: the stored primary key or the row's error.
+    pub async fn batch_create_lenient<T>(models: &[T], db: &Database) -> Result<Vec<Result<String>>>
+    where
+        T: crate::Orso,
+    {
+        Self::batch_create_lenient_with_table(models, db, T::table_name()).await
+    }
+
+    pub async fn batch_create_lenient_with_table<T>(
+        models: &[T],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Result<String>>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+
+        let mut outcomes = Vec::with_capacity(models.len());
+        for model in models {
+            let outcome = Self::insert_returning_key::<T>(model, db, table_name).await;
+            if let Err(e) = &outcome {
+                warn!(table = table_name, error = %e, "Skipping record that failed to insert");
+            }
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+
+    async fn insert_returning_key<T>(model: &T, db: &Database, table_name: &str) -> Result<String>
+    where
+        T: crate::Orso,
+    {
+        let map = model.to_map_cow()?;
+        let columns: Vec<&str> = map.keys().map(|column| column.as_ref()).collect();
+        let placeholders: Vec<String> = columns.iter().map(|_| "?".to_string()).collect();
+        let params: Vec<libsql::Value> =
+            map.values().map(|v| T::value_to_libsql_value(v)).collect();
+
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+            table_name,
+            columns.join(", "),
+            placeholders.join(", "),
+            T::primary_key_field()
+        );
+        debug!(sql = %sql, "Executing insert returning");
+
+        let mut rows = db.query(&sql, params).await?;
+        let row = rows
+            .next()
+            .await?
+            .ok_or_else(|| Error::DatabaseError("Insert returned no row".to_string()))?;
+        Self::key_text::<T>(row.get_value(0)?)
+    }
+
+    /// Find a record by its primary key
+    pub async fn find_by_id<T>(id: &str, db: &Database) -> Result<Option<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_by_id_with_table(id, db, T::table_name()).await
+    }
+
+    pub async fn find_by_id_with_table<T>(
+        id: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Option<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let sql = format!(
+            "SELECT * FROM {} WHERE {} = ? LIMIT 1",
+            table_name,
+            T::primary_key_field() // Use dynamic primary key field name
+        );
+
+        debug!(table =table_name, id = %id, "Finding record by ID");
+        debug!(sql = %sql, "Executing find query");
+
+        let mut rows = db.query(&sql, vec![Self::key_param::<T>(id)?]).await?;
+
+        if let Some(row) = rows.next().await? {
+            let map = T::row_to_map(&row)?;
+            debug!(table =table_name, id = %id, "Found record");
+            Ok(Some(T::from_map(map)?))
+        } else {
+            debug!(table =table_name, id = %id, "No record found");
+            Ok(None)
+        }
+    }
+
+    /// Find a single record by a specific condition
+    pub async fn find_one<T>(filter: FilterOperator, db: &Database) -> Result<Option<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_one_with_table(filter, db, T::table_name()).await
+    }
+
+    pub async fn find_one_with_table<T>(
+        filter: FilterOperator,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Option<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let builder = QueryBuilder::new(table_name)._where(filter).limit(1);
+
+        let results = builder.execute::<T>(db).await?;
+        Ok(results.into_iter().next())
+    }
+
+    /// Find all records, in the model's `default_sort` order if it declares one
+    pub async fn find_all<T>(db: &Database) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_all_with_table(db, T::table_name()).await
+    }
+
+    pub async fn find_all_with_table<T>(db: &Database, table_name: &str) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let mut builder = QueryBuilder::new(table_name);
+        if let Some(sorts) = T::default_sort() {
+            builder = builder.order_by_multiple(sorts);
+        }
+        builder.execute::<T>(db).await
+    }
+
+    /// Up to `n` records sampled with `ORDER BY RANDOM()`. SQLite sorts the whole
+    /// table to do this, so keep it to small samples.
+    pub async fn find_random<T>(n: usize, db: &Database) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_random_with_table(n, db, T::table_name()).await
+    }
+
+    pub async fn find_random_with_table<T>(
+        n: usize,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+
+        let sql = format!("SELECT * FROM {table_name} ORDER BY RANDOM() LIMIT ?");
+        let mut rows = db
+            .query(&sql, vec![libsql::Value::Integer(n as i64)])
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await? {
+            results.push(T::from_map(T::row_to_map(&row)?)?);
+        }
+        Ok(results)
+    }
+
+    /// Write every record to `writer` as newline-delimited JSON, one row at a time so memory
+    /// stays flat however large the table is. Returns the number of records written.
+    pub async fn export_ndjson<T, W>(writer: W, db: &Database) -> Result<u64>
+    where
+        T: crate::Orso,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        Self::export_ndjson_with_table::<T, W>(writer, db, T::table_name()).await
+    }
+
+    pub async fn export_ndjson_with_table<T, W>(
+        mut writer: W,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        Self::validate_table_name(table_name)?;
+        let (sql, params) = QueryBuilder::new(table_name).build()?;
+        let mut rows = db.query(&sql, params).await?;
+
+        let mut count = 0u64;
+        let mut line = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let record = T::from_map(T::row_to_map(&row)?)?;
+            line.clear();
+            serde_json::to_writer(&mut line, &record)?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+            count += 1;
+        }
+        writer.flush().await?;
+
+        info!(table = table_name, count, "Exported records as NDJSON");
+        Ok(count)
+    }
+
+    /// `export_ndjson` through a gzip or zstd encoder, e.g. to write `.ndjson.zst` directly
+    pub async fn export_ndjson_compressed<T, W>(
+        writer: W,
+        compression: ExportCompression,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        Self::export_ndjson_compressed_with_table::<T, W>(writer, compression, db, T::table_name())
+            .await
+    }
+
+    pub async fn export_ndjson_compressed_with_table<T, W>(
+        writer: W,
+        compression: ExportCompression,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
+        use tokio::io::AsyncWriteExt;
+
+        // Encoders only write their trailer on shutdown
+        match compression {
+            ExportCompression::None => {
+                Self::export_ndjson_with_table::<T, W>(writer, db, table_name).await
+            }
+            ExportCompression::Gzip => {
+                let mut encoder = GzipEncoder::new(writer);
+                let count =
+                    Self::export_ndjson_with_table::<T, _>(&mut encoder, db, table_name).await?;
+                encoder.shutdown().await?;
+                Ok(count)
+            }
+            ExportCompression::Zstd => {
+                let mut encoder = ZstdEncoder::new(writer);
+                let count =
+                    Self::export_ndjson_with_table::<T, _>(&mut encoder, db, table_name).await?;
+                encoder.shutdown().await?;
+                Ok(count)
+            }
+        }
+    }
+
+    /// Read newline-delimited JSON (as written by `export_ndjson`) and insert the records,
+    /// `chunk_size` rows per transaction. Gzip and zstd input is detected and decompressed.
+    /// Blank lines are skipped. Returns records inserted.
+    pub async fn import_ndjson<T, R>(reader: R, chunk_size: usize, db: &Database) -> Result<u64>
+    where
+        T: crate::Orso,
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        Self::import_ndjson_with_table::<T, R>(reader, chunk_size, db, T::table_name()).await
+    }
+
+    pub async fn import_ndjson_with_table<T, R>(
+        reader: R,
+        chunk_size: usize,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
+        use tokio::io::{AsyncBufReadExt, BufReader};
+
+        Self::validate_table_name(table_name)?;
+        if chunk_size == 0 {
+            return Err(Error::Validation(
+                "Import chunk size must be at least 1".to_string(),
+            ));
+        }
+
+        let mut reader = reader;
+        let head = reader.fill_buf().await?;
+        if head.starts_with(&[0x1f, 0x8b]) {
+            let reader = BufReader::new(GzipDecoder::new(reader));
+            Self::import_ndjson_lines::<T, _>(reader, chunk_size, db, table_name).await
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            let reader = BufReader::new(ZstdDecoder::new(reader));
+            Self::import_ndjson_lines::<T, _>(reader, chunk_size, db, table_name).await
+        } else {
+            Self::import_ndjson_lines::<T, _>(reader, chunk_size, db, table_name).await
+        }
+    }
+
+    async fn import_ndjson_lines<T, R>(
+        reader: R,
+        chunk_size: usize,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        use tokio::io::AsyncBufReadExt;
+
+        let mut lines = reader.lines();
+        let mut line_number = 0usize;
+        let mut chunk: Vec<T> = Vec::with_capacity(chunk_size);
+        let mut imported = 0u64;
+        while let Some(line) = lines.next_line().await? {
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: T = serde_json::from_str(&line)
+                .map_err(|e| Error::Serialization(format!("NDJSON line {line_number}: {e}")))?;
+            chunk.push(record);
+
+            if chunk.len() == chunk_size {
+                imported += Self::insert_chunk::<T>(&chunk, db, table_name).await?;
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            imported += Self::insert_chunk::<T>(&chunk, db, table_name).await?;
+        }
+
+        info!(
+            table = table_name,
+            count = imported,
+            "Imported NDJSON records"
+        );
+        Ok(imported)
+    }
+
+    // Insert records in a single transaction
+    async fn insert_chunk<T>(models: &[T], db: &Database, table_name: &str) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        let tx = db.transaction_with_mode(TransactionMode::Immediate).await?;
+        for model in models {
+            let map = model.to_map_cow()?;
+            let columns: Vec<&str> = map.keys().map(|column| column.as_ref()).collect();
+            let placeholders: Vec<String> = columns.iter().map(|_| "?".to_string()).collect();
+            let params: Vec<libsql::Value> =
+                map.values().map(|v| T::value_to_libsql_value(v)).collect();
+
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table_name,
+                columns.join(", "),
+                placeholders.join(", ")
+            );
+            tx.db().execute_write(&sql, params).await?;
+        }
+        tx.commit().await?;
+
+        debug!(
+            table = table_name,
+            rows = models.len(),
+            "Inserted import chunk"
+        );
+        Ok(models.len() as u64)
+    }
+
+    /// Find records with a filter, in the model's `default_sort` order if it declares one
+    pub async fn find_where<T>(filter: FilterOperator, db: &Database) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_where_with_table(filter, db, T::table_name()).await
+    }
+
+    pub async fn find_where_with_table<T>(
+        filter: FilterOperator,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let mut builder = QueryBuilder::new(table_name)._where(filter);
+        if let Some(sorts) = T::default_sort() {
+            builder = builder.order_by_multiple(sorts);
+        }
+        builder.execute::<T>(db).await
+    }
+
+    /// Records matching `filter`, grouped by the stored value of `key_column`
+    pub async fn find_where_grouped<T>(
+        filter: FilterOperator,
+        key_column: &str,
+        db: &Database,
+    ) -> Result<HashMap<crate::Value, Vec<T>>>
     where
         T: crate::Orso,
     {
-        Self::batch_insert_with_table(models, db, T::table_name()).await
+        Self::find_where_grouped_with_table::<T>(filter, key_column, db, T::table_name()).await
     }
 
-    pub async fn batch_insert_with_table<T>(
-        models: &[T],
+    pub async fn find_where_grouped_with_table<T>(
+        filter: FilterOperator,
+        key_column: &str,
         db: &Database,
         table_name: &str,
-    ) -> Result<()>
+    ) -> Result<HashMap<crate::Value, Vec<T>>>
     where
         T: crate::Orso,
     {
-        if models.is_empty() {
-            return Ok(());
+        Self::validate_table_name(table_name)?;
+        if !T::field_names().contains(&key_column) {
+            return Err(Error::Validation(format!(
+                "Unknown column '{key_column}' for table {table_name}"
+            )));
         }
 
-        // Use proper parameterized queries instead of building SQL strings
-        for model in models {
-            let map = model.to_map()?;
-            let columns: Vec<String> = map.keys().cloned().collect();
-            let placeholders: Vec<String> = columns.iter().map(|_| "?".to_string()).collect();
-            let params: Vec<libsql::Value> = map.values().map(|v| T::value_to_libsql_value(v)).collect();
+        let maps = QueryBuilder::new(table_name)
+            ._where(filter)
+            .execute_maps(db)
+            .await?;
+        let mut groups: HashMap<crate::Value, Vec<T>> = HashMap::new();
+        for map in maps {
+            let key = map.get(key_column).cloned().unwrap_or(crate::Value::Null);
+            groups.entry(key).or_default().push(T::from_map(map)?);
+        }
+        Ok(groups)
+    }
 
-            let sql = format!(
-                "INSERT INTO {} ({}) VALUES ({})",
-                table_name,
-                columns.join(", "),
-                placeholders.join(", ")
-            );
+    /// Stream records (optionally filtered) in rowid order, fetched
+    /// `fetch_batch_size` rows per round-trip
+    pub fn stream<T>(filter: Option<FilterOperator>, db: &Database) -> Result<RecordStream<'_, T>>
+    where
+        T: crate::Orso,
+    {
+        Self::stream_with_table(filter, db, T::table_name())
+    }
 
-            db.conn.execute(&sql, params).await?;
-        }
-        Ok(())
+    pub fn stream_with_table<'a, T>(
+        filter: Option<FilterOperator>,
+        db: &'a Database,
+        table_name: &str,
+    ) -> Result<RecordStream<'a, T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        Ok(RecordStream::new(db, table_name, filter))
     }
 
-    /// Find a record by its primary key
-    pub async fn find_by_id<T>(id: &str, db: &Database) -> Result<Option<T>>
+    /// Keyset pagination ordered by `key_column`, with the primary key breaking ties. The
+    /// page starts after `pagination.cursor` (at it with `include_cursor`), and
+    /// `next_cursor` encodes the last row's key and primary key. `key_column` should be
+    /// NOT NULL, since NULL keys can't be compared against a cursor.
+    pub async fn find_after<T>(
+        filter: Option<FilterOperator>,
+        key_column: &str,
+        pagination: &CursorPagination,
+        db: &Database,
+    ) -> Result<CursorPaginatedResult<T>>
     where
         T: crate::Orso,
     {
-        Self::find_by_id_with_table(id, db, T::table_name()).await
+        Self::find_after_with_table(filter, key_column, pagination, db, T::table_name()).await
     }
 
-    pub async fn find_by_id_with_table<T>(
-        id: &str,
+    pub async fn find_after_with_table<T>(
+        filter: Option<FilterOperator>,
+        key_column: &str,
+        pagination: &CursorPagination,
         db: &Database,
         table_name: &str,
-    ) -> Result<Option<T>>
+    ) -> Result<CursorPaginatedResult<T>>
     where
         T: crate::Orso,
     {
-        let sql = format!(
-            "SELECT * FROM {} WHERE {} = ? LIMIT 1",
-            table_name,
-            T::primary_key_field() // Use dynamic primary key field name
-        );
+        Self::validate_table_name(table_name)?;
+        if !T::field_names().contains(&key_column) {
+            return Err(Error::Validation(format!(
+                "Unknown column '{key_column}' for table {table_name}"
+            )));
+        }
+        if T::compressed_field_names().contains(&key_column) {
+            return Err(Error::Validation(format!(
+                "Column '{key_column}' is compressed and can't be used as a page key"
+            )));
+        }
+        let pk_field = T::primary_key_field();
 
-        debug!(table =table_name, id = %id, "Finding record by ID");
-        debug!(sql = %sql, "Executing find query");
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        if let Some(filter) = &filter {
+            let (sql, filter_params) = FilterOperations::build_filter_operator(filter)?;
+            conditions.push(format!("({sql})"));
+            params.extend(filter_params);
+        }
+        if let Some(cursor) = &pagination.cursor {
+            let (key, id): (crate::Value, crate::Value) = serde_json::from_str(cursor)
+                .map_err(|e| Error::Pagination(format!("Invalid cursor '{cursor}': {e}")))?;
+            let comparison = if pagination.include_cursor { ">=" } else { ">" };
+            conditions.push(format!("({key_column}, {pk_field}) {comparison} (?, ?)"));
+            params.push(T::value_to_libsql_value(&key));
+            params.push(T::value_to_libsql_value(&id));
+        }
 
-        let mut rows = db
-            .conn
-            .query(&sql, vec![libsql::Value::Text(id.to_string())])
-            .await?;
+        let limit = pagination.limit().max(1) as usize;
+        let mut sql = format!("SELECT * FROM {table_name}");
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        // One extra row tells whether there is a next page
+        sql.push_str(&format!(
+            " ORDER BY {key_column}, {pk_field} LIMIT {}",
+            limit + 1
+        ));
+
+        let mut rows = db.query(&sql, params).await?;
+        let mut maps = Vec::new();
+        while let Some(row) = rows.next().await? {
+            maps.push(T::row_to_map(&row)?);
+        }
 
-        if let Some(row) = rows.next().await? {
-            let map = T::row_to_map(&row)?;
-            debug!(table =table_name, id = %id, "Found record");
-            Ok(Some(T::from_map(map)?))
-        } else {
-            debug!(table =table_name, id = %id, "No record found");
-            Ok(None)
+        let has_next = maps.len() > limit;
+        maps.truncate(limit);
+        let next_cursor = match maps.last() {
+            Some(last) if has_next => {
+                let key = last.get(key_column).cloned().unwrap_or(crate::Value::Null);
+                let id = last.get(pk_field).cloned().unwrap_or(crate::Value::Null);
+                Some(serde_json::to_string(&(key, id))?)
+            }
+            _ => None,
+        };
+
+        let mut data = Vec::with_capacity(maps.len());
+        for map in maps {
+            data.push(T::from_map(map)?);
         }
+        let mut pagination = pagination.clone();
+        pagination.has_prev = pagination.cursor.is_some();
+        pagination.has_next = has_next;
+        pagination.next_cursor = next_cursor;
+        Ok(CursorPaginatedResult::new(data, pagination))
     }
 
-    /// Find a single record by a specific condition
-    pub async fn find_one<T>(filter: FilterOperator, db: &Database) -> Result<Option<T>>
+    /// Records whose `updated_at` (falling back to `created_at`) is after `since`, oldest
+    /// first. Fails with `Error::Validation` when the model has neither timestamp.
+    pub async fn changed_since<T>(
+        since: chrono::DateTime<chrono::Utc>,
+        db: &Database,
+    ) -> Result<Vec<T>>
     where
         T: crate::Orso,
     {
-        Self::find_one_with_table(filter, db, T::table_name()).await
+        Self::changed_since_with_table::<T>(since, db, T::table_name()).await
     }
 
-    pub async fn find_one_with_table<T>(
-        filter: FilterOperator,
+    pub async fn changed_since_with_table<T>(
+        since: chrono::DateTime<chrono::Utc>,
         db: &Database,
         table_name: &str,
-    ) -> Result<Option<T>>
+    ) -> Result<Vec<T>>
     where
         T: crate::Orso,
     {
-        let builder = QueryBuilder::new(table_name)._where(filter).limit(1);
+        Self::validate_table_name(table_name)?;
+        let column = T::updated_at_field()
+            .or_else(T::created_at_field)
+            .ok_or_else(|| {
+                Error::Validation(format!(
+                    "Table {table_name} has no updated_at or created_at column to track changes"
+                ))
+            })?;
 
-        let results = builder.execute::<T>(db).await?;
-        Ok(results.into_iter().next())
+        // julianday compares the instants, whatever the fractional digits of each side
+        let sql = format!(
+            "SELECT * FROM {table_name} WHERE julianday({column}) > julianday(?) \
+             ORDER BY {column}, {}",
+            T::primary_key_field()
+        );
+        let params = vec![libsql::Value::Text(crate::Utils::create_timestamp(since))];
+        let mut rows = db.query(&sql, params).await?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next().await? {
+            records.push(T::from_map(T::row_to_map(&row)?)?);
+        }
+        Ok(records)
     }
 
-    /// Find all records
-    pub async fn find_all<T>(db: &Database) -> Result<Vec<T>>
+    /// Child records whose `fk_column` holds `parent_id`
+    pub async fn find_children<C>(parent_id: &str, fk_column: &str, db: &Database) -> Result<Vec<C>>
     where
-        T: crate::Orso,
+        C: crate::Orso,
     {
-        Self::find_all_with_table(db, T::table_name()).await
+        Self::find_children_with_table::<C>(parent_id, fk_column, db, C::table_name()).await
     }
 
-    pub async fn find_all_with_table<T>(db: &Database, table_name: &str) -> Result<Vec<T>>
+    pub async fn find_children_with_table<C>(
+        parent_id: &str,
+        fk_column: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<C>>
     where
-        T: crate::Orso,
+        C: crate::Orso,
     {
-        let builder = QueryBuilder::new(table_name);
-        builder.execute::<T>(db).await
+        Self::validate_table_name(table_name)?;
+        if !C::field_names().contains(&fk_column) {
+            return Err(Error::Validation(format!(
+                "Unknown column '{fk_column}' for table {table_name}"
+            )));
+        }
+
+        let filter = FilterOperator::Single(crate::Filter::eq(fk_column, parent_id));
+        QueryBuilder::new(table_name)
+            ._where(filter)
+            .execute::<C>(db)
+            .await
     }
 
-    /// Find records with a filter
-    pub async fn find_where<T>(filter: FilterOperator, db: &Database) -> Result<Vec<T>>
+    /// Full-text search over the model's `fts` columns, ranked best match first.
+    /// `query` uses FTS5 `MATCH` syntax (words, "phrases", prefix*, AND/OR/NOT).
+    pub async fn full_text_search<T>(query: &str, db: &Database) -> Result<Vec<T>>
     where
         T: crate::Orso,
     {
-        Self::find_where_with_table(filter, db, T::table_name()).await
+        Self::full_text_search_with_table::<T>(query, db, T::table_name()).await
     }
 
-    pub async fn find_where_with_table<T>(
-        filter: FilterOperator,
+    pub async fn full_text_search_with_table<T>(
+        query: &str,
         db: &Database,
         table_name: &str,
     ) -> Result<Vec<T>>
     where
         T: crate::Orso,
     {
-        let builder = QueryBuilder::new(table_name)._where(filter);
-        builder.execute::<T>(db).await
+        Self::validate_table_name(table_name)?;
+        if T::fts_fields().is_empty() {
+            return Err(Error::Validation(format!(
+                "No columns marked orso_column(fts) for table {table_name}"
+            )));
+        }
+
+        let fts_table = crate::migrations::fts_table_name(table_name);
+        let sql = format!(
+            "SELECT {table_name}.* FROM {table_name} JOIN {fts_table} ON {fts_table}.rowid = {table_name}.rowid WHERE {fts_table} MATCH ? ORDER BY {fts_table}.rank"
+        );
+        debug!(sql = %sql, "Executing full-text search");
+
+        let mut rows = db
+            .query(&sql, vec![libsql::Value::Text(query.to_string())])
+            .await?;
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let map = T::row_to_map(&row)?;
+            results.push(T::from_map(map)?);
+        }
+        Ok(results)
+    }
+
+    /// Find records across several tables sharing the model's schema (e.g. monthly
+    /// shards) with one `UNION ALL` query, applying `filter` to each table
+    pub async fn find_all_union<T>(
+        tables: &[&str],
+        filter: Option<FilterOperator>,
+        db: &Database,
+    ) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        if tables.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Name the columns so every SELECT lines up regardless of each table's column order
+        let columns = T::field_names();
+        let mut selects = Vec::with_capacity(tables.len());
+        let mut params = Vec::new();
+        for table_name in tables {
+            Self::validate_table_name(table_name)?;
+            let mut builder = QueryBuilder::new(*table_name).select_columns(&columns);
+            if let Some(filter) = &filter {
+                builder = builder._where(filter.clone());
+            }
+            let (sql, table_params) = builder.build()?;
+            selects.push(sql);
+            params.extend(table_params);
+        }
+
+        let sql = selects.join(" UNION ALL ");
+        debug!(sql = %sql, "Executing union query");
+
+        let mut rows = db.query(&sql, params).await?;
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let map = T::row_to_map(&row)?;
+            results.push(T::from_map(map)?);
+        }
+        Ok(results)
     }
 
     pub async fn find_latest<T>(db: &Database) -> Result<Option<T>>
@@ -294,6 +1278,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let created_at_field = T::created_at_field().unwrap_or("created_at");
         let sort = Sort::new(created_at_field, SortOrder::Desc);
         let builder = QueryBuilder::new(table_name).order_by(sort).limit(1);
@@ -318,6 +1303,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let created_at_field = T::created_at_field().unwrap_or("created_at");
         let sort = Sort::new(created_at_field, SortOrder::Desc);
         let builder = QueryBuilder::new(table_name)
@@ -344,6 +1330,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let created_at_field = T::created_at_field().unwrap_or("created_at");
         let sort = Sort::new(created_at_field, SortOrder::Asc);
         let builder = QueryBuilder::new(table_name)
@@ -366,6 +1353,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let builder = QueryBuilder::new(table_name).limit(1);
         let count = builder.execute_count(db).await?;
         Ok(count > 0)
@@ -387,6 +1375,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let builder = QueryBuilder::new(table_name)._where(filter).limit(1);
         let count = builder.execute_count(db).await?;
         Ok(count > 0)
@@ -409,6 +1398,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let filter =
             FilterOperator::Single(crate::Filter::new_simple(field, crate::Operator::Eq, value));
         let builder = QueryBuilder::new(table_name)._where(filter);
@@ -436,6 +1426,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let filter =
             FilterOperator::Single(crate::Filter::new_simple(field, crate::Operator::Eq, value));
         let created_at_field = T::created_at_field().unwrap_or("created_at");
@@ -469,6 +1460,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let filter =
             FilterOperator::Single(crate::Filter::new_simple(field, crate::Operator::Eq, value));
         let created_at_field = T::created_at_field().unwrap_or("created_at");
@@ -497,20 +1489,57 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         if ids.is_empty() {
             return Ok(Vec::new());
         }
 
         let id_values: Vec<crate::Value> = ids
             .iter()
-            .map(|id| crate::Value::Text(id.to_string()))
-            .collect();
+            .map(|id| Self::key_value::<T>(id))
+            .collect::<Result<_>>()?;
         let pk_field = T::primary_key_field();
         let filter = FilterOperator::Single(crate::Filter::in_values(pk_field, id_values));
         let builder = QueryBuilder::new(table_name)._where(filter);
         builder.execute::<T>(db).await
     }
 
+    /// Primary keys of the records matching `filter` (all records for `None`), in key order.
+    /// Only the key column is read, so compressed columns are never decoded; the result
+    /// feeds `find_by_ids` and `batch_delete`.
+    pub async fn find_ids<T>(filter: Option<FilterOperator>, db: &Database) -> Result<Vec<String>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_ids_with_table::<T>(filter, db, T::table_name()).await
+    }
+
+    pub async fn find_ids_with_table<T>(
+        filter: Option<FilterOperator>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<String>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let pk_field = T::primary_key_field();
+        let mut builder = QueryBuilder::new(table_name)
+            .select_column(pk_field)
+            .order_by(Sort::new(pk_field, SortOrder::Asc));
+        if let Some(filter) = filter {
+            builder = builder._where(filter);
+        }
+
+        let (sql, params) = builder.build()?;
+        let mut rows = db.query(&sql, params).await?;
+        let mut ids = Vec::new();
+        while let Some(row) = rows.next().await? {
+            ids.push(Self::key_text::<T>(row.get_value(0)?)?);
+        }
+        Ok(ids)
+    }
+
     /// Find records by multiple values for same field (IN clause)
     pub async fn find_by_field_in<T>(
         field: &str,
@@ -532,6 +1561,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         if values.is_empty() {
             return Ok(Vec::new());
         }
@@ -560,6 +1590,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let builder = QueryBuilder::new(table_name);
         builder.execute_paginated::<T>(db, pagination).await
     }
@@ -585,6 +1616,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let builder = QueryBuilder::new(table_name)._where(filter);
         builder.execute_paginated::<T>(db, pagination).await
     }
@@ -610,6 +1642,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let filter = search_filter.to_filter_operator();
         let pagination = pagination.unwrap_or(&Pagination::default()).clone();
 
@@ -628,8 +1661,9 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let sql = format!("SELECT COUNT(*) FROM {}", table_name);
-        let mut rows = db.conn.query(&sql, vec![libsql::Value::Null; 0]).await?;
+        let mut rows = db.query(&sql, vec![libsql::Value::Null; 0]).await?;
 
         if let Some(row) = rows.next().await? {
             row.get_value(0)
@@ -660,10 +1694,11 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let builder = QueryBuilder::new(table_name)._where(filter);
 
         let (sql, params) = builder.build_count()?;
-        let mut rows = db.conn.query(&sql, params).await?;
+        let mut rows = db.query(&sql, params).await?;
 
         if let Some(row) = rows.next().await? {
             row.get_value(0)
@@ -674,8 +1709,221 @@ impl CrudOperations {
                 })
                 .ok_or_else(|| Error::Query("Failed to get count".to_string()))
         } else {
-            Err(Error::Query("No count result".to_string()))
-        }
+            Err(Error::Query("No count result".to_string()))
+        }
+    }
+
+    /// Compressed and estimated uncompressed size of every compressed column. Byte totals
+    /// are summed by SQL `length()`; element counts are read from each value's header, so
+    /// only the header bytes leave the database and nothing is decompressed. Legacy JSON
+    /// values left by a migration have no header and are fetched whole to count them.
+    pub async fn compression_report<T>(db: &Database) -> Result<Vec<ColumnCompressionReport>>
+    where
+        T: crate::Orso,
+    {
+        Self::compression_report_with_table::<T>(db, T::table_name()).await
+    }
+
+    pub async fn compression_report_with_table<T>(
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<ColumnCompressionReport>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let text_columns: Vec<&str> = T::field_names()
+            .into_iter()
+            .zip(T::field_compressed_text())
+            .filter_map(|(name, text)| text.then_some(name))
+            .collect();
+
+        let mut reports = Vec::new();
+        for column in T::compressed_field_names() {
+            let mut report = ColumnCompressionReport {
+                column: column.to_string(),
+                ..Default::default()
+            };
+
+            // length() of a BLOB is its size in bytes; legacy TEXT is cast to count bytes too
+            let sql = format!(
+                "SELECT COUNT({column}), COALESCE(SUM(length(CAST({column} AS BLOB))), 0) \
+                 FROM {table_name}"
+            );
+            let mut rows = db.query(&sql, vec![]).await?;
+            if let Some(row) = rows.next().await? {
+                report.rows = row.get::<i64>(0)? as u64;
+                report.compressed_bytes = row.get::<i64>(1)? as u64;
+            }
+
+            // Only the header of encoded values ('ORSO' / 'ORST' magic) is fetched
+            let header_len = ORSO_HEADER_LEN.max(TEXT_HEADER_LEN);
+            let sql = format!(
+                "SELECT CASE WHEN substr({column}, 1, 4) IN (x'4F52534F', x'4F525354') \
+                 THEN substr({column}, 1, {header_len}) ELSE {column} END \
+                 FROM {table_name} WHERE length({column}) > 0"
+            );
+            let mut rows = db.query(&sql, vec![]).await?;
+            while let Some(row) = rows.next().await? {
+                let elements = match row.get_value(0)? {
+                    libsql::Value::Blob(blob) if blob.starts_with(b"ORSO") => {
+                        Utils::compressed_element_count(&blob)
+                    }
+                    libsql::Value::Blob(blob) if blob.starts_with(b"ORST") => {
+                        Utils::compressed_text_len(&blob)
+                    }
+                    libsql::Value::Blob(blob) => {
+                        serde_json::from_slice::<Vec<serde_json::Value>>(&blob)
+                            .ok()
+                            .map(|values| values.len() as u64)
+                    }
+                    libsql::Value::Text(text) => {
+                        serde_json::from_str::<Vec<serde_json::Value>>(&text)
+                            .ok()
+                            .map(|values| values.len() as u64)
+                    }
+                    _ => None,
+                };
+                report.elements += elements.unwrap_or(0);
+            }
+
+            // Text is one byte per element, vectors are estimated at 8
+            let element_size = if text_columns.contains(&column) { 1 } else { 8 };
+            report.estimated_uncompressed_bytes = report.elements * element_size;
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+
+    /// Total bytes stored in a compressed column across the table
+    pub async fn column_blob_bytes<T>(column: &str, db: &Database) -> Result<i64>
+    where
+        T: crate::Orso,
+    {
+        Self::column_blob_bytes_with_table::<T>(column, db, T::table_name()).await
+    }
+
+    pub async fn column_blob_bytes_with_table<T>(
+        column: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<i64>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        if !T::field_names().contains(&column) {
+            return Err(Error::Validation(format!(
+                "Unknown column '{column}' for table {table_name}"
+            )));
+        }
+        if !T::compressed_field_names().contains(&column) {
+            return Err(Error::Validation(format!(
+                "Column '{column}' is not a compressed BLOB column"
+            )));
+        }
+
+        // length() of a BLOB is its size in bytes
+        let sql = format!("SELECT COALESCE(SUM(length({column})), 0) FROM {table_name}");
+        let mut rows = db.query(&sql, vec![]).await?;
+
+        if let Some(row) = rows.next().await? {
+            row.get_value(0)
+                .ok()
+                .and_then(|v| match v {
+                    libsql::Value::Integer(i) => Some(i),
+                    _ => None,
+                })
+                .ok_or_else(|| Error::Query("Failed to get column size".to_string()))
+        } else {
+            Err(Error::Query("No column size result".to_string()))
+        }
+    }
+
+    /// Records whose compressed column holds exactly `values`, found by comparing the
+    /// stored BLOB with `values` encoded by the same codec. Encoding is deterministic, so
+    /// no row needs decompressing. For columns stored with the signed integer codec
+    /// (`Vec<i64>`, `Vec<i32>`).
+    pub async fn find_by_compressed<T>(
+        column: &str,
+        values: &[i64],
+        db: &Database,
+    ) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::find_by_compressed_with_table::<T>(column, values, db, T::table_name()).await
+    }
+
+    pub async fn find_by_compressed_with_table<T>(
+        column: &str,
+        values: &[i64],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let position = T::field_names()
+            .iter()
+            .position(|name| *name == column)
+            .ok_or_else(|| {
+                Error::Validation(format!("Unknown column '{column}' for table {table_name}"))
+            })?;
+        if T::field_compressed().get(position) != Some(&true) {
+            return Err(Error::Validation(format!(
+                "Column '{column}' is not a compressed BLOB column"
+            )));
+        }
+        if matches!(T::field_types().get(position), Some(FieldType::Numeric)) {
+            return Err(Error::Validation(format!(
+                "Column '{column}' is compressed with the float codec, not the integer codec"
+            )));
+        }
+
+        let blob = crate::IntegerCodec::default().compress_i64(values)?;
+        let filter = FilterOperator::Single(Filter::eq(column, crate::Value::Blob(blob)));
+        Self::find_where_with_table(filter, db, table_name).await
+    }
+
+    /// Row count and approximate stored bytes for the model's table
+    pub async fn stats<T>(db: &Database) -> Result<TableStats>
+    where
+        T: crate::Orso,
+    {
+        Self::stats_with_table::<T>(db, T::table_name()).await
+    }
+
+    pub async fn stats_with_table<T>(db: &Database, table_name: &str) -> Result<TableStats>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let lengths: Vec<String> = T::field_names()
+            .iter()
+            .map(|column| format!("COALESCE(SUM(length({column})), 0)"))
+            .collect();
+        let bytes = if lengths.is_empty() {
+            "0".to_string()
+        } else {
+            lengths.join(" + ")
+        };
+        let sql = format!("SELECT COUNT(*), {bytes} FROM {table_name}");
+        let mut rows = db.query(&sql, vec![]).await?;
+
+        let row = rows
+            .next()
+            .await?
+            .ok_or_else(|| Error::Query("No table stats result".to_string()))?;
+        let read = |idx: i32| match row.get_value(idx) {
+            Ok(libsql::Value::Integer(i)) => Ok(i as u64),
+            _ => Err(Error::Query("Failed to get table stats".to_string())),
+        };
+        Ok(TableStats {
+            rows: read(0)?,
+            bytes: read(1)?,
+        })
     }
 
     /// Update a record
@@ -690,25 +1938,72 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let id = model.get_primary_key().ok_or_else(|| {
             Error::Validation("Cannot update record without primary key".to_string())
         })?;
 
-        let map = model.to_map()?;
+        let map = model.to_map_cow()?;
+        Self::update_map_with_table::<T>(id, map, db, table_name).await
+    }
+
+    /// Update a record, stamping the updated_by column from the write context
+    pub async fn update_with_context<T>(
+        model: &T,
+        context: &WriteContext,
+        db: &Database,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::update_with_context_and_table(model, context, db, T::table_name()).await
+    }
+
+    pub async fn update_with_context_and_table<T>(
+        model: &T,
+        context: &WriteContext,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let id = model.get_primary_key().ok_or_else(|| {
+            Error::Validation("Cannot update record without primary key".to_string())
+        })?;
+
+        let mut map = model.to_map_cow()?;
+        if let (Some(user_id), Some(field)) = (&context.user_id, T::updated_by_field()) {
+            map.insert(Cow::Borrowed(field), crate::Value::Text(user_id.clone()));
+        }
+        Self::update_map_with_table::<T>(id, map, db, table_name).await
+    }
+
+    async fn update_map_with_table<T>(
+        id: String,
+        map: HashMap<Cow<'static, str>, crate::Value>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()>
+    where
+        T: crate::Orso,
+    {
         let pk_field = T::primary_key_field();
-        let updated_at_field = T::updated_at_field();
+        let touched = T::touch_on_update_fields();
 
         let mut set_clauses = Vec::new();
         for k in map.keys() {
-            if k != pk_field {
-                // For updated_at fields, use database function instead of model value
-                if updated_at_field.is_some() && k == updated_at_field.unwrap() {
-                    set_clauses.push(format!("{k} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')"));
-                } else {
-                    set_clauses.push(format!("{k} = ?"));
-                }
+            if k != pk_field && !touched.contains(&k.as_ref()) {
+                set_clauses.push(format!("{k} = ?"));
             }
         }
+        // Touch-on-update fields use the database clock instead of the model value
+        for field in &touched {
+            set_clauses.push(format!(
+                "{field} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')"
+            ));
+        }
 
         let sql = format!(
             "UPDATE {} SET {} WHERE {} = ?",
@@ -722,19 +2017,101 @@ impl CrudOperations {
 
         let mut params: Vec<libsql::Value> = map
             .iter()
-            .filter(|(k, _)| {
-                k != &pk_field && !(updated_at_field.is_some() && k == &updated_at_field.unwrap())
-            })
+            .filter(|(k, _)| *k != pk_field && !touched.contains(&k.as_ref()))
             .map(|(_, v)| T::value_to_libsql_value(v))
             .collect();
-        params.push(libsql::Value::Text(id.clone()));
+        params.push(Self::key_param::<T>(&id)?);
 
-        db.conn.execute(&sql, params).await?;
+        db.execute_write(&sql, params).await?;
 
         info!(table = table_name, id = %id, "Successfully updated record");
         Ok(())
     }
 
+    /// Update only the given columns of a record, leaving every other column untouched
+    pub async fn update_fields<T>(
+        id: &str,
+        fields: HashMap<&str, crate::Value>,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        Self::update_fields_with_table::<T>(id, fields, db, T::table_name()).await
+    }
+
+    pub async fn update_fields_with_table<T>(
+        id: &str,
+        fields: HashMap<&str, crate::Value>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        if fields.is_empty() {
+            return Err(Error::Validation(
+                "No fields provided for partial update".to_string(),
+            ));
+        }
+
+        let field_names = T::field_names();
+        let pk_field = T::primary_key_field();
+        let touched = T::touch_on_update_fields();
+
+        let mut set_clauses = Vec::new();
+        let mut params = Vec::new();
+
+        for (column, value) in &fields {
+            if !field_names.contains(column) {
+                return Err(Error::Validation(format!(
+                    "Unknown column '{column}' for table {table_name}"
+                )));
+            }
+            if *column == pk_field {
+                return Err(Error::Validation(
+                    "Cannot update the primary key with update_fields".to_string(),
+                ));
+            }
+            if T::generated_fields().iter().any(|(name, _)| name == column) {
+                return Err(Error::Validation(format!(
+                    "Cannot update generated column '{column}'"
+                )));
+            }
+            // Touch-on-update and schema-version fields are always set below
+            if touched.contains(column) || T::schema_version_field() == Some(*column) {
+                continue;
+            }
+            set_clauses.push(format!("{column} = ?"));
+            params.push(T::value_to_libsql_value(value));
+        }
+
+        for field in &touched {
+            set_clauses.push(format!(
+                "{field} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')"
+            ));
+        }
+        if let Some(field) = T::schema_version_field() {
+            set_clauses.push(format!("{field} = ?"));
+            params.push(libsql::Value::Integer(T::schema_version()));
+        }
+        params.push(Self::key_param::<T>(id)?);
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {} = ?",
+            table_name,
+            set_clauses.join(", "),
+            pk_field
+        );
+
+        info!(table = table_name, id = %id, "Updating record fields");
+        debug!(sql = %sql, "Executing partial update query");
+
+        let affected = db.execute_write(&sql, params).await?;
+        Ok(affected)
+    }
+
     /// Update multiple records using Turso batch operations
     pub async fn batch_update<T>(models: &[T], db: &Database) -> Result<()>
     where
@@ -751,6 +2128,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         if models.is_empty() {
             return Ok(());
         }
@@ -760,27 +2138,29 @@ impl CrudOperations {
                 Error::Validation("Cannot batch update record without primary key".to_string())
             })?;
 
-            let map = model.to_map()?;
+            let map = model.to_map_cow()?;
             let pk_field = T::primary_key_field();
-            let updated_at_field = T::updated_at_field();
+            let touched = T::touch_on_update_fields();
             
             let mut set_clauses = Vec::new();
             let mut params = Vec::new();
             
             for (k, v) in &map {
-                if k != pk_field {
-                    // For updated_at fields, use database function instead of model value
-                    if updated_at_field.is_some() && k == updated_at_field.unwrap() {
-                        set_clauses.push(format!("{} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')", k));
-                    } else {
-                        set_clauses.push(format!("{} = ?", k));
-                        params.push(T::value_to_libsql_value(v));
-                    }
+                if k != pk_field && !touched.contains(&k.as_ref()) {
+                    set_clauses.push(format!("{} = ?", k));
+                    params.push(T::value_to_libsql_value(v));
                 }
             }
+            // Touch-on-update fields use the database clock instead of the model value
+            for field in &touched {
+                set_clauses.push(format!(
+                    "{} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')",
+                    field
+                ));
+            }
             
             // Add the ID parameter for the WHERE clause
-            params.push(libsql::Value::Text(id.clone()));
+            params.push(Self::key_param::<T>(&id)?);
 
             let sql = format!(
                 "UPDATE {} SET {} WHERE {} = ?",
@@ -789,7 +2169,7 @@ impl CrudOperations {
                 pk_field
             );
 
-            db.conn.execute(&sql, params).await?;
+            db.execute_write(&sql, params).await?;
         }
         Ok(())
     }
@@ -806,6 +2186,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let id = model.get_primary_key().ok_or_else(|| {
             Error::Validation("Cannot delete record without primary key".to_string())
         })?;
@@ -819,7 +2200,8 @@ impl CrudOperations {
         info!(table = table_name, id = %id, "Deleting record");
         debug!(sql = %sql, "Executing delete query");
 
-        db.conn.execute(&sql, vec![libsql::Value::Text(id)]).await?;
+        db.execute_write(&sql, vec![Self::key_param::<T>(&id)?])
+            .await?;
         info!(table = table_name, "Successfully deleted record");
         Ok(true)
     }
@@ -840,6 +2222,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         if ids.is_empty() {
             return Ok(0);
         }
@@ -849,11 +2232,16 @@ impl CrudOperations {
 
         let pk_field = T::primary_key_field();
         for id in ids {
+            let literal = match Self::key_value::<T>(id)? {
+                crate::Value::Blob(bytes) => format!(
+                    "X'{}'",
+                    bytes.iter().map(|b| format!("{b:02x}")).collect::<String>()
+                ),
+                _ => format!("'{}'", id.replace("'", "''")),
+            };
             let sql = format!(
-                "DELETE FROM {} WHERE {} = '{}'",
-                table_name,
-                pk_field,
-                id.replace("'", "''")
+                "DELETE FROM {} WHERE {} = {}",
+                table_name, pk_field, literal
             );
             stmts.push(sql);
         }
@@ -861,12 +2249,13 @@ impl CrudOperations {
         stmts.push("COMMIT".to_string());
         let batch_sql = stmts.join(";");
 
-        db.conn.execute_batch(&batch_sql).await?;
+        db.execute_batch_write(&batch_sql).await?;
         Ok(ids.len() as u64)
     }
 
-    /// Upsert multiple records using Turso batch operations with automatically detected unique columns
-    pub async fn batch_upsert<T>(models: &[T], db: &Database) -> Result<()>
+    /// Upsert multiple records in one transaction with chunked `INSERT ... ON CONFLICT DO UPDATE`
+    /// keyed on the unique columns. Returns the number of rows inserted or updated.
+    pub async fn batch_upsert<T>(models: &[T], db: &Database) -> Result<u64>
     where
         T: crate::Orso,
     {
@@ -877,12 +2266,13 @@ impl CrudOperations {
         models: &[T],
         db: &Database,
         table_name: &str,
-    ) -> Result<()>
+    ) -> Result<u64>
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         if models.is_empty() {
-            return Ok(());
+            return Ok(0);
         }
 
         let unique_columns: Vec<&str> = T::unique_fields();
@@ -892,54 +2282,73 @@ impl CrudOperations {
             ));
         }
 
+        // Rows sharing a column set go into the same multi-row statement
+        type RowGroup = (Vec<String>, Vec<HashMap<String, crate::Value>>);
+        let mut groups: Vec<RowGroup> = Vec::new();
         for model in models {
             let map = model.to_map()?;
-            
-            // Build conflict columns for ON CONFLICT clause
-            let conflict_columns = unique_columns.join(", ");
+            let mut columns: Vec<String> = map.keys().cloned().collect();
+            columns.sort();
+            match groups.last_mut() {
+                Some((group_columns, rows)) if *group_columns == columns => rows.push(map),
+                _ => groups.push((columns, vec![map])),
+            }
+        }
 
-            let columns: Vec<String> = map.keys().cloned().collect();
-            let placeholders: Vec<String> = columns.iter().map(|_| "?".to_string()).collect();
-            let params: Vec<libsql::Value> = map.values().map(|v| T::value_to_libsql_value(v)).collect();
+        // Immediate takes the write lock up front, so a busy database is retried at BEGIN
+        // rather than failing partway through the chunks
+        let tx = db.transaction_with_mode(TransactionMode::Immediate).await?;
+        let mut affected = 0;
 
-            // Build UPDATE SET clause for conflict resolution
-            let updated_at_field = T::updated_at_field();
-            let update_sets: Vec<String> = columns
-                .iter()
-                .filter(|col| !unique_columns.contains(&col.as_str())) // Don't update unique columns
-                .map(|col| {
-                    // For updated_at fields, use database function instead of excluded value
-                    if updated_at_field.is_some() && col == updated_at_field.unwrap() {
-                        format!("{} = strftime('%Y-%m-%dT%H:%M:%S.000Z', 'now')", col)
-                    } else {
-                        format!("{} = excluded.{}", col, col)
-                    }
-                })
-                .collect();
-
-            let sql = if update_sets.is_empty() {
-                // If no columns to update, just ignore conflicts
-                format!(
-                    "INSERT OR IGNORE INTO {} ({}) VALUES ({})",
-                    table_name,
-                    columns.join(", "),
-                    placeholders.join(", ")
-                )
-            } else {
-                // Use INSERT ... ON CONFLICT DO UPDATE for proper upsert
-                format!(
-                    "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {}",
-                    table_name,
-                    columns.join(", "),
-                    placeholders.join(", "),
-                    conflict_columns,
-                    update_sets.join(", ")
-                )
-            };
+        for (columns, rows) in &groups {
+            let conflict_columns = unique_columns.join(", ");
+            let row_placeholders = format!("({})", vec!["?"; columns.len()].join(", "));
 
-            db.conn.execute(&sql, params).await?;
+            // Build UPDATE SET clause for conflict resolution
+            let update_sets = Self::conflict_update_sets::<T>(columns, &unique_columns);
+
+            let rows_per_chunk = (BATCH_UPSERT_MAX_PARAMS / columns.len().max(1)).max(1);
+            for chunk in rows.chunks(rows_per_chunk) {
+                let values = vec![row_placeholders.as_str(); chunk.len()].join(", ");
+                let sql = if update_sets.is_empty() {
+                    // If no columns to update, just ignore conflicts
+                    format!(
+                        "INSERT OR IGNORE INTO {} ({}) VALUES {}",
+                        table_name,
+                        columns.join(", "),
+                        values
+                    )
+                } else {
+                    format!(
+                        "INSERT INTO {} ({}) VALUES {} ON CONFLICT ({}) DO UPDATE SET {}",
+                        table_name,
+                        columns.join(", "),
+                        values,
+                        conflict_columns,
+                        update_sets.join(", ")
+                    )
+                };
+
+                let params: Vec<libsql::Value> = chunk
+                    .iter()
+                    .flat_map(|map| {
+                        columns
+                            .iter()
+                            .map(|col| T::value_to_libsql_value(&map[col]))
+                    })
+                    .collect();
+
+                debug!(
+                    table = table_name,
+                    rows = chunk.len(),
+                    "Executing batch upsert chunk"
+                );
+                affected += tx.db().execute_write(&sql, params).await?;
+            }
         }
-        Ok(())
+
+        tx.commit().await?;
+        Ok(affected)
     }
 
     /// Delete records with a filter
@@ -958,17 +2367,52 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let builder = QueryBuilder::new(table_name)._where(filter);
 
         let (sql, params) = builder.build()?;
         let delete_sql = sql.replace("SELECT *", "DELETE");
-        db.conn.execute(&delete_sql, params).await?;
+        db.execute_write(&delete_sql, params).await?;
 
         // Note: SQLite doesn't return the number of affected rows directly
         // This is a simplified implementation
         Ok(1)
     }
 
+    /// Delete records with a filter and return them as they were before deletion
+    pub async fn delete_where_returning<T>(filter: FilterOperator, db: &Database) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::delete_where_returning_with_table::<T>(filter, db, T::table_name()).await
+    }
+
+    pub async fn delete_where_returning_with_table<T>(
+        filter: FilterOperator,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<T>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let builder = QueryBuilder::new(table_name)._where(filter);
+
+        let (sql, params) = builder.build()?;
+        let delete_sql = format!("{} RETURNING *", sql.replace("SELECT *", "DELETE"));
+        debug!(sql = %delete_sql, "Executing delete returning");
+
+        let mut rows = db.query(&delete_sql, params).await?;
+        let mut deleted = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let map = T::row_to_map(&row)?;
+            deleted.push(T::from_map(map)?);
+        }
+
+        info!(table = table_name, count = deleted.len(), "Deleted records");
+        Ok(deleted)
+    }
+
     /// List records with optional sorting and pagination
     pub async fn list<T>(
         sort: Option<Vec<Sort>>,
@@ -990,9 +2434,11 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let mut builder = QueryBuilder::new(table_name);
 
-        if let Some(sorts) = sort {
+        if let Some(sorts) = sort.or_else(T::default_sort) {
+            Self::validate_sort_columns::<T>(&sorts)?;
             builder = builder.order_by_multiple(sorts);
         }
 
@@ -1023,9 +2469,11 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let mut builder = QueryBuilder::new(table_name)._where(filter);
 
-        if let Some(sorts) = sort {
+        if let Some(sorts) = sort.or_else(T::default_sort) {
+            Self::validate_sort_columns::<T>(&sorts)?;
             builder = builder.order_by_multiple(sorts);
         }
 
@@ -1033,6 +2481,87 @@ impl CrudOperations {
         builder.execute_paginated::<T>(db, &pagination).await
     }
 
+    /// One page of records matching `filter` plus the total number of matches, read in a
+    /// single query with a `COUNT(*) OVER ()` window instead of a separate count scan.
+    /// Falls back to a page query and a count query if the engine has no window functions,
+    /// e.g. SQLite builds older than 3.25; other errors are returned as is. Unsorted pages
+    /// follow the model's `default_sort`.
+    pub async fn find_where_with_count<T>(
+        filter: FilterOperator,
+        sort: Option<Vec<Sort>>,
+        pagination: Option<&Pagination>,
+        db: &Database,
+    ) -> Result<(Vec<T>, u64)>
+    where
+        T: crate::Orso,
+    {
+        Self::find_where_with_count_and_table(filter, sort, pagination, db, T::table_name()).await
+    }
+
+    pub async fn find_where_with_count_and_table<T>(
+        filter: FilterOperator,
+        sort: Option<Vec<Sort>>,
+        pagination: Option<&Pagination>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<(Vec<T>, u64)>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        let pagination = pagination.cloned().unwrap_or_default();
+        let mut builder = QueryBuilder::new(table_name)
+            ._where(filter.clone())
+            .limit(pagination.limit())
+            .offset(pagination.offset());
+        if let Some(sorts) = sort.or_else(T::default_sort) {
+            Self::validate_sort_columns::<T>(&sorts)?;
+            builder = builder.order_by_multiple(sorts);
+        }
+
+        let windowed = builder
+            .clone()
+            .select(vec![
+                "*".to_string(),
+                format!("COUNT(*) OVER () AS {TOTAL_COLUMN}"),
+            ])
+            .execute_maps(db)
+            .await;
+        let maps = match windowed {
+            Ok(maps) => maps,
+            // Engines without window functions; any other error is the query's own
+            Err(Error::Sql(e)) if Self::is_window_unsupported(&e) => {
+                debug!(error = %e, "Window count failed, counting separately");
+                let data = builder.execute::<T>(db).await?;
+                let total = Self::count_where_with_table::<T>(filter, db, table_name).await?;
+                return Ok((data, total));
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Every row carries the total; a page past the end has no rows to carry it
+        let total = match maps.first().and_then(|map| map.get(TOTAL_COLUMN)) {
+            Some(crate::Value::Integer(total)) => *total as u64,
+            _ if pagination.offset() == 0 => 0,
+            _ => Self::count_where_with_table::<T>(filter, db, table_name).await?,
+        };
+        let mut data = Vec::with_capacity(maps.len());
+        for mut map in maps {
+            map.remove(TOTAL_COLUMN);
+            data.push(T::from_map(map)?);
+        }
+        Ok((data, total))
+    }
+
+    // Whether a query failed because the engine lacks `COUNT(*) OVER ()`. SQLite before
+    // 3.25 reads `OVER` as an alias and then stops at its parenthesis.
+    fn is_window_unsupported(err: &libsql::Error) -> bool {
+        let message = err.to_string().to_lowercase();
+        message.contains("no such function")
+            || message.contains("window")
+            || message.contains("near \"(\": syntax error")
+    }
+
     /// Execute a custom query
     pub async fn query<T>(builder: QueryBuilder, db: &Database) -> Result<Vec<T>>
     where
@@ -1100,6 +2629,7 @@ impl CrudOperations {
     where
         T: crate::Orso,
     {
+        Self::validate_table_name(table_name)?;
         let mut builder = QueryBuilder::new(table_name).aggregate(function, column, None::<String>);
 
         if let Some(filter) = filter {
@@ -1107,7 +2637,7 @@ impl CrudOperations {
         }
 
         let (sql, params) = builder.build()?;
-        let mut rows = db.conn.query(&sql, params).await?;
+        let mut rows = db.query(&sql, params).await?;
 
         if let Some(row) = rows.next().await? {
             let value = row
@@ -1125,6 +2655,216 @@ impl CrudOperations {
         }
     }
 
+    /// Number of rows for each distinct value of `column`
+    pub async fn count_by<T>(column: &str, db: &Database) -> Result<HashMap<crate::Value, i64>>
+    where
+        T: crate::Orso,
+    {
+        Self::count_by_with_table::<T>(column, db, T::table_name()).await
+    }
+
+    pub async fn count_by_with_table<T>(
+        column: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<crate::Value, i64>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        Self::grouped_counts::<T>(column, None, db, table_name).await
+    }
+
+    /// Row count per distinct value of `column`, keeping only the values with more than
+    /// `min_count` rows (`GROUP BY ... HAVING COUNT(*) > ?`)
+    pub async fn count_by_having<T>(
+        column: &str,
+        min_count: i64,
+        db: &Database,
+    ) -> Result<HashMap<crate::Value, i64>>
+    where
+        T: crate::Orso,
+    {
+        Self::count_by_having_with_table::<T>(column, min_count, db, T::table_name()).await
+    }
+
+    pub async fn count_by_having_with_table<T>(
+        column: &str,
+        min_count: i64,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<crate::Value, i64>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        Self::grouped_counts::<T>(column, Some(min_count), db, table_name).await
+    }
+
+    async fn grouped_counts<T>(
+        column: &str,
+        min_count: Option<i64>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<crate::Value, i64>>
+    where
+        T: crate::Orso,
+    {
+        if !T::field_names().contains(&column) {
+            return Err(Error::Validation(format!(
+                "Unknown column '{column}' for table {table_name}"
+            )));
+        }
+
+        let mut sql = format!("SELECT {column}, COUNT(*) FROM {table_name} GROUP BY {column}");
+        let mut params = Vec::new();
+        if let Some(min_count) = min_count {
+            sql.push_str(" HAVING COUNT(*) > ?");
+            params.push(libsql::Value::Integer(min_count));
+        }
+        let mut rows = db.query(&sql, params).await?;
+
+        let mut counts = HashMap::new();
+        while let Some(row) = rows.next().await? {
+            let value = Utils::libsql_value_to_value(&row.get_value(0)?);
+            let count: i64 = row.get(1)?;
+            counts.insert(value, count);
+        }
+        Ok(counts)
+    }
+
+    /// Count the rows matching each labeled filter with a single
+    /// `SUM(CASE WHEN ... END)` query
+    pub async fn count_multi<T>(
+        filters: &[(&str, FilterOperator)],
+        db: &Database,
+    ) -> Result<HashMap<String, i64>>
+    where
+        T: crate::Orso,
+    {
+        Self::count_multi_with_table::<T>(filters, db, T::table_name()).await
+    }
+
+    pub async fn count_multi_with_table<T>(
+        filters: &[(&str, FilterOperator)],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<String, i64>>
+    where
+        T: crate::Orso,
+    {
+        Self::validate_table_name(table_name)?;
+        if filters.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut sums = Vec::with_capacity(filters.len());
+        let mut params = Vec::new();
+        for (label, filter) in filters {
+            let (condition, filter_params) = FilterOperations::build_filter_operator(filter)?;
+            sums.push(format!(
+                "COALESCE(SUM(CASE WHEN {} THEN 1 ELSE 0 END), 0) AS \"{}\"",
+                condition,
+                label.replace('"', "\"\"")
+            ));
+            params.extend(filter_params);
+        }
+
+        let sql = format!("SELECT {} FROM {}", sums.join(", "), table_name);
+        debug!(sql = %sql, "Executing labeled counts");
+
+        let mut rows = db.query(&sql, params).await?;
+        let row = rows
+            .next()
+            .await?
+            .ok_or_else(|| Error::Query("No count result".to_string()))?;
+
+        let mut counts = HashMap::with_capacity(filters.len());
+        for (i, (label, _)) in filters.iter().enumerate() {
+            let count: i64 = row.get(i as i32)?;
+            counts.insert(label.to_string(), count);
+        }
+        Ok(counts)
+    }
+
+    /// Reject table name overrides that can't name a table
+    /// Table names are spliced into SQL unquoted, so only plain identifiers
+    /// (`[A-Za-z_][A-Za-z0-9_]*`) are accepted
+    pub(crate) fn validate_table_name(table_name: &str) -> Result<()> {
+        if table_name.is_empty() {
+            return Err(Error::Validation(
+                "Table name must not be empty".to_string(),
+            ));
+        }
+        let mut chars = table_name.chars();
+        let starts_ok = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+        if !starts_ok || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::Validation(format!(
+                "Invalid table name '{table_name}': expected letters, digits and underscores"
+            )));
+        }
+        Ok(())
+    }
+
+    // Compressed columns hold encoded bytes, so ordering by them is meaningless
+    fn validate_sort_columns<T>(sorts: &[Sort]) -> Result<()>
+    where
+        T: crate::Orso,
+    {
+        let compressed = T::compressed_field_names();
+        for sort in sorts {
+            if compressed.contains(&sort.column.as_str()) {
+                return Err(Error::Validation(format!(
+                    "Cannot sort by compressed column '{}' on {}: it is stored as encoded bytes",
+                    sort.column,
+                    T::table_name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Stored form of a primary key given as a string: the 16 UUID bytes for uuid_blob
+    // keys, the text itself otherwise
+    fn key_value<T>(id: &str) -> Result<crate::Value>
+    where
+        T: crate::Orso,
+    {
+        if T::primary_key_uuid_blob() {
+            Ok(crate::Value::Blob(Utils::uuid_text_to_blob(id)?))
+        } else {
+            Ok(crate::Value::Text(id.to_string()))
+        }
+    }
+
+    // String form of a stored primary key, the inverse of key_value
+    fn key_text<T>(value: libsql::Value) -> Result<String>
+    where
+        T: crate::Orso,
+    {
+        match value {
+            libsql::Value::Text(id) => Ok(id),
+            libsql::Value::Integer(id) => Ok(id.to_string()),
+            libsql::Value::Blob(bytes) if T::primary_key_uuid_blob() => {
+                Utils::uuid_blob_to_text(&bytes).ok_or_else(|| {
+                    Error::DatabaseError("Primary key is not a 16-byte UUID".to_string())
+                })
+            }
+            other => Err(Error::DatabaseError(format!(
+                "Unexpected primary key value: {other:?}"
+            ))),
+        }
+    }
+
+    fn key_param<T>(id: &str) -> Result<libsql::Value>
+    where
+        T: crate::Orso,
+    {
+        Ok(Utils::value_to_libsql_value(&Self::key_value::<T>(id)?))
+    }
+
     /// Convert a database row to a HashMap
     pub fn row_to_map(row: &libsql::Row) -> Result<HashMap<String, crate::Value>> {
         let mut map = HashMap::new();
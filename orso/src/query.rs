@@ -460,6 +460,20 @@ impl QueryBuilder {
         Ok(results)
     }
 
+    /// Execute the query and return each row as a column map, for projections that
+    /// don't fit a model (read typed values with `ValueMapExt::get_as`)
+    pub async fn execute_maps(&self, db: &Database) -> Result<Vec<HashMap<String, crate::Value>>> {
+        let (sql, params) = self.build()?;
+        let mut rows = db.query(&sql, params).await?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await? {
+            results.push(crate::operations::CrudOperations::row_to_map(&row)?);
+        }
+
+        Ok(results)
+    }
+
     /// Execute the query with pagination
     pub async fn execute_paginated<T>(
         &self,
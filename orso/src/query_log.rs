@@ -0,0 +1,75 @@
+// Opt-in statement logging. With the `query-log` feature every statement runs inside an
+// `orso.sql` span (target `orso::sql`) carrying its SQL and bound values, and finishes
+// with an event reporting the elapsed time. Without the feature this compiles to nothing.
+use tracing::Span;
+
+pub(crate) struct QueryLog {
+    #[cfg(feature = "query-log")]
+    span: Span,
+    #[cfg(feature = "query-log")]
+    started: std::time::Instant,
+}
+
+impl QueryLog {
+    #[cfg(feature = "query-log")]
+    pub(crate) fn start(sql: &str, params: &[libsql::Value]) -> Self {
+        let span = tracing::debug_span!(
+            target: "orso::sql",
+            "orso.sql",
+            sql = %sql,
+            params = %describe_params(params),
+        );
+        Self {
+            span,
+            started: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "query-log"))]
+    pub(crate) fn start(_sql: &str, _params: &[libsql::Value]) -> Self {
+        Self {}
+    }
+
+    /// Span to instrument the statement's future with
+    pub(crate) fn span(&self) -> Span {
+        #[cfg(feature = "query-log")]
+        return self.span.clone();
+        #[cfg(not(feature = "query-log"))]
+        Span::none()
+    }
+
+    /// Log the outcome and elapsed time, passing the result through
+    pub(crate) fn finish<T, E: std::fmt::Display>(
+        &self,
+        result: std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        #[cfg(feature = "query-log")]
+        {
+            let elapsed_us = self.started.elapsed().as_micros() as u64;
+            let _entered = self.span.enter();
+            match &result {
+                Ok(_) => tracing::debug!(target: "orso::sql", elapsed_us, "Statement finished"),
+                Err(e) => {
+                    tracing::debug!(target: "orso::sql", elapsed_us, error = %e, "Statement failed")
+                }
+            }
+        }
+        result
+    }
+}
+
+// Bound values as they'd read in SQL, with blobs reduced to their size
+#[cfg(feature = "query-log")]
+fn describe_params(params: &[libsql::Value]) -> String {
+    let values: Vec<String> = params
+        .iter()
+        .map(|value| match value {
+            libsql::Value::Null => "NULL".to_string(),
+            libsql::Value::Integer(i) => i.to_string(),
+            libsql::Value::Real(r) => r.to_string(),
+            libsql::Value::Text(text) => format!("'{}'", text.replace('\'', "''")),
+            libsql::Value::Blob(bytes) => format!("<{} bytes>", bytes.len()),
+        })
+        .collect();
+    format!("[{}]", values.join(", "))
+}
@@ -0,0 +1,100 @@
+// Batched streaming reads
+use crate::{Database, FilterOperations, FilterOperator, Orso, Result};
+use std::collections::VecDeque;
+use tracing::debug;
+
+// Alias for the rowid the stream pages on; stripped before `from_map`
+pub(crate) const ROWID_COLUMN: &str = "__orso_rowid";
+
+/// Reads a table in rowid order, fetching `batch_size` rows per round-trip while
+/// handing records back one at a time.
+///
+/// Each batch is its own keyset-paged query, so a long-running stream never holds
+/// a cursor open against a remote database.
+pub struct RecordStream<'a, T: Orso> {
+    db: &'a Database,
+    table_name: String,
+    filter: Option<FilterOperator>,
+    batch_size: usize,
+    buffer: VecDeque<T>,
+    last_rowid: Option<i64>,
+    exhausted: bool,
+    batches_fetched: usize,
+}
+
+impl<'a, T: Orso> RecordStream<'a, T> {
+    pub(crate) fn new(db: &'a Database, table_name: &str, filter: Option<FilterOperator>) -> Self {
+        Self {
+            db,
+            table_name: table_name.to_string(),
+            filter,
+            batch_size: db.config.fetch_batch_size.max(1),
+            buffer: VecDeque::new(),
+            last_rowid: None,
+            exhausted: false,
+            batches_fetched: 0,
+        }
+    }
+
+    /// Override the connection's `fetch_batch_size` for this stream
+    pub fn with_batch_size(mut self, rows: usize) -> Self {
+        self.batch_size = rows.max(1);
+        self
+    }
+
+    /// Rows requested per round-trip
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Round-trips made so far
+    pub fn batches_fetched(&self) -> usize {
+        self.batches_fetched
+    }
+
+    /// The next record, or `None` once the table is exhausted
+    pub async fn next(&mut self) -> Result<Option<T>> {
+        if self.buffer.is_empty() && !self.exhausted {
+            self.fetch_batch().await?;
+        }
+        Ok(self.buffer.pop_front())
+    }
+
+    async fn fetch_batch(&mut self) -> Result<()> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        if let Some(filter) = &self.filter {
+            let (sql, filter_params) = FilterOperations::build_filter_operator(filter)?;
+            conditions.push(format!("({sql})"));
+            params.extend(filter_params);
+        }
+        if let Some(last_rowid) = self.last_rowid {
+            conditions.push("rowid > ?".to_string());
+            params.push(libsql::Value::Integer(last_rowid));
+        }
+
+        let mut sql = format!("SELECT rowid AS {ROWID_COLUMN}, * FROM {}", self.table_name);
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        sql.push_str(&format!(" ORDER BY rowid LIMIT {}", self.batch_size));
+        debug!(sql = %sql, "Fetching stream batch");
+
+        let mut rows = self.db.query(&sql, params).await?;
+        let mut fetched = 0;
+        while let Some(row) = rows.next().await? {
+            self.last_rowid = Some(row.get::<i64>(0)?);
+            let mut map = T::row_to_map(&row)?;
+            map.remove(ROWID_COLUMN);
+            self.buffer.push_back(T::from_map(map)?);
+            fetched += 1;
+        }
+
+        self.batches_fetched += 1;
+        // A short batch means there is nothing after it
+        if fetched < self.batch_size {
+            self.exhausted = true;
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use crate::{self as orso, FloatingCodec, IntegerCodec, Migrations, Utils};
+    use crate::{self as orso, FloatingCodec, IntegerCodec, MigrationTrait, Migrations, Utils};
     use orso::{
-        migration, Database, DatabaseConfig, Filter, FilterOperator, Operator, Orso, Pagination,
-        Sort, SortOrder, Value,
+        migration, ColumnAspect, ConflictStrategy, Database, DatabaseConfig, Filter,
+        FilterOperator, Operator, Orso, Pagination, PendingChange, Sort, SortOrder,
+        TransactionMode, Value, ValueMapExt, DEFAULT_FETCH_BATCH_SIZE,
     };
     use serde::{Deserialize, Serialize};
 
@@ -108,6 +109,4316 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_compressed_float_non_finite_round_trip() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(FieldTypeDebug)]).await?;
+
+        let record = FieldTypeDebug {
+            id: None,
+            int_data: vec![1, 2, 3],
+            float_data: vec![1.0, f64::NAN, f64::INFINITY, -0.0, f64::NEG_INFINITY],
+            name: "non-finite".to_string(),
+        };
+
+        // The compressed column is a blob, never a lossy JSON fallback
+        let map = record.to_map()?;
+        assert!(matches!(map.get("float_data"), Some(Value::Blob(_))));
+
+        record.insert(&db).await?;
+        let stored = FieldTypeDebug::find_all(&db).await?.remove(0);
+
+        assert_eq!(stored.float_data.len(), 5);
+        assert_eq!(stored.float_data[0], 1.0);
+        assert!(stored.float_data[1].is_nan());
+        assert_eq!(stored.float_data[2], f64::INFINITY);
+        assert_eq!(stored.float_data[3], 0.0);
+        assert!(stored.float_data[3].is_sign_negative());
+        assert_eq!(stored.float_data[4], f64::NEG_INFINITY);
+        assert_eq!(stored.int_data, vec![1, 2, 3]);
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_counters")]
+    struct TestCounter {
+        #[orso_column(primary_key)]
+        id: Option<i64>,
+
+        label: String,
+    }
+
+    #[test]
+    fn test_inspect_blob_reports_decoded_contents() -> Result<(), Box<dyn std::error::Error>> {
+        let values: Vec<i64> = (0..100).map(|i| i * 7 - 50).collect();
+        let blob = IntegerCodec::default().compress_i64(&values)?;
+
+        let info = Utils::inspect_blob(&blob, 3)?;
+        assert_eq!(info.element_type, orso::BlobElementType::I64);
+        assert_eq!(info.len, 100);
+        assert_eq!(info.bytes, blob.len());
+        assert_eq!(
+            info.head,
+            vec![
+                Value::Integer(-50),
+                Value::Integer(-43),
+                Value::Integer(-36)
+            ]
+        );
+        assert_eq!(
+            info.tail,
+            vec![
+                Value::Integer(629),
+                Value::Integer(636),
+                Value::Integer(643)
+            ]
+        );
+
+        // Short blobs don't repeat values between head and tail
+        let blob = IntegerCodec::default().compress_i64(&[1, 2, 3, 4])?;
+        let info = Utils::inspect_blob(&blob, 3)?;
+        assert_eq!(info.head.len() + info.tail.len(), 4);
+
+        assert!(Utils::inspect_blob(b"not a codec blob", 3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_integer_encoding_is_stable() -> Result<(), Box<dyn std::error::Error>> {
+        // find_by_compressed matches blobs byte for byte, so a codec change that encodes the
+        // same values differently would stop it finding rows written before the change
+        let blob = IntegerCodec::default().compress_i64(&[
+            1_700_000_000_000,
+            1_700_000_000_250,
+            1_700_000_000_500,
+            -3,
+            0,
+            42,
+        ])?;
+        assert_eq!(
+            blob,
+            vec![
+                79, 82, 83, 79, 1, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 128, 160, 171, 254, 249, 98, 244,
+                3, 244, 3, 237, 167, 171, 254, 249, 98, 6, 84
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_errors_keep_their_source() -> Result<(), Box<dyn std::error::Error>> {
+        use std::error::Error as _;
+
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        let err: orso::Error = db
+            .query("SELECT * FROM missing_table", vec![])
+            .await
+            .map(|_| ())
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, orso::Error::Sql(_)));
+        assert!(err.to_string().contains("missing_table"));
+        let source = err.source().expect("libsql error kept as source");
+        assert!(source.downcast_ref::<libsql::Error>().is_some());
+
+        // Model reads surface the same way through `?`
+        let err = TestUser::find_all(&db).await.unwrap_err();
+        assert!(err
+            .source()
+            .and_then(|e| e.downcast_ref::<libsql::Error>())
+            .is_some());
+
+        let err: orso::Error = serde_json::from_str::<TestUser>("{").unwrap_err().into();
+        assert!(err
+            .source()
+            .and_then(|e| e.downcast_ref::<serde_json::Error>())
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejected_token_is_connection_error() {
+        let hrana = |message: &str| orso::Error::from(libsql::Error::Hrana(message.into()));
+
+        assert!(hrana("api error: `status=401, body=Unauthorized`").is_connection_error());
+        assert!(hrana("api error: `status=403, body=Forbidden`").is_connection_error());
+        assert!(hrana("stream closed: `eof`").is_connection_error());
+        assert!(!hrana("api error: `status=400, body=bad request`").is_connection_error());
+    }
+
+    #[test]
+    fn test_remote_unique_failure_is_unique_violation() {
+        // The Display of a Hrana stream error from a remote server
+        let message = "stream error: `Error { message: \"SQLite error: UNIQUE constraint failed: \
+                       users.tenant, users.email\", code: \"SQLITE_CONSTRAINT_UNIQUE\" }`";
+        let err = orso::Error::from(libsql::Error::Hrana(message.into()));
+        assert!(
+            matches!(&err, orso::Error::UniqueViolation { column, .. } if column == "tenant, email"),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_integer_primary_key() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCounter)]).await?;
+
+        let mut counter = TestCounter {
+            id: None,
+            label: "answer".to_string(),
+        };
+        counter.set_key(42);
+        assert_eq!(counter.get_key(), Some(42));
+        assert_eq!(counter.get_primary_key(), Some("42".to_string()));
+        assert!(matches!(
+            counter.set_primary_key("forty-two".to_string()),
+            Err(orso::Error::Validation(_))
+        ));
+        counter.set_primary_key("42".to_string())?;
+        assert_eq!(counter.get_key(), Some(42));
+        counter.insert(&db).await?;
+
+        let found = TestCounter::find_by_id(42, &db).await?.unwrap();
+        assert_eq!(found.id, Some(42));
+        assert_eq!(found.label, "answer");
+        assert!(TestCounter::find_by_id(7, &db).await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_upsert_overlapping() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+
+        let user = |i: i32, age: i32| TestUser {
+            id: None,
+            name: format!("User {i}"),
+            email: format!("user{i}@example.com"),
+            age,
+            created_at: None,
+            updated_at: None,
+        };
+
+        // First half already exists; enough rows overall to span several chunks
+        let existing: Vec<TestUser> = (0..250).map(|i| user(i, 20)).collect();
+        TestUser::batch_create(&existing, &db).await?;
+
+        let incoming: Vec<TestUser> = (0..500).map(|i| user(i, 40)).collect();
+        let affected = TestUser::batch_upsert(&incoming, &db).await?;
+        assert_eq!(affected, 500);
+
+        let all = TestUser::find_all(&db).await?;
+        assert_eq!(all.len(), 500);
+        assert!(all.iter().all(|u| u.age == 40));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_busy_retry_concurrent_writers() -> Result<(), Box<dyn std::error::Error>> {
+        let db_path = "busy_retry_test.db";
+        let _ = std::fs::remove_file(db_path);
+
+        let config = DatabaseConfig::local(db_path).with_busy_retries(50);
+        let db = Database::init(config.clone()).await?;
+        Migrations::init(&db, &[migration!(TestCompressed), migration!(TestUser)]).await?;
+
+        // Each writer gets its own connection so they contend for the file lock
+        let mut handles = Vec::new();
+        for writer in 0..8 {
+            let config = config.clone();
+            handles.push(tokio::spawn(async move {
+                let db = Database::init(config).await?;
+                for i in 0..25 {
+                    let record = TestCompressed {
+                        id: None,
+                        data_points: vec![writer, i],
+                        name: format!("writer {writer} row {i}"),
+                        age: i as i32,
+                    };
+                    record.insert(&db).await?;
+                }
+                let users: Vec<TestUser> = (0..5)
+                    .map(|i| TestUser {
+                        name: format!("writer {writer}"),
+                        email: format!("writer{writer}-{i}@example.com"),
+                        age: i,
+                        ..Default::default()
+                    })
+                    .collect();
+                TestUser::batch_upsert(&users, &db).await?;
+                let ndjson: String = (0..5)
+                    .map(|i| {
+                        let record = TestCompressed {
+                            id: None,
+                            data_points: vec![writer, i],
+                            name: format!("writer {writer} import {i}"),
+                            age: i as i32,
+                        };
+                        serde_json::to_string(&record).map(|line| line + "\n")
+                    })
+                    .collect::<Result<_, _>>()?;
+                TestCompressed::import_ndjson(ndjson.as_bytes(), 2, &db).await?;
+                Ok::<(), anyhow::Error>(())
+            }));
+        }
+
+        for handle in handles {
+            if let Err(e) = handle.await? {
+                assert!(!e.to_string().contains("locked"), "write failed: {e}");
+                return Err(e.into());
+            }
+        }
+        assert_eq!(TestCompressed::count(&db).await?, 240);
+        assert_eq!(TestUser::count(&db).await?, 40);
+
+        std::fs::remove_file(db_path)?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_find_by_id_for_update_serializes_writers(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db_path = "for_update_test.db";
+        let _ = std::fs::remove_file(db_path);
+
+        let config = DatabaseConfig::local(db_path).with_busy_retries(200);
+        let db = Database::init(config.clone()).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        TestUser {
+            name: "counter".to_string(),
+            email: "counter@example.com".to_string(),
+            age: 0,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        let id = TestUser::find_all(&db).await?.remove(0).id.unwrap();
+
+        let deferred = db.transaction().await?;
+        assert!(deferred
+            .find_by_id_for_update::<TestUser>(id.clone())
+            .await
+            .is_err());
+        deferred.rollback().await?;
+
+        // Each task increments through its own connection; without the write lock taken at
+        // BEGIN both could read the same age and one increment would be lost
+        let mut handles = Vec::new();
+        for _ in 0..2 {
+            let config = config.clone();
+            let id = id.clone();
+            handles.push(tokio::spawn(async move {
+                let db = Database::init(config).await?;
+                for _ in 0..20 {
+                    let tx = db.transaction_with_mode(TransactionMode::Immediate).await?;
+                    let mut user = tx
+                        .find_by_id_for_update::<TestUser>(id.clone())
+                        .await?
+                        .unwrap();
+                    tokio::task::yield_now().await;
+                    user.age += 1;
+                    user.update(tx.db()).await?;
+                    tx.commit().await?;
+                }
+                Ok::<(), anyhow::Error>(())
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+
+        let user = TestUser::find_by_id(id, &db).await?.unwrap();
+        assert_eq!(user.age, 40);
+
+        std::fs::remove_file(db_path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_transaction_sees_one_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+        let db_path = "read_transaction_test.db";
+        let _ = std::fs::remove_file(db_path);
+
+        let config = DatabaseConfig::local(db_path)
+            .with_pragmas(vec![("journal_mode".into(), "WAL".into())]);
+        let reader = Database::init(config.clone()).await?;
+        let writer = Database::init(config).await?;
+        Migrations::init(&reader, &[migration!(TestUser)]).await?;
+        let user = |name: &str| TestUser {
+            name: name.to_string(),
+            email: format!("{name}@example.com"),
+            age: 30,
+            ..Default::default()
+        };
+        user("first").insert(&writer).await?;
+
+        // The snapshot is taken when the transaction opens, not at its first read
+        let tx = reader.read_transaction().await?;
+        user("second").insert(&writer).await?;
+        let before = TestUser::find_all(tx.db()).await?;
+        user("third").insert(&writer).await?;
+        let after = TestUser::find_all(tx.db()).await?;
+        assert_eq!(before.len(), 1);
+        assert_eq!(after.len(), 1);
+        assert_eq!(before[0].name, after[0].name);
+        tx.commit().await?;
+
+        assert_eq!(TestUser::count(&reader).await?, 3);
+
+        drop(reader);
+        drop(writer);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{db_path}{suffix}"));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_raw_copies_compressed_blob_verbatim(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(
+            &db,
+            &[
+                migration!(TestCompressed),
+                migration!(TestCompressed, "test_compressed_copy"),
+            ],
+        )
+        .await?;
+        let original = TestCompressed {
+            id: None,
+            data_points: (0..1000).map(|i| i * 7 - 300).collect(),
+            name: "shard row".to_string(),
+            age: 9,
+        };
+        original.insert(&db).await?;
+
+        let mut rows = db.query("SELECT * FROM test_compressed", vec![]).await?;
+        let row = rows.next().await?.unwrap();
+        let map = TestCompressed::row_to_map(&row)?;
+        let source_blob = map["data_points"].clone();
+        TestCompressed::insert_raw_with_table(map, &db, "test_compressed_copy").await?;
+
+        let mut rows = db
+            .query("SELECT data_points FROM test_compressed_copy", vec![])
+            .await?;
+        let copied_blob: Vec<u8> = rows.next().await?.unwrap().get(0)?;
+        assert_eq!(Value::Blob(copied_blob), source_blob);
+
+        let copy = TestCompressed::find_all_with_table(&db, "test_compressed_copy")
+            .await?
+            .remove(0);
+        assert_eq!(copy.data_points, original.data_points);
+        assert_eq!(copy.name, "shard row");
+
+        // Decoded values aren't accepted for a compressed column
+        let mut decoded = original.to_map()?;
+        decoded.insert(
+            "data_points".to_string(),
+            Value::Text("[1,2,3]".to_string()),
+        );
+        assert!(TestCompressed::insert_raw(decoded, &db).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compress_field_offline_round_trips_through_insert_raw(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let arrays: Vec<Vec<i64>> = (0..4)
+            .map(|n| (0..500).map(|i| i * (n + 1) - 250).collect())
+            .collect();
+        let blobs = TestCompressed::compress_field("data_points", &arrays)?;
+        assert_eq!(blobs.len(), arrays.len());
+
+        for (n, blob) in blobs.iter().enumerate() {
+            let mut map = std::collections::HashMap::new();
+            map.insert("data_points".to_string(), Value::Blob(blob.clone()));
+            map.insert("name".to_string(), Value::Text(format!("offline{n}")));
+            map.insert("age".to_string(), Value::Integer(n as i64));
+            TestCompressed::insert_raw(map, &db).await?;
+        }
+
+        let mut stored = TestCompressed::find_all(&db).await?;
+        stored.sort_by_key(|r| r.age);
+        let stored: Vec<Vec<i64>> = stored.into_iter().map(|r| r.data_points).collect();
+        assert_eq!(stored, arrays);
+
+        // Blobs written through the model decode offline too
+        let mut rows = db
+            .query(
+                "SELECT data_points FROM test_compressed ORDER BY age",
+                vec![],
+            )
+            .await?;
+        let mut raw = Vec::new();
+        while let Some(row) = rows.next().await? {
+            raw.push(row.get::<Vec<u8>>(0)?);
+        }
+        assert_eq!(raw, blobs);
+        assert_eq!(
+            TestCompressed::decompress_field::<i64>("data_points", &raw)?,
+            arrays
+        );
+
+        assert!(matches!(
+            TestCompressed::compress_field("name", &arrays),
+            Err(orso::Error::Validation(_))
+        ));
+        assert!(matches!(
+            TestCompressed::compress_field("data_points", &[vec![1.5f64]]),
+            Err(orso::Error::Validation(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_from_renames_columns_and_recompresses(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // One row per batch, so every copy spans several source reads
+        let db = Database::init(DatabaseConfig::memory().with_fetch_batch_size(1)).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestCompressed)]).await?;
+
+        db.execute(
+            "CREATE TABLE legacy_people (id TEXT PRIMARY KEY, full_name TEXT, mail TEXT, \
+             age INTEGER, fax TEXT)",
+        )
+        .await?;
+        db.execute(
+            "INSERT INTO legacy_people VALUES \
+             ('p1', 'Ada', 'ada@example.com', 36, '555-0100'), \
+             ('p2', 'Alan', 'alan@example.com', 41, NULL)",
+        )
+        .await?;
+
+        let copied = TestUser::migrate_from(
+            "legacy_people",
+            std::collections::HashMap::from([("full_name", "name"), ("mail", "email")]),
+            &db,
+        )
+        .await?;
+        assert_eq!(copied, 2);
+
+        let ada = TestUser::find_by_id("p1", &db).await?.unwrap();
+        assert_eq!(ada.name, "Ada");
+        assert_eq!(ada.email, "ada@example.com");
+        assert_eq!(ada.age, 36);
+        assert!(ada.created_at.is_some());
+        let alan = TestUser::find_by_id("p2", &db).await?.unwrap();
+        assert_eq!((alan.name.as_str(), alan.age), ("Alan", 41));
+
+        // A JSON series in the source is compressed for the target column
+        db.execute("CREATE TABLE legacy_series (id TEXT, label TEXT, points TEXT, age INTEGER)")
+            .await?;
+        db.execute("INSERT INTO legacy_series VALUES ('s1', 'series', '[5,10,15]', 2)")
+            .await?;
+        TestCompressed::migrate_from(
+            "legacy_series",
+            std::collections::HashMap::from([("label", "name"), ("points", "data_points")]),
+            &db,
+        )
+        .await?;
+        let mut rows = db
+            .query(
+                "SELECT data_points FROM test_compressed WHERE id = 's1'",
+                vec![],
+            )
+            .await?;
+        let stored: Vec<u8> = rows.next().await?.unwrap().get(0)?;
+        assert!(stored.starts_with(b"ORSO"));
+        let series = TestCompressed::find_by_id("s1", &db).await?.unwrap();
+        assert_eq!(series.data_points, vec![5, 10, 15]);
+
+        assert!(TestUser::migrate_from(
+            "legacy_people",
+            std::collections::HashMap::from([("mail", "mailbox")]),
+            &db,
+        )
+        .await
+        .is_err());
+
+        // A transform sees the renamed row, including columns the model lacks
+        db.execute("DELETE FROM test_users").await?;
+        let copied = TestUser::migrate_from_with_transform(
+            "legacy_people",
+            std::collections::HashMap::from([("full_name", "name"), ("mail", "email")]),
+            |row: &mut std::collections::HashMap<String, orso::Value>| {
+                if let Some(orso::Value::Text(name)) = row.get("name") {
+                    let upper = name.to_uppercase();
+                    row.insert("name".to_string(), orso::Value::Text(upper));
+                }
+                if let Some(orso::Value::Text(fax)) = row.remove("fax") {
+                    row.insert("email".to_string(), orso::Value::Text(format!("fax:{fax}")));
+                }
+                Ok(())
+            },
+            &db,
+        )
+        .await?;
+        assert_eq!(copied, 2);
+        let ada = TestUser::find_by_id("p1", &db).await?.unwrap();
+        assert_eq!(
+            (ada.name.as_str(), ada.email.as_str()),
+            ("ADA", "fax:555-0100")
+        );
+        let alan = TestUser::find_by_id("p2", &db).await?.unwrap();
+        assert_eq!(
+            (alan.name.as_str(), alan.email.as_str()),
+            ("ALAN", "alan@example.com")
+        );
+
+        let failing = TestUser::migrate_from_with_transform(
+            "legacy_people",
+            std::collections::HashMap::from([("full_name", "name"), ("mail", "email")]),
+            |_: &mut std::collections::HashMap<String, orso::Value>| {
+                Err(orso::Error::Validation("rejected".to_string()))
+            },
+            &db,
+        )
+        .await;
+        assert!(matches!(failing, Err(orso::Error::Validation(_))));
+
+        // A row failing after others were copied leaves the target as it was
+        db.execute("DELETE FROM test_users").await?;
+        let failing = TestUser::migrate_from_with_transform(
+            "legacy_people",
+            std::collections::HashMap::from([("full_name", "name"), ("mail", "email")]),
+            |row: &mut std::collections::HashMap<String, orso::Value>| match row.get("name") {
+                Some(orso::Value::Text(name)) if name == "Alan" => {
+                    Err(orso::Error::Validation("rejected".to_string()))
+                }
+                _ => Ok(()),
+            },
+            &db,
+        )
+        .await;
+        assert!(matches!(failing, Err(orso::Error::Validation(_))));
+        assert_eq!(TestUser::count(&db).await?, 0);
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_bars")]
+    struct TestBars {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        symbol: String,
+
+        #[orso_column(compress)]
+        open_times: Vec<chrono::DateTime<chrono::Utc>>,
+    }
+
+    #[tokio::test]
+    async fn test_compressed_timestamps_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestBars)]).await?;
+
+        let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")?.to_utc();
+        let open_times: Vec<_> = (0..10_000)
+            .map(|i| start + chrono::Duration::minutes(i))
+            .collect();
+        TestBars {
+            id: Some("btc-1m".to_string()),
+            symbol: "BTCUSD".to_string(),
+            open_times: open_times.clone(),
+        }
+        .insert(&db)
+        .await?;
+        TestBars {
+            id: Some("empty".to_string()),
+            symbol: "ETHUSD".to_string(),
+            open_times: Vec::new(),
+        }
+        .insert(&db)
+        .await?;
+
+        let mut rows = db
+            .query(
+                "SELECT open_times FROM test_bars WHERE id = 'btc-1m'",
+                vec![],
+            )
+            .await?;
+        let blob: Vec<u8> = rows.next().await?.unwrap().get(0)?;
+        assert!(blob.starts_with(b"ORSO"));
+        assert!(blob.len() < open_times.len() * 8);
+
+        let bars = TestBars::find_by_id("btc-1m", &db).await?.unwrap();
+        assert_eq!(bars.open_times, open_times);
+        let empty = TestBars::find_by_id("empty", &db).await?.unwrap();
+        assert!(empty.open_times.is_empty());
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_order_lines")]
+    struct TestOrderLine {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        price: f64,
+        qty: i64,
+
+        #[orso_column(generated = "price * qty", stored)]
+        total: f64,
+    }
+
+    #[tokio::test]
+    async fn test_generated_column() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestOrderLine)]).await?;
+
+        let line = TestOrderLine {
+            id: None,
+            price: 2.5,
+            qty: 4,
+            total: 0.0,
+        };
+        assert!(!line.to_map()?.contains_key("total"));
+        line.insert(&db).await?;
+
+        let mut stored = TestOrderLine::find_all(&db).await?.remove(0);
+        assert_eq!(stored.total, 10.0);
+
+        // Writes ignore the field; SQLite recomputes it
+        stored.qty = 10;
+        stored.total = -1.0;
+        stored.update(&db).await?;
+        let reloaded = TestOrderLine::find_all(&db).await?.remove(0);
+        assert_eq!(reloaded.total, 25.0);
+
+        // The migration diff sees the generated column as up to date
+        let results = Migrations::init(&db, &[migration!(TestOrderLine)]).await?;
+        assert!(matches!(
+            results[0].action,
+            orso::migrations::MigrationAction::SchemaMatched
+        ));
+
+        // A plain `total` column is detected and migrated to the generated one
+        db.execute(
+            "CREATE TABLE test_order_lines_legacy (id TEXT PRIMARY KEY, price REAL NOT NULL, \
+             qty INTEGER NOT NULL, total REAL NOT NULL)",
+        )
+        .await?;
+        db.execute("INSERT INTO test_order_lines_legacy VALUES ('a', 3.0, 2, 0.0)")
+            .await?;
+        let results =
+            Migrations::init(&db, &[migration!(TestOrderLine, "test_order_lines_legacy")]).await?;
+        assert!(matches!(
+            results[0].action,
+            orso::migrations::MigrationAction::DataMigrated { .. }
+        ));
+        let migrated = TestOrderLine::find_all_with_table(&db, "test_order_lines_legacy").await?;
+        assert_eq!(migrated[0].total, 6.0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_where_returning() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        for (name, age) in [("Ann", 20), ("Bob", 35), ("Cid", 41), ("Dee", 30)] {
+            TestCompressed {
+                id: None,
+                data_points: (0..50).map(|i| i * age as i64).collect(),
+                name: name.to_string(),
+                age,
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let filter =
+            FilterOperator::Single(Filter::new_simple("age", Operator::Gt, Value::Integer(30)));
+        let mut deleted = TestCompressed::delete_where_returning(filter, &db).await?;
+        deleted.sort_by_key(|r| r.age);
+
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(deleted[0].name, "Bob");
+        assert_eq!(deleted[1].name, "Cid");
+        for record in &deleted {
+            let expected: Vec<i64> = (0..50).map(|i| i * record.age as i64).collect();
+            assert_eq!(record.data_points, expected);
+        }
+
+        let remaining = TestCompressed::find_all(&db).await?;
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.iter().all(|r| r.age <= 30));
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_notes")]
+    struct TestNote {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        position: i32,
+        body: Option<String>,
+
+        #[orso_column(empty_as_null)]
+        summary: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn test_optional_text_null_vs_empty() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestNote)]).await?;
+
+        let values = [None, Some(String::new()), Some("x".to_string())];
+        for (position, value) in values.iter().enumerate() {
+            TestNote {
+                id: None,
+                position: position as i32,
+                body: value.clone(),
+                summary: value.clone(),
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let mut notes = TestNote::find_all(&db).await?;
+        notes.sort_by_key(|n| n.position);
+
+        // Plain Option<String> keeps None, Some("") and Some("x") distinct
+        let bodies: Vec<Option<String>> = notes.iter().map(|n| n.body.clone()).collect();
+        assert_eq!(bodies, values);
+
+        // empty_as_null stores Some("") as NULL
+        let summaries: Vec<Option<String>> = notes.iter().map(|n| n.summary.clone()).collect();
+        assert_eq!(summaries, vec![None, None, Some("x".to_string())]);
+
+        let mut rows = db
+            .query("SELECT COUNT(*) FROM test_notes WHERE body IS NULL", vec![])
+            .await?;
+        let row = rows.next().await?.unwrap();
+        assert_eq!(row.get::<i64>(0)?, 1);
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_posts")]
+    struct TestPost {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        #[orso_column(ref = "test_users")]
+        user_id: String,
+
+        title: String,
+    }
+
+    #[tokio::test]
+    async fn test_filter_in_subquery() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestPost)]).await?;
+
+        for (name, age) in [("young", 25), ("old", 45)] {
+            TestUser {
+                id: None,
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age,
+                created_at: None,
+                updated_at: None,
+            }
+            .insert(&db)
+            .await?;
+        }
+        for user in TestUser::find_all(&db).await? {
+            for i in 0..2 {
+                TestPost {
+                    id: None,
+                    user_id: user.id.clone().unwrap(),
+                    title: format!("{} post {i}", user.name),
+                }
+                .insert(&db)
+                .await?;
+            }
+        }
+
+        let over_30 =
+            FilterOperator::Single(Filter::new_simple("age", Operator::Gt, Value::Integer(30)));
+        let filter = FilterOperator::Single(Filter::new_in_subquery(
+            "user_id",
+            TestUser::subquery_ids(over_30),
+        ));
+        let posts = TestPost::find_where(filter, &db).await?;
+
+        assert_eq!(posts.len(), 2);
+        assert!(posts.iter().all(|p| p.title.starts_with("old")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_ref_loads_parent() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestPost)]).await?;
+
+        TestUser {
+            name: "author".to_string(),
+            email: "author@example.com".to_string(),
+            age: 40,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        let author = TestUser::find_one(FilterOperator::Single(Filter::eq("name", "author")), &db)
+            .await?
+            .unwrap();
+
+        let post = TestPost {
+            id: None,
+            user_id: author.id.clone().unwrap(),
+            title: "hello".to_string(),
+        };
+        assert_eq!(TestPost::foreign_keys(), vec![("user_id", "test_users")]);
+
+        let parent = post.fetch_user::<TestUser>(&db).await?.unwrap();
+        assert_eq!(parent.id, author.id);
+        assert_eq!(parent.email, "author@example.com");
+
+        let parent: Option<TestUser> = post.fetch_ref("user_id", &db).await?;
+        assert_eq!(parent.unwrap().name, "author");
+
+        assert!(post.fetch_ref::<TestUser>("title", &db).await.is_err());
+        assert!(post.fetch_user::<TestPost>(&db).await.is_err());
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_sequenced")]
+    struct TestSequenced {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        sequence: i64,
+
+        label: String,
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_tagged")]
+    struct TestTagged {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        tags: Vec<String>,
+
+        labels: std::collections::HashMap<String, String>,
+
+        aliases: Option<Vec<String>>,
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_ledger")]
+    struct TestLedger {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        // Stored as decimal text by the derive itself
+        quantity: i128,
+
+        ceiling: Option<u128>,
+
+        // The serde helper also makes the struct's own JSON use the text form
+        #[serde(with = "orso::as_text::option")]
+        cap: Option<u128>,
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_ranked", order_by = "rank desc, title")]
+    struct TestRanked {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        title: String,
+        rank: i64,
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_slim_notes")]
+    #[serde(deny_unknown_fields)]
+    struct TestSlimNote {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        body: String,
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_documents")]
+    struct TestDocument {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        title: String,
+
+        #[orso_column(compress_text)]
+        body: String,
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_scored")]
+    struct TestScored {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        name: String,
+
+        #[orso_column(not_null, default = "0")]
+        score: Option<i32>,
+    }
+
+    #[tokio::test]
+    async fn test_find_after_pages_by_integer_sequence() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestSequenced)]).await?;
+        // Inserted out of order, with pairs sharing a sequence so the key alone can't page
+        for i in (0..23).rev() {
+            TestSequenced {
+                id: None,
+                sequence: i / 2,
+                label: format!("row{i}"),
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let mut pagination = orso::CursorPagination::new(5);
+        let mut seen = Vec::new();
+        let mut pages = 0;
+        loop {
+            let page = TestSequenced::find_after("sequence", &pagination, &db).await?;
+            pages += 1;
+            seen.extend(page.data.iter().map(|r| (r.sequence, r.label.clone())));
+            match page.pagination.next_cursor.clone() {
+                Some(cursor) => {
+                    assert!(page.pagination.has_next);
+                    pagination = orso::CursorPagination::with_cursor(5, Some(cursor));
+                }
+                None => break,
+            }
+        }
+        assert_eq!(pages, 5);
+        assert_eq!(seen.len(), 23);
+        let sequences: Vec<i64> = seen.iter().map(|(sequence, _)| *sequence).collect();
+        assert!(sequences.windows(2).all(|pair| pair[0] <= pair[1]));
+        let mut labels: Vec<String> = seen.into_iter().map(|(_, label)| label).collect();
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels.len(), 23);
+
+        let filtered = TestSequenced::find_after_where(
+            FilterOperator::Single(Filter::ge("sequence", 10)),
+            "sequence",
+            &orso::CursorPagination::new(5),
+            &db,
+        )
+        .await?;
+        assert_eq!(filtered.data.len(), 3);
+        assert!(!filtered.pagination.has_next);
+
+        assert!(TestSequenced::find_after("missing", &pagination, &db)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_where_with_count_reads_page_and_total(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        for age in 0..25 {
+            TestUser {
+                id: None,
+                name: format!("user{age}"),
+                email: format!("user{age}@example.com"),
+                age,
+                created_at: None,
+                updated_at: None,
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let adults = || FilterOperator::Single(Filter::ge("age", 18));
+        let (page, total) = TestUser::find_where_with_count(
+            adults(),
+            Some(vec![Sort::new("age", SortOrder::Asc)]),
+            Some(&Pagination::new(2, 3)),
+            &db,
+        )
+        .await?;
+        assert_eq!(total, TestUser::count_where(adults(), &db).await?);
+        assert_eq!(total, 7);
+        let ages: Vec<i32> = page.iter().map(|u| u.age).collect();
+        assert_eq!(ages, vec![21, 22, 23]);
+
+        // A page past the end still reports the total
+        let (page, total) =
+            TestUser::find_where_with_count(adults(), None, Some(&Pagination::new(9, 3)), &db)
+                .await?;
+        assert!(page.is_empty());
+        assert_eq!(total, 7);
+
+        let (page, total) = TestUser::find_where_with_count(
+            FilterOperator::Single(Filter::gt("age", 100)),
+            None,
+            None,
+            &db,
+        )
+        .await?;
+        assert!(page.is_empty());
+        assert_eq!(total, 0);
+
+        // Errors in the query itself are returned, not retried without the window count
+        let missing = FilterOperator::Single(Filter::eq("no_such_column", 1));
+        assert!(matches!(
+            TestUser::find_where_with_count(missing, None, None, &db).await,
+            Err(orso::Error::Sql(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_where_grouped_by_user_id() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestPost)]).await?;
+
+        let mut ids = Vec::new();
+        for name in ["alice", "bob"] {
+            TestUser {
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age: 30,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+            let user = TestUser::find_one(FilterOperator::Single(Filter::eq("name", name)), &db)
+                .await?
+                .unwrap();
+            ids.push(user.id.unwrap());
+        }
+        for (user, title) in [(0, "a1"), (0, "a2"), (1, "b1"), (1, "draft")] {
+            TestPost {
+                id: None,
+                user_id: ids[user].clone(),
+                title: title.to_string(),
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let filter = FilterOperator::Single(Filter::ne("title", "draft"));
+        let groups = TestPost::find_where_grouped(filter.clone(), "user_id", &db).await?;
+        assert_eq!(groups.len(), 2);
+        let mut alice: Vec<_> = groups[&Value::Text(ids[0].clone())]
+            .iter()
+            .map(|p| p.title.as_str())
+            .collect();
+        alice.sort();
+        assert_eq!(alice, vec!["a1", "a2"]);
+        let bob = &groups[&Value::Text(ids[1].clone())];
+        assert_eq!(bob.len(), 1);
+        assert_eq!(bob[0].title, "b1");
+
+        assert!(TestPost::find_where_grouped(filter, "author_id", &db)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_children_loads_posts_of_user() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestPost)]).await?;
+
+        let mut ids = Vec::new();
+        for name in ["writer", "lurker"] {
+            TestUser {
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age: 30,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+            let user = TestUser::find_one(FilterOperator::Single(Filter::eq("name", name)), &db)
+                .await?
+                .unwrap();
+            ids.push(user.id.unwrap());
+        }
+        for title in ["first", "second"] {
+            TestPost {
+                id: None,
+                user_id: ids[0].clone(),
+                title: title.to_string(),
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let mut posts = TestUser::find_children::<TestPost>(&ids[0], "user_id", &db).await?;
+        posts.sort_by(|a, b| a.title.cmp(&b.title));
+        let titles: Vec<_> = posts.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["first", "second"]);
+        assert!(posts.iter().all(|p| p.user_id == ids[0]));
+
+        assert!(TestUser::find_children::<TestPost>(&ids[1], "user_id", &db)
+            .await?
+            .is_empty());
+        assert!(
+            TestUser::find_children::<TestPost>(&ids[0], "author_id", &db)
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tracked_updates_only_changed_fields() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        TestCompressed {
+            id: None,
+            data_points: (0..500).collect(),
+            name: "before".to_string(),
+            age: 30,
+        }
+        .insert(&db)
+        .await?;
+        let id = TestCompressed::find_all(&db).await?[0].id.clone().unwrap();
+
+        let mut tracked = TestCompressed::find_by_id_tracked(&id, &db).await?.unwrap();
+        assert!(tracked.changed_fields()?.is_empty());
+        assert_eq!(tracked.update(&db).await?, 0);
+
+        tracked.name = "after".to_string();
+        assert_eq!(tracked.changed_fields()?, vec!["name"]);
+
+        // Only the changed column is encoded; the compressed array is left alone
+        let changes = tracked.to_map_only(Some(&tracked.changed_fields()?))?;
+        assert_eq!(changes.keys().collect::<Vec<_>>(), vec!["name"]);
+
+        assert_eq!(tracked.update(&db).await?, 1);
+        assert!(tracked.changed_fields()?.is_empty());
+
+        let stored = TestCompressed::find_by_id(&id, &db).await?.unwrap();
+        assert_eq!(stored.name, "after");
+        assert_eq!(stored.age, 30);
+        assert_eq!(stored.data_points, (0..500).collect::<Vec<i64>>());
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_audit_log")]
+    struct TestAuditLog {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        action: String,
+        happened_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    }
+
+    #[tokio::test]
+    async fn test_fixed_offset_datetime_preserved() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestAuditLog)]).await?;
+
+        let happened_at = chrono::DateTime::parse_from_rfc3339("2025-03-14T09:26:53+05:30")?;
+        TestAuditLog {
+            id: None,
+            action: "login".to_string(),
+            happened_at: Some(happened_at),
+        }
+        .insert(&db)
+        .await?;
+
+        let stored = TestAuditLog::find_all(&db).await?.remove(0);
+        let stored_at = stored.happened_at.unwrap();
+        assert_eq!(stored_at, happened_at);
+        assert_eq!(stored_at.offset().local_minus_utc(), 5 * 3600 + 30 * 60);
+        assert_eq!(stored_at.to_rfc3339(), "2025-03-14T09:26:53+05:30");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_by_compressed_matches_exact_series() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let series: Vec<i64> = (0..1_000).map(|i| 117_000 + i * 3).collect();
+        for (name, data_points) in [
+            ("target", series.clone()),
+            ("shifted", series.iter().map(|v| v + 1).collect()),
+            ("prefix", series[..999].to_vec()),
+        ] {
+            TestCompressed {
+                id: None,
+                data_points,
+                name: name.to_string(),
+                age: 1,
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        // Encoding the same values twice gives the same bytes
+        let codec = IntegerCodec::default();
+        assert_eq!(codec.compress_i64(&series)?, codec.compress_i64(&series)?);
+
+        let found = TestCompressed::find_by_compressed("data_points", &series, &db).await?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "target");
+        assert_eq!(found[0].data_points, series);
+
+        assert!(
+            TestCompressed::find_by_compressed("data_points", &[1, 2, 3], &db)
+                .await?
+                .is_empty()
+        );
+        assert!(matches!(
+            TestCompressed::find_by_compressed("name", &series, &db).await,
+            Err(orso::Error::Validation(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compressed_column_with_legacy_json_rows() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(
+            &db,
+            &[migration!(TestCompressed), migration!(FieldTypeDebug)],
+        )
+        .await?;
+
+        // Rows written before the column was compressed: JSON as text and as a blob
+        db.execute(
+            "INSERT INTO test_compressed (id, data_points, name, age) VALUES \
+             ('legacy-text', '[1,2,3]', 'text', 1), \
+             ('legacy-blob', CAST('[4,5,6]' AS BLOB), 'blob', 2)",
+        )
+        .await?;
+        TestCompressed {
+            id: Some("compressed".to_string()),
+            data_points: vec![7, 8, 9],
+            name: "compressed".to_string(),
+            age: 3,
+        }
+        .insert(&db)
+        .await?;
+
+        let mut records = TestCompressed::find_all(&db).await?;
+        records.sort_by_key(|r| r.age);
+        let points: Vec<Vec<i64>> = records.iter().map(|r| r.data_points.clone()).collect();
+        assert_eq!(points, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+
+        // Same for directly encoded float arrays
+        db.execute(
+            "INSERT INTO field_type_debug (id, int_data, float_data, name) VALUES \
+             ('legacy', '[1]', CAST('[0.5,1.5]' AS BLOB), 'legacy')",
+        )
+        .await?;
+        let legacy = FieldTypeDebug::find_by_id("legacy", &db).await?.unwrap();
+        assert_eq!(legacy.int_data, vec![1]);
+        assert_eq!(legacy.float_data, vec![0.5, 1.5]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_table_override_per_call() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(
+            &db,
+            &[
+                migration!(TestUser, "users_2025_01"),
+                migration!(TestUser, "users_2025_02"),
+            ],
+        )
+        .await?;
+
+        for (table, name) in [("users_2025_01", "january"), ("users_2025_02", "february")] {
+            let user = TestUser {
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age: 30,
+                ..Default::default()
+            };
+            user.insert_with_table(&db, table).await?;
+        }
+
+        let january = TestUser::find_all_with_table(&db, "users_2025_01").await?;
+        let february = TestUser::find_all_with_table(&db, "users_2025_02").await?;
+        assert_eq!(january.len(), 1);
+        assert_eq!(january[0].name, "january");
+        assert!(january[0].id.is_some());
+        assert_eq!(february.len(), 1);
+        assert_eq!(february[0].name, "february");
+
+        let user = january[0].clone();
+        assert!(user.insert_with_table(&db, "").await.is_err());
+        assert!(TestUser::find_all_with_table(&db, "  ").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_month_shards_created_on_demand() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+
+        let january = chrono::NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let february = chrono::NaiveDate::from_ymd_opt(2025, 2, 3)
+            .and_then(|d| d.and_hms_opt(12, 0, 0))
+            .unwrap()
+            .and_utc();
+
+        // Concurrent and repeated creation of the same shard is harmless
+        let (first, second) = tokio::join!(
+            Migrations::ensure_month_shard::<TestUser>(&db, "users", &january),
+            Migrations::ensure_month_shard::<TestUser>(&db, "users", &january),
+        );
+        assert_eq!(first?, "users_2025_01");
+        assert_eq!(second?, "users_2025_01");
+
+        for (i, date) in [january.and_hms_opt(0, 0, 0).unwrap().and_utc(), february]
+            .iter()
+            .enumerate()
+        {
+            let user = TestUser {
+                name: format!("user{i}"),
+                email: format!("user{i}@example.com"),
+                age: 20,
+                ..Default::default()
+            };
+            user.insert_into_month_shard(&db, "users", date).await?;
+        }
+
+        let mut rows = db
+            .query(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'users_%' ORDER BY name",
+                vec![],
+            )
+            .await?;
+        let mut tables = Vec::new();
+        while let Some(row) = rows.next().await? {
+            tables.push(row.get::<String>(0)?);
+        }
+        assert_eq!(tables, vec!["users_2025_01", "users_2025_02"]);
+
+        // Both shards carry the model's schema
+        let results = Migrations::init(
+            &db,
+            &[
+                migration!(TestUser, "users_2025_01"),
+                migration!(TestUser, "users_2025_02"),
+            ],
+        )
+        .await?;
+        assert!(results
+            .iter()
+            .all(|r| matches!(r.action, orso::migrations::MigrationAction::SchemaMatched)));
+        assert_eq!(TestUser::count_with_table(&db, "users_2025_01").await?, 1);
+        assert_eq!(TestUser::count_with_table(&db, "users_2025_02").await?, 1);
+
+        assert!(
+            Migrations::ensure_month_shard::<TestUser>(&db, "", &january)
+                .await
+                .is_err()
+        );
+        let err = Migrations::ensure_month_shard::<TestUser>(
+            &db,
+            "users_2025_01 (id TEXT); DROP TABLE users_2025_02; --",
+            &january,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, orso::Error::Validation(_)), "{err:?}");
+        assert_eq!(TestUser::count_with_table(&db, "users_2025_02").await?, 1);
+
+        // Shards get the model's indexes
+        let (first, second) = tokio::join!(
+            Migrations::ensure_month_shard::<TestTask>(&db, "tasks", &january),
+            Migrations::ensure_month_shard::<TestTask>(&db, "tasks", &january),
+        );
+        assert_eq!(first?, "tasks_2025_01");
+        assert_eq!(second?, "tasks_2025_01");
+        let mut rows = db
+            .query(
+                "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'tasks_2025_01' \
+                 AND sql IS NOT NULL ORDER BY name",
+                vec![],
+            )
+            .await?;
+        let mut indexes = Vec::new();
+        while let Some(row) = rows.next().await? {
+            indexes.push(row.get::<String>(0)?);
+        }
+        assert_eq!(
+            indexes,
+            vec!["idx_tasks_2025_01_created_at", "uq_task_owner_title"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_table_names_must_be_identifiers() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+
+        for name in [
+            "test_users; DROP TABLE test_users",
+            "test users",
+            "1users",
+            "",
+        ] {
+            let err = TestUser::count_with_table(&db, name).await.unwrap_err();
+            assert!(
+                matches!(err, orso::Error::Validation(_)),
+                "{name:?}: {err:?}"
+            );
+        }
+        let err = TestUser::count_by_with_table("name", &db, "test_users--")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, orso::Error::Validation(_)));
+        assert!(orso::migrations::pending_changes_with_name::<TestUser>(
+            &db,
+            "test_users)",
+            &orso::migrations::MigrationConfig::default()
+        )
+        .await
+        .is_err());
+
+        // The table itself is untouched
+        assert_eq!(TestUser::count(&db).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deserialization_error_names_column() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+
+        db.execute(
+            "INSERT INTO test_users (id, name, email, age) VALUES ('bad-age', 'Bad', 'bad@example.com', 'thirty')",
+        )
+        .await?;
+
+        match TestUser::find_by_id("bad-age", &db).await {
+            Err(orso::Error::FieldDeserialization { field, value, .. }) => {
+                assert_eq!(field, "age");
+                assert_eq!(value, Value::Text("thirty".to_string()));
+            }
+            other => panic!("expected a field deserialization error, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_imports")]
+    struct TestImport {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        source: String,
+
+        #[orso_column(default_now)]
+        imported_at: Option<chrono::DateTime<chrono::Utc>>,
+
+        #[orso_column(touch_on_update)]
+        synced_at: Option<chrono::DateTime<chrono::Utc>>,
+
+        #[orso_column(updated_at)]
+        updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    }
+
+    #[tokio::test]
+    async fn test_default_now_and_touch_on_update_fields() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestImport)]).await?;
+
+        assert_eq!(
+            TestImport::default_now_fields(),
+            vec!["imported_at", "synced_at", "updated_at"]
+        );
+        assert_eq!(
+            TestImport::touch_on_update_fields(),
+            vec!["synced_at", "updated_at"]
+        );
+
+        TestImport {
+            id: Some("import-1".to_string()),
+            source: "csv".to_string(),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        let inserted = TestImport::find_by_id("import-1", &db).await?.unwrap();
+        assert!(inserted.imported_at.is_some());
+        assert!(inserted.synced_at.is_some());
+        assert!(inserted.updated_at.is_some());
+
+        // Backdate every timestamp so the update's effect is visible within the same second
+        let old = "2000-01-01T00:00:00.000Z";
+        db.execute(&format!(
+            "UPDATE test_imports SET imported_at = '{old}', synced_at = '{old}', updated_at = '{old}'"
+        ))
+        .await?;
+
+        let mut record = TestImport::find_by_id("import-1", &db).await?.unwrap();
+        record.source = "api".to_string();
+        record.update(&db).await?;
+
+        let updated = TestImport::find_by_id("import-1", &db).await?.unwrap();
+        let old: chrono::DateTime<chrono::Utc> = old.parse()?;
+        assert_eq!(updated.source, "api");
+        assert_eq!(updated.imported_at, Some(old));
+        assert!(updated.synced_at.unwrap() > old);
+        assert!(updated.updated_at.unwrap() > old);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ensure_table_is_idempotent() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+
+        TestUser::ensure_table(&db).await?;
+        TestUser::ensure_table(&db).await?;
+
+        let mut rows = db
+            .query(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'test_users'",
+                vec![],
+            )
+            .await?;
+        let count: i64 = rows.next().await?.unwrap().get(0)?;
+        assert_eq!(count, 1);
+
+        TestUser {
+            name: "Script".to_string(),
+            email: "script@example.com".to_string(),
+            age: 1,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        assert_eq!(TestUser::count(&db).await?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_column_blob_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        assert_eq!(
+            TestCompressed::column_blob_bytes("data_points", &db).await?,
+            0
+        );
+
+        let records: Vec<TestCompressed> = (0..5)
+            .map(|i| TestCompressed {
+                id: Some(format!("series-{i}")),
+                data_points: (0..1000).map(|x| 1_700_000_000 + x * 60).collect(),
+                name: format!("series {i}"),
+                age: i,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let bytes = TestCompressed::column_blob_bytes("data_points", &db).await?;
+        let uncompressed = (records.len() * 1000 * std::mem::size_of::<i64>()) as i64;
+        assert!(bytes > 0);
+        assert!(
+            bytes < uncompressed,
+            "{bytes} bytes is not below {uncompressed}"
+        );
+
+        assert!(TestCompressed::column_blob_bytes("name", &db)
+            .await
+            .is_err());
+        assert!(TestCompressed::column_blob_bytes("missing", &db)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compression_report_per_column() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let records: Vec<TestCompressed> = (0..3)
+            .map(|i| TestCompressed {
+                id: Some(format!("series-{i}")),
+                data_points: (0..1000).map(|x| 1_700_000_000 + x * 60).collect(),
+                name: format!("series {i}"),
+                age: i,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let report = TestCompressed::compression_report(&db).await?;
+        assert_eq!(report.len(), 1);
+        let data_points = &report[0];
+        assert_eq!(data_points.column, "data_points");
+        assert_eq!(data_points.rows, 3);
+        assert_eq!(data_points.elements, 3000);
+        assert_eq!(data_points.estimated_uncompressed_bytes, 24_000);
+        assert_eq!(
+            data_points.compressed_bytes as i64,
+            TestCompressed::column_blob_bytes("data_points", &db).await?
+        );
+        assert!(
+            data_points.ratio() > 1.0,
+            "ratio {} is not above 1",
+            data_points.ratio()
+        );
+
+        Migrations::init(&db, &[migration!(TestDocument)]).await?;
+        let body = "level=info msg=\"cache hit\"\n".repeat(1000);
+        TestDocument {
+            id: None,
+            title: "log".to_string(),
+            body: body.clone(),
+        }
+        .insert(&db)
+        .await?;
+        let report = TestDocument::compression_report(&db).await?;
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].column, "body");
+        assert_eq!(report[0].elements, body.len() as u64);
+        assert_eq!(report[0].estimated_uncompressed_bytes, body.len() as u64);
+        assert!(report[0].ratio() > 1.0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_interrupt_after_aborts_runaway_query() -> Result<(), Box<dyn std::error::Error>> {
+        let config =
+            DatabaseConfig::memory().with_interrupt_after(std::time::Duration::from_millis(100));
+        let db = Database::init(config).await?;
+
+        // Counts to a billion inside the engine; far longer than the interrupt deadline
+        let started = std::time::Instant::now();
+        let result = async {
+            let mut rows = db
+                .query(
+                    "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 1000000000) \
+                     SELECT COUNT(*) FROM c",
+                    vec![],
+                )
+                .await?;
+            rows.next().await.map(|_| ())
+        }
+        .await;
+
+        match result.map_err(orso::Error::from) {
+            Err(orso::Error::Interrupted) => {}
+            other => panic!("expected Error::Interrupted, got {other:?}"),
+        }
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+        // Quick statements still run normally afterwards
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        assert_eq!(TestUser::count(&db).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_interrupt_after_spares_later_statements() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let config =
+            DatabaseConfig::memory().with_interrupt_after(std::time::Duration::from_millis(100));
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+
+        // A fast statement finishes well within its deadline
+        assert_eq!(TestUser::count(&db).await?, 0);
+
+        // A transaction begun afterwards outlives that deadline; nothing may interrupt it
+        let tx = db.transaction().await?;
+        TestUser {
+            id: None,
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+            age: 30,
+            created_at: None,
+            updated_at: None,
+        }
+        .insert(tx.db())
+        .await?;
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        tx.commit().await?;
+        assert_eq!(TestUser::count(&db).await?, 1);
+
+        // Statements running side by side each keep their own deadline
+        let mut first = db.query("SELECT 1 UNION ALL SELECT 2", vec![]).await?;
+        let mut second = db.query("SELECT 3", vec![]).await?;
+        assert!(second.next().await?.is_some());
+        assert!(second.next().await?.is_none());
+        assert!(first.next().await?.is_some());
+        assert!(first.next().await?.is_some());
+        assert!(first.next().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_statements_reconnect_after_dropped_connection(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db_path = "reconnect_test.db";
+        let _ = std::fs::remove_file(db_path);
+
+        let db = Database::init(DatabaseConfig::local(db_path)).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        TestUser {
+            name: "Persisted".to_string(),
+            email: "persisted@example.com".to_string(),
+            age: 50,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        // The next statement fails the way a dropped remote stream does
+        db.inject_failure(libsql::Error::Hrana("stream closed: `eof`".into()));
+        assert_eq!(TestUser::count(&db).await?, 1);
+        db.inject_failure(libsql::Error::ConnectionFailed("reset".to_string()));
+        db.execute_write(
+            "UPDATE test_users SET age = ? WHERE name = ?",
+            vec![51.into(), "Persisted".into()],
+        )
+        .await?;
+        assert_eq!(TestUser::find_all(&db).await?[0].age, 51);
+        db.inject_failure(libsql::Error::ConnectionFailed("reset".to_string()));
+        db.execute_batch_write(
+            "UPDATE test_users SET age = 52; UPDATE test_users SET age = age + 1",
+        )
+        .await?;
+        assert_eq!(TestUser::find_all(&db).await?[0].age, 53);
+
+        // Statement errors are returned as is, without reconnecting
+        let err = db
+            .query("SELECT * FROM missing_table", vec![])
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        assert!(!orso::Error::from(err).is_connection_error());
+
+        // A transaction lost with the connection isn't retried on a fresh one
+        let tx = db.transaction().await?;
+        tx.db()
+            .inject_failure(libsql::Error::ConnectionFailed("reset".to_string()));
+        let err = TestUser::count(tx.db()).await.unwrap_err();
+        assert!(err.is_connection_error());
+        drop(tx);
+
+        drop(db);
+        let _ = std::fs::remove_file(db_path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_token_provider_refreshes_on_reconnect() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db_path = "token_refresh_test.db";
+        let _ = std::fs::remove_file(db_path);
+
+        let refreshes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let provider = {
+            let refreshes = refreshes.clone();
+            orso::TokenProvider::new(move || {
+                let n = refreshes.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                async move { Ok(format!("token-{n}")) }
+            })
+        };
+        let config = DatabaseConfig::local(db_path).with_token_provider(provider);
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+
+        // How the Hrana client reports the server rejecting the expired token
+        db.inject_failure(libsql::Error::Hrana(
+            "api error: `status=401, body=Unauthorized`".into(),
+        ));
+        assert_eq!(TestUser::count(&db).await?, 0);
+
+        assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(db.db_token(), "token-1");
+
+        drop(db);
+        let _ = std::fs::remove_file(db_path);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_field_names() {
+        assert_eq!(
+            TestCompressed::compressed_field_names(),
+            vec!["data_points"]
+        );
+        assert!(TestUser::compressed_field_names().is_empty());
+    }
+
+    #[test]
+    fn test_table_naming_strategies() {
+        #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+        #[orso_table(naming = "snake_case")]
+        struct TestUser {
+            #[orso_column(primary_key)]
+            id: Option<String>,
+        }
+
+        #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+        #[orso_table(naming = "snake_case_plural")]
+        struct OrderCategory {
+            #[orso_column(primary_key)]
+            id: Option<String>,
+        }
+
+        #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+        #[orso_table(naming = "snake_case_plural")]
+        struct HTTPAddress {
+            #[orso_column(primary_key)]
+            id: Option<String>,
+        }
+
+        #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+        #[orso_table(naming = "as_is")]
+        struct AuditEntry {
+            #[orso_column(primary_key)]
+            id: Option<String>,
+        }
+
+        #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+        struct PlainRecord {
+            #[orso_column(primary_key)]
+            id: Option<String>,
+        }
+
+        assert_eq!(TestUser::table_name(), "test_user");
+        assert_eq!(OrderCategory::table_name(), "order_categories");
+        assert_eq!(HTTPAddress::table_name(), "http_addresses");
+        assert_eq!(AuditEntry::table_name(), "AuditEntry");
+        assert_eq!(PlainRecord::table_name(), "plainrecord");
+    }
+
+    #[test]
+    fn test_database_config_builder_matches_constructors() {
+        let built = DatabaseConfig::builder()
+            .mode(orso::TursoMode::Sync)
+            .local_db_path("replica.db")
+            .db_url("libsql://example.turso.io")
+            .db_token("token")
+            .build()
+            .unwrap();
+        assert_eq!(
+            built,
+            DatabaseConfig::sync("replica.db", "libsql://example.turso.io", "token")
+        );
+
+        let built = DatabaseConfig::builder()
+            .mode(orso::TursoMode::Memory)
+            .busy_retries(3)
+            .build()
+            .unwrap();
+        assert_eq!(built, DatabaseConfig::memory().with_busy_retries(3));
+
+        // Each mode's required fields are checked
+        assert!(DatabaseConfig::builder().build().is_err());
+        assert!(DatabaseConfig::builder()
+            .mode(orso::TursoMode::Local)
+            .build()
+            .is_err());
+        assert!(DatabaseConfig::builder()
+            .mode(orso::TursoMode::Embed)
+            .local_db_path("replica.db")
+            .db_url("libsql://example.turso.io")
+            .build()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_count_by_column() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+
+        for (i, age) in [30, 30, 41, 30, 41, 25].into_iter().enumerate() {
+            TestUser {
+                name: format!("user{i}"),
+                email: format!("user{i}@example.com"),
+                age,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let by_age = TestUser::count_by("age", &db).await?;
+        assert_eq!(by_age.len(), 3);
+        assert_eq!(by_age[&Value::Integer(30)], 3);
+        assert_eq!(by_age[&Value::Integer(41)], 2);
+        assert_eq!(by_age[&Value::Integer(25)], 1);
+
+        assert!(TestUser::count_by("age; DROP TABLE test_users", &db)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_compressed_arrays_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(
+            &db,
+            &[migration!(TestCompressed), migration!(FieldTypeDebug)],
+        )
+        .await?;
+
+        TestCompressed {
+            id: Some("empty".to_string()),
+            data_points: vec![],
+            name: "empty".to_string(),
+            age: 0,
+        }
+        .insert(&db)
+        .await?;
+        FieldTypeDebug {
+            id: Some("empty".to_string()),
+            int_data: vec![],
+            float_data: vec![],
+            name: "empty".to_string(),
+        }
+        .insert(&db)
+        .await?;
+
+        let loaded = TestCompressed::find_by_id("empty", &db).await?.unwrap();
+        assert!(loaded.data_points.is_empty());
+        let loaded = FieldTypeDebug::find_by_id("empty", &db).await?.unwrap();
+        assert!(loaded.int_data.is_empty());
+        assert!(loaded.float_data.is_empty());
+
+        Ok(())
+    }
+
+    // test_users with an extra column, standing in for an edited TestUser
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_users")]
+    struct TestUserWithNickname {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        name: String,
+
+        #[orso_column(unique)]
+        email: String,
+
+        age: i32,
+
+        #[orso_column(created_at)]
+        created_at: Option<chrono::DateTime<chrono::Utc>>,
+
+        #[orso_column(updated_at)]
+        updated_at: Option<chrono::DateTime<chrono::Utc>>,
+
+        nickname: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn test_memory_shared_connections_see_each_others_writes(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let writer = Database::init(DatabaseConfig::memory_shared("orso_shared_test")).await?;
+        let reader = Database::init(DatabaseConfig::memory_shared("orso_shared_test")).await?;
+        Migrations::init(&writer, &[migration!(TestUser)]).await?;
+
+        TestUser {
+            id: None,
+            name: "Shared".to_string(),
+            email: "shared@example.com".to_string(),
+            age: 30,
+            created_at: None,
+            updated_at: None,
+        }
+        .insert(&writer)
+        .await?;
+        assert_eq!(TestUser::count(&reader).await?, 1);
+
+        // A different name, or a private memory database, is a separate database
+        let other = Database::init(DatabaseConfig::memory_shared("orso_shared_other")).await?;
+        assert_eq!(
+            TestUser::pending_changes(&other).await?,
+            vec![PendingChange::MissingTable {
+                table: "test_users".to_string()
+            }]
+        );
+        let private = Database::init(DatabaseConfig::memory()).await?;
+        assert_eq!(
+            TestUser::pending_changes(&private).await?,
+            vec![PendingChange::MissingTable {
+                table: "test_users".to_string()
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pending_changes_reports_schema_drift() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+
+        assert_eq!(
+            TestUser::pending_changes(&db).await?,
+            vec![PendingChange::MissingTable {
+                table: "test_users".to_string()
+            }]
+        );
+
+        TestUser::ensure_table(&db).await?;
+        assert!(TestUser::pending_changes(&db).await?.is_empty());
+
+        let changes = TestUserWithNickname::pending_changes(&db).await?;
+        assert!(changes.contains(&PendingChange::MissingColumn {
+            column: "nickname".to_string()
+        }));
+        assert!(changes
+            .iter()
+            .any(|change| change.to_string() == "Missing column: nickname"));
+
+        // Reporting doesn't touch the table
+        assert!(TestUser::pending_changes(&db).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_assert_in_sync_fails_on_out_of_band_change(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use orso::migrations::{MigrationConfig, MigrationEntry};
+
+        let db = Database::init(DatabaseConfig::memory()).await?;
+
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        Migrations::assert_in_sync(&db, &[migration!(TestUser)]).await?;
+
+        db.execute("ALTER TABLE test_users ADD COLUMN legacy_flag INTEGER")
+            .await?;
+        let Err(err) = Migrations::assert_in_sync(&db, &[migration!(TestUser)]).await else {
+            panic!("expected a schema mismatch");
+        };
+        assert!(matches!(err, orso::Error::Validation(_)));
+        let message = err.to_string();
+        assert!(message.contains("test_users: "), "{message}");
+        assert!(message.contains("Extra column: legacy_flag"), "{message}");
+
+        // Columns a migration would keep aren't drift, whether kept by the config or the entry
+        let config = MigrationConfig::default().with_preserve_extra_columns(true);
+        Migrations::assert_in_sync_with_config(&db, &[migration!(TestUser)], &config).await?;
+        let entry: Box<dyn MigrationTrait> =
+            Box::new(MigrationEntry::<TestUser>::new().preserve_extra_columns(true));
+        Migrations::assert_in_sync(&db, &[entry]).await?;
+        assert_eq!(
+            orso::migrations::pending_changes_with_name::<TestUser>(&db, "test_users", &config)
+                .await?,
+            Vec::<PendingChange>::new()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_default_sort_orders_finders_unless_overridden(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestRanked)]).await?;
+        for (title, rank) in [("b", 2), ("d", 9), ("a", 5), ("c", 5)] {
+            TestRanked {
+                id: None,
+                title: title.to_string(),
+                rank,
+            }
+            .insert(&db)
+            .await?;
+        }
+        let titles = |rows: &[TestRanked]| -> Vec<String> {
+            rows.iter().map(|row| row.title.clone()).collect()
+        };
+
+        assert_eq!(
+            titles(&TestRanked::find_all(&db).await?),
+            ["d", "a", "c", "b"]
+        );
+        let filter =
+            FilterOperator::Single(Filter::new_simple("rank", Operator::Ge, Value::Integer(5)));
+        assert_eq!(
+            titles(&TestRanked::find_where(filter, &db).await?),
+            ["d", "a", "c"]
+        );
+        assert_eq!(
+            titles(&TestRanked::list(None, None, &db).await?.data),
+            ["d", "a", "c", "b"]
+        );
+
+        let filter =
+            FilterOperator::Single(Filter::new_simple("rank", Operator::Ge, Value::Integer(5)));
+        let (page, total) = TestRanked::find_where_with_count(filter, None, None, &db).await?;
+        assert_eq!(titles(&page), ["d", "a", "c"]);
+        assert_eq!(total, 3);
+
+        let by_title = vec![Sort::new("title", SortOrder::Asc)];
+        assert_eq!(
+            titles(&TestRanked::list(Some(by_title), None, &db).await?.data),
+            ["a", "b", "c", "d"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_from_map_ignores_columns_missing_from_struct(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestSlimNote)]).await?;
+        // Columns a preserving migration left behind, unknown to the struct
+        db.execute("ALTER TABLE test_slim_notes ADD COLUMN legacy_title TEXT")
+            .await?;
+        db.execute("ALTER TABLE test_slim_notes ADD COLUMN legacy_blob BLOB")
+            .await?;
+        db.execute(
+            "INSERT INTO test_slim_notes (id, body, legacy_title, legacy_blob) \
+             VALUES ('n1', 'kept', 'old', x'00ff')",
+        )
+        .await?;
+
+        let notes = TestSlimNote::find_all(&db).await?;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].body, "kept");
+        assert_eq!(notes[0].id.as_deref(), Some("n1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compress_text_stores_large_text_compressed(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestDocument)]).await?;
+        // The BLOB column matches the expected schema, so init doesn't rebuild it again
+        assert!(TestDocument::pending_changes(&db).await?.is_empty());
+        assert_eq!(TestDocument::compressed_field_names(), vec!["body"]);
+        let by_body = vec![Sort::new("body", SortOrder::Asc)];
+        assert!(TestDocument::list(Some(by_body), None, &db).await.is_err());
+
+        let body = "2025-01-01T00:00:00Z INFO request served in 3ms\n".repeat(2100);
+        assert!(body.len() > 100_000);
+        TestDocument {
+            id: Some("log".to_string()),
+            title: "access log".to_string(),
+            body: body.clone(),
+        }
+        .insert(&db)
+        .await?;
+
+        let stored: Option<i64> = db
+            .query_scalar("SELECT length(body) FROM test_documents", vec![])
+            .await?;
+        assert!(stored.unwrap() < body.len() as i64 / 20);
+
+        let loaded = TestDocument::find_by_id("log", &db).await?.unwrap();
+        assert_eq!(loaded.body, body);
+        assert_eq!(loaded.title, "access log");
+
+        // A header claiming far more text than the payload can hold is rejected up front
+        let mut blob = orso::Utils::compress_text(&body)?;
+        assert_eq!(orso::Utils::decompress_text(&blob)?, body);
+        blob[5..13].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(orso::Utils::decompress_text(&blob).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wide_integers_round_trip_as_text() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestLedger)]).await?;
+
+        for (quantity, cap) in [
+            (i128::MAX, Some(u128::MAX)),
+            (i128::MIN, None),
+            (-42, Some(7)),
+        ] {
+            TestLedger {
+                id: None,
+                quantity,
+                ceiling: cap,
+                cap,
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let stored = db
+            .query_scalar::<String>(
+                "SELECT cap FROM test_ledger WHERE quantity = ?",
+                vec![libsql::Value::Text(i128::MAX.to_string())],
+            )
+            .await?;
+        assert_eq!(stored, Some(u128::MAX.to_string()));
+        let stored = db
+            .query_scalar::<String>(
+                "SELECT ceiling FROM test_ledger WHERE quantity = ?",
+                vec![libsql::Value::Text(i128::MIN.to_string())],
+            )
+            .await?;
+        assert_eq!(stored, None);
+
+        let mut rows: Vec<(i128, Option<u128>)> = TestLedger::find_all(&db)
+            .await?
+            .into_iter()
+            .map(|row| {
+                assert_eq!(row.ceiling, row.cap);
+                (row.quantity, row.cap)
+            })
+            .collect();
+        rows.sort();
+        assert_eq!(
+            rows,
+            vec![
+                (i128::MIN, None),
+                (-42, Some(7)),
+                (i128::MAX, Some(u128::MAX))
+            ]
+        );
+
+        // Text that isn't a number names the column
+        db.execute("UPDATE test_ledger SET quantity = 'lots' WHERE quantity = '-42'")
+            .await?;
+        let err = TestLedger::find_all(&db).await.unwrap_err();
+        assert!(
+            matches!(&err, orso::Error::FieldDeserialization { field, .. } if field == "quantity"),
+            "{err:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_collection_fields_round_trip_as_json_text(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestTagged)]).await?;
+
+        let tagged = TestTagged {
+            id: None,
+            tags: vec!["rust".to_string(), "sql, \"quoted\"".to_string()],
+            labels: [("env".to_string(), "prod".to_string())].into(),
+            aliases: Some(vec!["t1".to_string()]),
+        };
+        tagged.insert(&db).await?;
+        TestTagged::default().insert(&db).await?;
+
+        let mut rows = db
+            .query(
+                "SELECT tags, labels FROM test_tagged WHERE aliases IS NOT NULL",
+                vec![],
+            )
+            .await?;
+        let row = rows.next().await?.expect("inserted row");
+        assert_eq!(row.get::<String>(0)?, r#"["rust","sql, \"quoted\""]"#);
+        assert_eq!(row.get::<String>(1)?, r#"{"env":"prod"}"#);
+
+        let mut stored = TestTagged::find_all(&db).await?;
+        stored.sort_by_key(|r| r.aliases.is_none());
+        assert_eq!(stored[0].tags, tagged.tags);
+        assert_eq!(stored[0].labels, tagged.labels);
+        assert_eq!(stored[0].aliases, tagged.aliases);
+        assert!(stored[1].tags.is_empty());
+        assert!(stored[1].labels.is_empty());
+        assert_eq!(stored[1].aliases, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_not_null_option_field_uses_column_default(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestScored)]).await?;
+
+        let mut rows = db
+            .conn()
+            .query(
+                "SELECT \"notnull\", dflt_value FROM pragma_table_info('test_scored') WHERE name = 'score'",
+                (),
+            )
+            .await?;
+        let row = rows.next().await?.expect("score column");
+        assert_eq!(row.get::<i64>(0)?, 1);
+        assert_eq!(row.get::<String>(1)?, "0");
+        assert!(TestScored::pending_changes(&db).await?.is_empty());
+
+        TestScored {
+            name: "unscored".to_string(),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        TestScored {
+            name: "scored".to_string(),
+            score: Some(7),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        let by_name = |name: &str| FilterOperator::Single(Filter::eq("name", name));
+        let unscored = TestScored::find_one(by_name("unscored"), &db)
+            .await?
+            .expect("inserted row");
+        assert_eq!(unscored.score, Some(0));
+        let scored = TestScored::find_one(by_name("scored"), &db)
+            .await?
+            .expect("inserted row");
+        assert_eq!(scored.score, Some(7));
+
+        // A changed default alone is picked up, and survives the rebuild
+        let legacy_sql = TestScored::migration_sql()
+            .replace("test_scored", "test_scored_legacy")
+            .replace("DEFAULT (0)", "DEFAULT (5)");
+        db.execute(&legacy_sql).await?;
+        let config = orso::migrations::MigrationConfig::default();
+        let pending = orso::migrations::pending_changes_with_name::<TestScored>(
+            &db,
+            "test_scored_legacy",
+            &config,
+        )
+        .await?;
+        assert!(
+            pending.iter().any(|change| matches!(
+                change,
+                PendingChange::ColumnDiffers { column, aspect: ColumnAspect::Default, .. }
+                    if column == "score"
+            )),
+            "{pending:?}"
+        );
+        assert!(pending
+            .iter()
+            .any(|change| change.to_string().starts_with("Default mismatch for score")));
+        Migrations::init(&db, &[migration!(TestScored, "test_scored_legacy")]).await?;
+        assert!(orso::migrations::pending_changes_with_name::<TestScored>(
+            &db,
+            "test_scored_legacy",
+            &config
+        )
+        .await?
+        .is_empty());
+        db.execute("INSERT INTO test_scored_legacy (name) VALUES ('legacy')")
+            .await?;
+        let legacy = TestScored::find_all_with_table(&db, "test_scored_legacy").await?;
+        assert_eq!(legacy[0].score, Some(0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_in_tx_rolls_back_with_the_caller() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+
+        let tx = db.transaction().await?;
+        Migrations::apply_in_tx(&tx, &[migration!(TestUser)]).await?;
+        TestUser {
+            name: "Staged".to_string(),
+            email: "staged@example.com".to_string(),
+            age: 33,
+            ..Default::default()
+        }
+        .insert(tx.db())
+        .await?;
+        tx.rollback().await?;
+        assert_eq!(
+            TestUser::pending_changes(&db).await?,
+            vec![PendingChange::MissingTable {
+                table: "test_users".to_string()
+            }]
+        );
+
+        let tx = db.transaction().await?;
+        Migrations::apply_in_tx(&tx, &[migration!(TestUser)]).await?;
+        tx.commit().await?;
+        assert!(TestUser::pending_changes(&db).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_leaves_concurrent_statements_out(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = std::sync::Arc::new(Database::init(DatabaseConfig::memory()).await?);
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        let user = |name: &str| TestUser {
+            name: name.to_string(),
+            email: format!("{name}@example.com"),
+            age: 30,
+            ..Default::default()
+        };
+
+        let tx = db.transaction().await?;
+        user("staged").insert(tx.db()).await?;
+
+        // Another task writing through the same Database waits for the transaction
+        let concurrent = tokio::spawn({
+            let db = db.clone();
+            async move { user("concurrent").insert(&db).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!concurrent.is_finished());
+
+        tx.rollback().await?;
+        concurrent.await??;
+        let users = TestUser::find_all(&db).await?;
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, "concurrent");
+
+        // A read transaction shuts unrelated statements out of its snapshot the same way
+        let tx = db.read_transaction().await?;
+        let concurrent = tokio::spawn({
+            let db = db.clone();
+            async move { user("later").insert(&db).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(TestUser::count(tx.db()).await?, 1);
+        tx.commit().await?;
+        concurrent.await??;
+        assert_eq!(TestUser::count(&db).await?, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diff_sql_returns_statements_init_would_run(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+
+        let statements = Migrations::diff_sql(&db, &[migration!(TestUser)]).await?;
+        assert_eq!(statements.len(), 2);
+        assert!(statements[1].contains("CREATE TABLE IF NOT EXISTS test_users"));
+
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        assert!(Migrations::diff_sql(&db, &[migration!(TestUser)])
+            .await?
+            .is_empty());
+
+        let statements = Migrations::diff_sql(&db, &[migration!(TestUserWithNickname)]).await?;
+        assert_eq!(statements.len(), 4);
+        assert!(statements[0].contains("CREATE TABLE IF NOT EXISTS \"test_users_temp_"));
+        assert!(statements[0].contains("\"nickname\" TEXT"));
+        assert!(statements[1].starts_with("INSERT INTO \"test_users_temp_"));
+        assert!(statements[2].starts_with("ALTER TABLE test_users RENAME TO test_users_migration_"));
+        assert!(statements[3].ends_with("RENAME TO test_users"));
+
+        // Nothing ran: the live table still matches the old model
+        assert!(TestUser::pending_changes(&db).await?.is_empty());
+
+        // Indexes, the FTS index and the updated_at trigger are planned too
+        let statements =
+            Migrations::diff_sql(&db, &[migration!(TestTask), migration!(TestArticle)]).await?;
+        for prefix in [
+            "CREATE INDEX idx_test_tasks_created_at",
+            "CREATE UNIQUE INDEX uq_task_owner_title",
+            "CREATE VIRTUAL TABLE IF NOT EXISTS test_articles_fts",
+            "CREATE TRIGGER test_articles_fts_au",
+            "INSERT INTO test_articles_fts(test_articles_fts) VALUES ('rebuild')",
+        ] {
+            assert!(
+                statements.iter().any(|sql| sql.starts_with(prefix)),
+                "{prefix}: {statements:?}"
+            );
+        }
+        Migrations::init(&db, &[migration!(TestTask), migration!(TestArticle)]).await?;
+        assert!(
+            Migrations::diff_sql(&db, &[migration!(TestTask), migration!(TestArticle)])
+                .await?
+                .is_empty()
+        );
+
+        let config = orso::migrations::MigrationConfig::default().with_updated_at_trigger(true);
+        let statements =
+            Migrations::diff_sql_with_config(&db, &[migration!(TestUser)], &config).await?;
+        assert_eq!(statements.len(), 2);
+        assert!(statements[1].starts_with("CREATE TRIGGER test_users_touch_updated_at"));
+        Migrations::init_with_config(&db, &[migration!(TestUser)], &config).await?;
+        assert!(
+            Migrations::diff_sql_with_config(&db, &[migration!(TestUser)], &config)
+                .await?
+                .is_empty()
+        );
+        assert_eq!(
+            Migrations::diff_sql(&db, &[migration!(TestUser)]).await?,
+            vec!["DROP TRIGGER IF EXISTS test_users_touch_updated_at".to_string()]
+        );
+
+        // A rebuild moves the indexes to the backup table, so they're recreated
+        db.execute("ALTER TABLE test_tasks ADD COLUMN junk TEXT")
+            .await?;
+        let statements = Migrations::diff_sql(&db, &[migration!(TestTask)]).await?;
+        assert!(statements.contains(&"DROP INDEX uq_task_owner_title".to_string()));
+        Migrations::init(&db, &[migration!(TestTask)]).await?;
+        assert!(Migrations::diff_sql(&db, &[migration!(TestTask)])
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_changed_since_returns_rows_written_after_timestamp(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestUser::ensure_table(&db).await?;
+        for i in 0..4 {
+            TestUser {
+                name: format!("user{i}"),
+                email: format!("user{i}@example.com"),
+                age: 20 + i,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+        db.execute("UPDATE test_users SET updated_at = '2020-01-01T00:00:00.000Z'")
+            .await?;
+
+        let since = "2024-01-01T00:00:00Z".parse()?;
+        assert!(TestUser::changed_since(since, &db).await?.is_empty());
+
+        for name in ["user1", "user3"] {
+            let mut user =
+                TestUser::find_one(FilterOperator::Single(Filter::eq("name", name)), &db)
+                    .await?
+                    .expect("inserted user");
+            user.age += 10;
+            user.update(&db).await?;
+        }
+
+        let mut changed: Vec<String> = TestUser::changed_since(since, &db)
+            .await?
+            .into_iter()
+            .map(|user| user.name)
+            .collect();
+        changed.sort();
+        assert_eq!(changed, vec!["user1", "user3"]);
+        let everything = TestUser::changed_since("2019-12-31T23:59:59.5Z".parse()?, &db).await?;
+        assert_eq!(everything.len(), 4);
+
+        assert!(matches!(
+            TestCompressed::changed_since(since, &db).await,
+            Err(orso::Error::Validation(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_existing_key_is_unique_violation() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestUser::ensure_table(&db).await?;
+
+        let user = |email: &str| TestUser {
+            id: Some("fixed-id".to_string()),
+            name: "Keyed".to_string(),
+            email: email.to_string(),
+            age: 30,
+            ..Default::default()
+        };
+        user("first@example.com").insert(&db).await?;
+
+        let err = user("second@example.com").insert(&db).await.unwrap_err();
+        assert!(
+            matches!(&err, orso::Error::UniqueViolation { column, .. } if column == "id"),
+            "{err:?}"
+        );
+        let err = TestUser {
+            id: None,
+            ..user("first@example.com")
+        }
+        .insert(&db)
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(&err, orso::Error::UniqueViolation { column, .. } if column == "email"),
+            "{err:?}"
+        );
+
+        // insert_or_update takes the update path for an existing key
+        user("second@example.com").insert_or_update(&db).await?;
+        assert_eq!(TestUser::count(&db).await?, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_with_conflict_strategies() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        TestUser::ensure_table(&db).await?;
+
+        let user = |name: &str, age: i32| TestUser {
+            name: name.to_string(),
+            email: "dup@example.com".to_string(),
+            age,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            user("First", 20)
+                .insert_with(ConflictStrategy::Fail, &db)
+                .await?,
+            1
+        );
+        assert!(user("Second", 21)
+            .insert_with(ConflictStrategy::Fail, &db)
+            .await
+            .is_err());
+
+        assert_eq!(
+            user("Ignored", 22)
+                .insert_with(ConflictStrategy::Ignore, &db)
+                .await?,
+            0
+        );
+        let all = TestUser::find_all(&db).await?;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].name, "First");
+        let first_id = all[0].id.clone();
+
+        user("Updated", 23)
+            .insert_with(ConflictStrategy::Update, &db)
+            .await?;
+        let all = TestUser::find_all(&db).await?;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].name, "Updated");
+        assert_eq!(all[0].age, 23);
+        // An upsert keeps the existing row
+        assert_eq!(all[0].id, first_id);
+
+        user("Replaced", 24)
+            .insert_with(ConflictStrategy::Replace, &db)
+            .await?;
+        let all = TestUser::find_all(&db).await?;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].name, "Replaced");
+        // A replace deletes the old row and inserts a fresh one
+        assert_ne!(all[0].id, first_id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_as_reads_typed_values_from_projection(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        TestUser::ensure_table(&db).await?;
+
+        TestUser {
+            name: "Typed".to_string(),
+            email: "typed@example.com".to_string(),
+            age: 41,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        let rows = orso::QueryBuilder::new(TestUser::table_name())
+            .select_columns(&["name", "age", "created_at"])
+            .execute_maps(&db)
+            .await?;
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+
+        assert_eq!(row.get_as::<String>("name")?, "Typed");
+        assert_eq!(row.get_as::<i64>("age")?, 41);
+        assert_eq!(row.get_as::<i32>("age")?, 41);
+        assert_eq!(row.get_as::<f64>("age")?, 41.0);
+        assert!(row.get_as::<Option<String>>("created_at")?.is_some());
+
+        match row.get_as::<i64>("name") {
+            Err(orso::Error::FieldDeserialization { field, .. }) => assert_eq!(field, "name"),
+            other => panic!("expected a type mismatch, got {other:?}"),
+        }
+        assert!(matches!(
+            row.get_as::<String>("email"),
+            Err(orso::Error::NotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_create_lenient_reports_per_row() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        TestUser::ensure_table(&db).await?;
+
+        TestUser {
+            name: "Existing".to_string(),
+            email: "taken@example.com".to_string(),
+            age: 50,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        let users: Vec<TestUser> = ["first@example.com", "taken@example.com", "last@example.com"]
+            .iter()
+            .map(|email| TestUser {
+                name: "Import".to_string(),
+                email: email.to_string(),
+                age: 30,
+                ..Default::default()
+            })
+            .collect();
+
+        let outcomes = TestUser::batch_create_lenient(&users, &db).await?;
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes[1].is_err());
+
+        let first_id = outcomes[0].as_ref().unwrap();
+        let last_id = outcomes[2].as_ref().unwrap();
+        let first = TestUser::find_by_id(first_id.as_str(), &db).await?.unwrap();
+        assert_eq!(first.email, "first@example.com");
+        let last = TestUser::find_by_id(last_id.as_str(), &db).await?.unwrap();
+        assert_eq!(last.email, "last@example.com");
+
+        assert_eq!(TestUser::count(&db).await?, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_table_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        assert_eq!(
+            TestCompressed::stats(&db).await?,
+            orso::TableStats::default()
+        );
+
+        let records: Vec<TestCompressed> = (0..3)
+            .map(|i| TestCompressed {
+                id: Some(format!("stats-{i}")),
+                data_points: (0..100).collect(),
+                name: format!("series {i}"),
+                age: i,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let stats = TestCompressed::stats(&db).await?;
+        assert_eq!(stats.rows, 3);
+        let blob_bytes = TestCompressed::column_blob_bytes("data_points", &db).await?;
+        assert!(stats.bytes > blob_bytes as u64);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_as_new_duplicates_with_fresh_key_and_timestamps(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        TestUser::ensure_table(&db).await?;
+
+        let old = chrono::NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        TestUser {
+            id: Some("original".to_string()),
+            name: "Original".to_string(),
+            email: "original@example.com".to_string(),
+            age: 33,
+            created_at: Some(old),
+            updated_at: Some(old),
+        }
+        .insert(&db)
+        .await?;
+
+        let original = TestUser::find_by_id("original", &db).await?.unwrap();
+        assert_eq!(original.created_at, Some(old));
+
+        let mut copy = original.as_new();
+        assert!(copy.id.is_none());
+        assert!(copy.created_at.is_none());
+        assert!(copy.updated_at.is_none());
+        assert_eq!(copy.name, "Original");
+
+        copy.email = "copy@example.com".to_string();
+        copy.insert(&db).await?;
+
+        let stored = TestUser::find_one(
+            FilterOperator::Single(Filter::eq("email", "copy@example.com")),
+            &db,
+        )
+        .await?
+        .unwrap();
+        assert_ne!(stored.id, original.id);
+        assert!(stored.created_at.unwrap() > old);
+        assert!(stored.updated_at.unwrap() > old);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_ids_returns_matching_keys() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        for (name, age) in [("young", 25), ("middle", 35), ("senior", 60)] {
+            TestUser {
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let filter =
+            FilterOperator::Single(Filter::new_simple("age", Operator::Gt, Value::Integer(30)));
+        let ids = TestUser::find_ids(Some(filter.clone()), &db).await?;
+        let mut expected: Vec<String> = TestUser::find_where(filter, &db)
+            .await?
+            .into_iter()
+            .filter_map(|user| user.id)
+            .collect();
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert_eq!(ids.len(), 2);
+
+        let older =
+            TestUser::find_by_ids(&ids.iter().map(String::as_str).collect::<Vec<_>>(), &db).await?;
+        assert!(older.iter().all(|user| user.age > 30));
+        assert_eq!(TestUser::find_ids(None, &db).await?.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_all_union_across_shards() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+
+        for (month, age) in [(1, 25), (2, 45)] {
+            let date = chrono::NaiveDate::from_ymd_opt(2025, month, 10).unwrap();
+            TestUser {
+                name: format!("month{month}"),
+                email: format!("month{month}@example.com"),
+                age,
+                ..Default::default()
+            }
+            .insert_into_month_shard(&db, "users", &date)
+            .await?;
+        }
+
+        let shards = ["users_2025_01", "users_2025_02"];
+        let mut all = TestUser::find_all_union(&shards, None, &db).await?;
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<&str> = all.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["month1", "month2"]);
+
+        let filter =
+            FilterOperator::Single(Filter::new_simple("age", Operator::Gt, Value::Integer(30)));
+        let older = TestUser::find_all_union(&shards, Some(filter), &db).await?;
+        assert_eq!(older.len(), 1);
+        assert_eq!(older[0].name, "month2");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_fixtures_seed_and_reset() -> Result<(), Box<dyn std::error::Error>> {
+        use orso::Fixtures;
+
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        let users: Vec<TestUser> = (0..5)
+            .map(|i| TestUser {
+                id: None,
+                name: format!("fixture{i}"),
+                email: format!("fixture{i}@example.com"),
+                age: 20 + i,
+                created_at: None,
+                updated_at: None,
+            })
+            .collect();
+
+        Fixtures::seed(&db, &users).await?;
+        assert_eq!(TestUser::count(&db).await?, 5);
+
+        assert_eq!(Fixtures::reset::<TestUser>(&db).await?, 5);
+        assert_eq!(TestUser::count(&db).await?, 0);
+
+        // The table is kept, so it can be seeded again
+        Fixtures::seed(&db, &users[..2]).await?;
+        assert_eq!(TestUser::count(&db).await?, 2);
+
+        assert!(matches!(
+            Fixtures::reset_table(&db, "test_users; DROP TABLE test_users").await,
+            Err(orso::Error::Validation(_))
+        ));
+        assert_eq!(TestUser::count(&db).await?, 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "query-log")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_query_log_traces_sql_and_params() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestCompressed::ensure_table(&db).await?;
+        TestCompressed {
+            data_points: (0..64).collect(),
+            name: "logged".to_string(),
+            age: 41,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        TestCompressed::find_where(FilterOperator::Single(Filter::eq("name", "logged")), &db)
+            .await?;
+        TestCompressed::find_by_id("missing", &db).await?;
+
+        assert!(logs_contain("orso.sql"));
+        assert!(logs_contain(
+            "SELECT * FROM test_compressed WHERE id = ? LIMIT 1"
+        ));
+        assert!(logs_contain("INSERT INTO test_compressed"));
+        assert!(logs_contain("SELECT * FROM test_compressed WHERE name = ?"));
+        assert!(logs_contain("'logged'"));
+        assert!(logs_contain(" bytes>"));
+        assert!(logs_contain("elapsed_us"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_scalar_reads_single_value() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestUser::ensure_table(&db).await?;
+        assert_eq!(
+            db.query_scalar::<i64>("SELECT MAX(age) FROM test_users", vec![])
+                .await?,
+            None
+        );
+
+        for i in 0..3 {
+            TestUser {
+                name: format!("user{i}"),
+                email: format!("user{i}@example.com"),
+                age: 20 + i,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+        assert_eq!(
+            db.query_scalar::<i64>("SELECT COUNT(*) FROM test_users", vec![])
+                .await?,
+            Some(3)
+        );
+        assert_eq!(
+            db.query_scalar::<String>(
+                "SELECT name FROM test_users WHERE age = ?",
+                vec![libsql::Value::Integer(21)]
+            )
+            .await?,
+            Some("user1".to_string())
+        );
+        assert!(db
+            .query_scalar::<i64>("SELECT name FROM test_users WHERE age = 20", vec![])
+            .await
+            .is_err());
+        assert!(db
+            .query_scalar::<i64>("SELECT age FROM test_users", vec![])
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pragmas_applied_at_connect() -> Result<(), Box<dyn std::error::Error>> {
+        let config =
+            DatabaseConfig::memory().with_pragmas(vec![("cache_size".into(), "-4000".into())]);
+        let db = Database::init(config).await?;
+        let mut rows = db.query("PRAGMA cache_size", vec![]).await?;
+        let row = rows.next().await?.expect("cache_size row");
+        assert_eq!(row.get::<i64>(0)?, -4000);
+
+        // Typos are rejected rather than silently ignored by SQLite
+        let typo = DatabaseConfig::memory().with_pragmas(vec![("cache_sise".into(), "1".into())]);
+        let Err(err) = Database::init(typo).await else {
+            panic!("unknown pragma accepted")
+        };
+        assert!(matches!(err.downcast_ref(), Some(orso::Error::Config(_))));
+        let injected =
+            DatabaseConfig::memory().with_pragmas(vec![("cache_size".into(), "1; DROP".into())]);
+        let Err(err) = Database::init(injected).await else {
+            panic!("invalid pragma value accepted")
+        };
+        assert!(matches!(err.downcast_ref(), Some(orso::Error::Config(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_fetches_in_configured_batches() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestUser::ensure_table(&db).await?;
+        assert_eq!(
+            TestUser::stream(&db)?.batch_size(),
+            DEFAULT_FETCH_BATCH_SIZE
+        );
+
+        let db = Database::init(DatabaseConfig::memory().with_fetch_batch_size(100)).await?;
+        TestUser::ensure_table(&db).await?;
+
+        let users: Vec<TestUser> = (0..1050)
+            .map(|i| TestUser {
+                name: format!("user{i}"),
+                email: format!("user{i}@example.com"),
+                age: i % 90,
+                ..Default::default()
+            })
+            .collect();
+        TestUser::batch_create(&users, &db).await?;
+
+        let mut stream = TestUser::stream(&db)?;
+        assert_eq!(stream.batch_size(), 100);
+        let mut seen = std::collections::HashSet::new();
+        while let Some(user) = stream.next().await? {
+            assert!(seen.insert(user.email));
+        }
+        assert_eq!(seen.len(), 1050);
+        assert_eq!(stream.batches_fetched(), 11);
+
+        // A per-stream override and a filter
+        let mut stream =
+            TestUser::stream_where(FilterOperator::Single(Filter::lt("age", 10)), &db)?
+                .with_batch_size(7);
+        let mut count = 0;
+        while let Some(user) = stream.next().await? {
+            assert!(user.age < 10);
+            count += 1;
+        }
+        assert_eq!(count, 120);
+        assert_eq!(stream.batches_fetched(), 18);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_compressed_column_is_rejected() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestCompressed::ensure_table(&db).await?;
+        TestCompressed {
+            id: None,
+            data_points: vec![1, 2, 3],
+            name: "a".to_string(),
+            age: 1,
+        }
+        .insert(&db)
+        .await?;
+
+        let err = TestCompressed::list(
+            Some(vec![
+                Sort::new("name", SortOrder::Asc),
+                Sort::new("data_points", SortOrder::Desc),
+            ]),
+            None,
+            &db,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, orso::Error::Validation(_)));
+        assert!(err.to_string().contains("compressed column 'data_points'"));
+
+        let err = TestCompressed::list_where(
+            FilterOperator::Single(Filter::gt("age", 0)),
+            Some(vec![Sort::new("data_points", SortOrder::Asc)]),
+            None,
+            &db,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("data_points"));
+
+        // Plain columns still sort
+        let listed =
+            TestCompressed::list(Some(vec![Sort::new("age", SortOrder::Desc)]), None, &db).await?;
+        assert_eq!(listed.data.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_random_samples_distinct_rows() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestCompressed::ensure_table(&db).await?;
+
+        let records: Vec<TestCompressed> = (0..20)
+            .map(|i| TestCompressed {
+                id: None,
+                data_points: vec![i, i * 2, i * 3],
+                name: format!("row{i}"),
+                age: i as i32,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let sample = TestCompressed::find_random(5, &db).await?;
+        assert_eq!(sample.len(), 5);
+        let ids: std::collections::HashSet<_> = sample.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(ids.len(), 5);
+        for record in &sample {
+            let i = record.age as i64;
+            assert_eq!(record.data_points, vec![i, i * 2, i * 3]);
+        }
+
+        assert_eq!(TestCompressed::find_random(50, &db).await?.len(), 20);
+        assert!(TestCompressed::find_random(0, &db).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_ndjson_writes_one_line_per_row() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let records: Vec<TestCompressed> = (0..1000)
+            .map(|i| TestCompressed {
+                id: Some(format!("row-{i}")),
+                data_points: vec![i, i + 1, i + 2],
+                name: format!("row {i}"),
+                age: (i % 90) as i32,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let written = TestCompressed::export_ndjson(&mut buffer, &db).await?;
+        assert_eq!(written, 1000);
+
+        let text = String::from_utf8(buffer)?;
+        assert_eq!(text.lines().count(), 1000);
+        // Compressed columns are exported decoded
+        let first: TestCompressed = serde_json::from_str(text.lines().next().unwrap())?;
+        let original = records.iter().find(|r| r.id == first.id).unwrap();
+        assert_eq!(first.data_points, original.data_points);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_ndjson_round_trips_export() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let records: Vec<TestCompressed> = (0..25)
+            .map(|i| TestCompressed {
+                id: Some(format!("row-{i:02}")),
+                data_points: (0..50).map(|x| x * i).collect(),
+                name: format!("row {i}"),
+                age: i as i32,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let mut buffer: Vec<u8> = Vec::new();
+        TestCompressed::export_ndjson(&mut buffer, &db).await?;
+
+        Migrations::init(
+            &db,
+            &[migration!(TestCompressed, "test_compressed_restore")],
+        )
+        .await?;
+        let imported = TestCompressed::import_ndjson_with_table(
+            buffer.as_slice(),
+            10,
+            &db,
+            "test_compressed_restore",
+        )
+        .await?;
+        assert_eq!(imported, 25);
+
+        let mut restored =
+            TestCompressed::find_all_with_table(&db, "test_compressed_restore").await?;
+        restored.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(restored.len(), records.len());
+        for (restored, original) in restored.iter().zip(&records) {
+            assert_eq!(restored.id, original.id);
+            assert_eq!(restored.data_points, original.data_points);
+            assert_eq!(restored.name, original.name);
+            assert_eq!(restored.age, original.age);
+        }
+
+        // A malformed line names its line number
+        let bad = b"{\"id\":\"ok\",\"data_points\":[],\"name\":\"a\",\"age\":1}\nnot json\n";
+        match TestCompressed::import_ndjson(&bad[..], 10, &db).await {
+            Err(orso::Error::Serialization(msg)) => assert!(msg.contains("line 2"), "{msg}"),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compressed_ndjson_export_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let records: Vec<TestCompressed> = (0..200)
+            .map(|i| TestCompressed {
+                id: Some(format!("row-{i:03}")),
+                data_points: (0..20).map(|x| x + i).collect(),
+                name: format!("row {i}"),
+                age: (i % 60) as i32,
+            })
+            .collect();
+        TestCompressed::batch_create(&records, &db).await?;
+
+        let mut plain: Vec<u8> = Vec::new();
+        TestCompressed::export_ndjson(&mut plain, &db).await?;
+
+        for (compression, table) in [
+            (orso::ExportCompression::Zstd, "test_compressed_zstd"),
+            (orso::ExportCompression::Gzip, "test_compressed_gzip"),
+        ] {
+            let mut buffer: Vec<u8> = Vec::new();
+            let written =
+                TestCompressed::export_ndjson_compressed(&mut buffer, compression, &db).await?;
+            assert_eq!(written, 200);
+            assert!(buffer.len() < plain.len());
+
+            Migrations::init(&db, &[migration!(TestCompressed, table)]).await?;
+            let imported =
+                TestCompressed::import_ndjson_with_table(buffer.as_slice(), 64, &db, table).await?;
+            assert_eq!(imported, 200);
+
+            let mut restored = TestCompressed::find_all_with_table(&db, table).await?;
+            restored.sort_by(|a, b| a.id.cmp(&b.id));
+            assert_eq!(restored.len(), records.len());
+            for (restored, original) in restored.iter().zip(&records) {
+                assert_eq!(restored.id, original.id);
+                assert_eq!(restored.data_points, original.data_points);
+                assert_eq!(restored.name, original.name);
+                assert_eq!(restored.age, original.age);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_filter_expression() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        TestUser::ensure_table(&db).await?;
+
+        for (name, age) in [("bob", 35), ("Bobby", 28), ("alice", 40), ("carol", 31)] {
+            TestUser {
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let columns = TestUser::field_names();
+        let parsed = FilterOperator::parse(
+            "age > 30 AND (name LIKE '%bob%' OR email = 'alice@example.com') AND NOT age IN (40, 41)",
+            &columns,
+        )?;
+        let built = FilterOperator::And(vec![
+            FilterOperator::Single(Filter::gt("age", 30)),
+            FilterOperator::Or(vec![
+                FilterOperator::Single(Filter::like("name", "%bob%")),
+                FilterOperator::Single(Filter::eq("email", "alice@example.com")),
+            ]),
+            FilterOperator::Not(Box::new(FilterOperator::Single(Filter::in_values(
+                "age",
+                vec![40, 41],
+            )))),
+        ]);
+        assert_eq!(
+            orso::FilterOperations::build_filter_operator(&parsed)?,
+            orso::FilterOperations::build_filter_operator(&built)?
+        );
+
+        let mut from_parsed = TestUser::find_where(parsed, &db).await?;
+        let mut from_built = TestUser::find_where(built, &db).await?;
+        from_parsed.sort_by(|a, b| a.name.cmp(&b.name));
+        from_built.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<&str> = from_parsed.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["bob"]);
+        assert_eq!(from_parsed.len(), from_built.len());
+
+        let between =
+            FilterOperator::parse("age BETWEEN 30 AND 35 AND email IS NOT NULL", &columns)?;
+        assert_eq!(TestUser::find_where(between, &db).await?.len(), 2);
+
+        match FilterOperator::parse("age > 30 AND salary < 10", &columns) {
+            Err(orso::Error::Query(msg)) => {
+                assert!(
+                    msg.contains("position 13") && msg.contains("salary"),
+                    "{msg}"
+                )
+            }
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+        match FilterOperator::parse("age ~ 30", &columns) {
+            Err(orso::Error::Query(msg)) => assert!(msg.contains("position 4"), "{msg}"),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+        assert!(FilterOperator::parse("(age > 30", &columns).is_err());
+
+        // Deep nesting is a parse error rather than a stack overflow
+        let nested = format!("{}age > 30{}", "(".repeat(100_000), ")".repeat(100_000));
+        match FilterOperator::parse(&nested, &columns) {
+            Err(orso::Error::Query(msg)) => assert!(msg.contains("nested deeper"), "{msg}"),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+        assert!(FilterOperator::parse(&"NOT ".repeat(100_000), &columns).is_err());
+        let shallow = format!("{}age > 30{}", "(".repeat(32), ")".repeat(32));
+        assert!(FilterOperator::parse(&shallow, &columns).is_ok());
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_articles")]
+    struct TestArticle {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        #[orso_column(fts)]
+        title: String,
+
+        #[orso_column(fts)]
+        content: String,
+    }
+
+    #[tokio::test]
+    async fn test_ensure_all_creates_tables_and_indexes() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        let models = [
+            migration!(TestUser),
+            migration!(TestPost),
+            migration!(TestArticle),
+        ];
+        Migrations::ensure_all(&db, &models).await?;
+        // Repeat runs are no-ops
+        Migrations::ensure_all(&db, &models).await?;
+
+        let mut rows = db
+            .query(
+                "SELECT type, name FROM sqlite_master WHERE tbl_name LIKE 'test_%' ORDER BY name",
+                vec![],
+            )
+            .await?;
+        let mut objects = Vec::new();
+        while let Some(row) = rows.next().await? {
+            objects.push((row.get::<String>(0)?, row.get::<String>(1)?));
+        }
+        let has = |kind: &str, name: &str| objects.iter().any(|(k, n)| k == kind && n == name);
+
+        assert!(has("table", "test_users"));
+        assert!(has("table", "test_posts"));
+        assert!(has("table", "test_articles"));
+        assert!(has("table", "test_articles_fts"));
+        assert!(has("trigger", "test_articles_fts_ai"));
+        // The unique email column is backed by an index
+        assert!(
+            objects
+                .iter()
+                .any(|(kind, name)| kind == "index"
+                    && name.starts_with("sqlite_autoindex_test_users")),
+            "no index on test_users in {objects:?}"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestArticle)]).await?;
+
+        for (id, title, content) in [
+            (
+                "a",
+                "Gardening",
+                "Tomatoes need sun. Rust on leaves is a fungus.",
+            ),
+            (
+                "b",
+                "Rust tips",
+                "Rust ownership and rust lifetimes, all about rust.",
+            ),
+            ("c", "Baking", "Bread needs flour, water and time."),
+        ] {
+            TestArticle {
+                id: Some(id.to_string()),
+                title: title.to_string(),
+                content: content.to_string(),
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let results = TestArticle::full_text_search("rust", &db).await?;
+        let ids: Vec<&str> = results.iter().filter_map(|a| a.id.as_deref()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+
+        // Triggers keep the index in sync with updates and deletes
+        let mut baking = TestArticle::find_by_id("c", &db).await?.unwrap();
+        baking.content = "Sourdough bread with a rusty old oven".to_string();
+        baking.update(&db).await?;
+        assert_eq!(
+            TestArticle::full_text_search("sourdough", &db).await?.len(),
+            1
+        );
+        assert!(TestArticle::full_text_search("flour", &db)
+            .await?
+            .is_empty());
+
+        TestArticle::find_by_id("b", &db)
+            .await?
+            .unwrap()
+            .delete(&db)
+            .await?;
+        let ids: Vec<String> = TestArticle::full_text_search("rust", &db)
+            .await?
+            .into_iter()
+            .filter_map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec!["a"]);
+
+        // Models without fts columns can't be searched
+        assert!(TestUser::full_text_search("rust", &db).await.is_err());
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_versioned")]
+    struct TestVersionedV1 {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        name: String,
+
+        #[orso_column(schema_version)]
+        schema_version: Option<i64>,
+    }
+
+    // The next release of TestVersionedV1, with an added column
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_versioned")]
+    struct TestVersionedV2 {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        name: String,
+
+        #[orso_column(schema_version)]
+        schema_version: Option<i64>,
+
+        score: Option<i64>,
+    }
+
+    #[tokio::test]
+    async fn test_schema_version_column_stamped_on_write() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestVersionedV1)]).await?;
+
+        let v1 = TestVersionedV1::schema_version();
+        let v2 = TestVersionedV2::schema_version();
+        assert_ne!(v1, v2);
+        assert_eq!(v1, TestVersionedV1::schema_version());
+
+        TestVersionedV1 {
+            name: "old".to_string(),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        Migrations::init(&db, &[migration!(TestVersionedV2)]).await?;
+        TestVersionedV2 {
+            name: "new".to_string(),
+            score: Some(3),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        let rows = TestVersionedV2::find_all(&db).await?;
+        let version_of = |name: &str| {
+            rows.iter()
+                .find(|row| row.name == name)
+                .and_then(|row| row.schema_version)
+        };
+        assert_eq!(version_of("old"), Some(v1));
+        assert_eq!(version_of("new"), Some(v2));
+
+        // Rewriting an old row under the new model restamps it, partial updates included
+        let old = rows.iter().find(|row| row.name == "old").unwrap();
+        let id = old.id.clone().unwrap();
+        TestVersionedV2::update_fields(
+            &id,
+            std::collections::HashMap::from([("score", Value::Integer(1))]),
+            &db,
+        )
+        .await?;
+        let old = TestVersionedV2::find_by_id(&id, &db).await?.unwrap();
+        assert_eq!(old.schema_version, Some(v2));
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_tasks")]
+    #[orso_index(("created_at"), order = "desc", where = "deleted_at IS NULL")]
+    #[orso_index(("owner", "title"), unique, name = "uq_task_owner_title")]
+    struct TestTask {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        owner: String,
+        title: String,
+        deleted_at: Option<String>,
+
+        #[orso_column(created_at)]
+        created_at: Option<chrono::DateTime<chrono::Utc>>,
+    }
+
+    #[tokio::test]
+    async fn test_any_of_ors_conditions_on_one_column() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestUser::ensure_table(&db).await?;
+        for age in [25, 30, 45, 51, 60] {
+            TestUser {
+                name: format!("age{age}"),
+                email: format!("age{age}@example.com"),
+                age,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let filter = Filter::any_of("age", vec![(Operator::Eq, 30), (Operator::Gt, 50)])?;
+        let (sql, params) = orso::FilterOperations::build_filter_operator(&filter)?;
+        assert_eq!(sql, "(age = ? OR age > ?)");
+        assert_eq!(params.len(), 2);
+
+        let mut ages: Vec<i32> = TestUser::find_where(filter, &db)
+            .await?
+            .into_iter()
+            .map(|user| user.age)
+            .collect();
+        ages.sort();
+        assert_eq!(ages, vec![30, 51, 60]);
+
+        // No conditions match nothing; multi-value operators are refused
+        let none = Filter::any_of("age", Vec::<(Operator, i64)>::new())?;
+        assert!(TestUser::find_where(none, &db).await?.is_empty());
+        for operator in [Operator::In, Operator::Between] {
+            assert!(matches!(
+                Filter::any_of("age", vec![(operator, 30)]),
+                Err(orso::Error::Validation(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_filter_translates_globs_and_escapes_like(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        TestUser::ensure_table(&db).await?;
+        for (i, name) in [
+            "foo-bar1",
+            "foobar!",
+            "foobar",
+            "xfoo-bar1",
+            "50% off",
+            "500 off",
+            "a_b",
+            "axb",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            TestUser {
+                name: name.to_string(),
+                email: format!("user{i}@example.com"),
+                age: 30,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let matching = |pattern: &str| {
+            let filter = FilterOperator::Single(Filter::new_wildcard("name", pattern));
+            let db = &db;
+            async move {
+                let mut names: Vec<String> = TestUser::find_where(filter, db)
+                    .await?
+                    .into_iter()
+                    .map(|user| user.name)
+                    .collect();
+                names.sort();
+                Ok::<_, orso::Error>(names)
+            }
+        };
+        assert_eq!(matching("foo*bar?").await?, vec!["foo-bar1", "foobar!"]);
+        assert_eq!(matching("50%*").await?, vec!["50% off"]);
+        assert_eq!(matching("a_b").await?, vec!["a_b"]);
+
+        let (sql, _) = orso::FilterOperations::build_filter(&Filter::new_wildcard("name", "a*"))?;
+        assert_eq!(sql, "name LIKE ? ESCAPE '\\'");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_like_copies_schema_without_rows() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestTask)]).await?;
+        TestUser {
+            name: "Existing".to_string(),
+            email: "existing@example.com".to_string(),
+            age: 40,
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        TestUser::create_like("test_users_staging", &db).await?;
+        assert!(orso::migrations::pending_changes_with_name::<TestUser>(
+            &db,
+            "test_users_staging",
+            &orso::migrations::MigrationConfig::default()
+        )
+        .await?
+        .is_empty());
+        assert_eq!(
+            TestUser::count_with_table(&db, "test_users_staging").await?,
+            0
+        );
+        assert_eq!(TestUser::count(&db).await?, 1);
+        assert!(matches!(
+            TestUser::create_like("test_users_staging", &db).await,
+            Err(orso::Error::Validation(_))
+        ));
+
+        // A named index stays on its table instead of moving to the copy
+        assert!(matches!(
+            TestTask::create_like("test_tasks_staging", &db).await,
+            Err(orso::Error::Validation(_))
+        ));
+        assert_eq!(
+            orso::migrations::pending_changes_with_name::<TestTask>(
+                &db,
+                "test_tasks_staging",
+                &orso::migrations::MigrationConfig::default()
+            )
+            .await?,
+            vec![PendingChange::MissingTable {
+                table: "test_tasks_staging".to_string()
+            }]
+        );
+        assert!(orso::migrations::pending_changes::<TestTask>(&db)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_orso_index_partial_and_descending() -> Result<(), Box<dyn std::error::Error>> {
+        async fn index_sql(
+            db: &Database,
+            name: &str,
+        ) -> Result<String, Box<dyn std::error::Error>> {
+            let sql = db
+                .query_scalar::<String>(
+                    "SELECT sql FROM sqlite_master WHERE type = 'index' AND name = ?",
+                    vec![libsql::Value::Text(name.to_string())],
+                )
+                .await?;
+            Ok(sql.unwrap_or_default())
+        }
+
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestTask)]).await?;
+        assert_eq!(
+            index_sql(&db, "idx_test_tasks_created_at").await?,
+            "CREATE INDEX idx_test_tasks_created_at ON test_tasks (created_at DESC) WHERE deleted_at IS NULL"
+        );
+        assert_eq!(
+            index_sql(&db, "uq_task_owner_title").await?,
+            "CREATE UNIQUE INDEX uq_task_owner_title ON test_tasks (owner, title)"
+        );
+        assert!(orso::migrations::pending_changes::<TestTask>(&db)
+            .await?
+            .is_empty());
+
+        // Modifiers are part of the index identity: a same-named index without them is
+        // reported and then replaced
+        db.execute("DROP INDEX idx_test_tasks_created_at").await?;
+        db.execute("CREATE INDEX idx_test_tasks_created_at ON test_tasks (created_at)")
+            .await?;
+        assert_eq!(
+            orso::migrations::pending_changes::<TestTask>(&db).await?,
+            vec![PendingChange::IndexDiffers {
+                name: "idx_test_tasks_created_at".to_string()
+            }]
+        );
+        Migrations::init(&db, &[migration!(TestTask)]).await?;
+        assert!(index_sql(&db, "idx_test_tasks_created_at")
+            .await?
+            .ends_with("(created_at DESC) WHERE deleted_at IS NULL"));
+
+        let plan = db
+            .explain(
+                "SELECT * FROM test_tasks WHERE deleted_at IS NULL ORDER BY created_at DESC",
+                vec![],
+            )
+            .await?;
+        assert!(plan.contains("idx_test_tasks_created_at"), "{plan}");
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_keep")]
+    struct TestKeepV1 {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        legacy_code: String,
+
+        name: String,
+    }
+
+    // TestKeepV1 with the field another system still reads removed from the struct
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_keep")]
+    struct TestKeepV2 {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_updated_at_trigger_stamps_raw_sql_updates(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use orso::migrations::MigrationConfig;
+
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        let config = MigrationConfig::default().with_updated_at_trigger(true);
+        Migrations::init_with_config(&db, &[migration!(TestUser)], &config).await?;
+        TestUser {
+            id: Some("u1".to_string()),
+            name: "Raw".to_string(),
+            email: "raw@example.com".to_string(),
+            age: 20,
+            created_at: None,
+            updated_at: None,
+        }
+        .insert(&db)
+        .await?;
+
+        // An explicit value is kept
+        let stale = "2000-01-01T00:00:00.000Z";
+        db.execute(&format!(
+            "UPDATE test_users SET updated_at = '{stale}' WHERE id = 'u1'"
+        ))
+        .await?;
+        let user = TestUser::find_by_id("u1", &db).await?.unwrap();
+        assert_eq!(
+            user.updated_at.unwrap().to_rfc3339(),
+            "2000-01-01T00:00:00+00:00"
+        );
+
+        // An update that doesn't touch updated_at gets the current time
+        db.execute("UPDATE test_users SET age = 21 WHERE id = 'u1'")
+            .await?;
+        let user = TestUser::find_by_id("u1", &db).await?.unwrap();
+        assert_eq!(user.age, 21);
+        let age = chrono::Utc::now() - user.updated_at.unwrap();
+        assert!(age < chrono::Duration::minutes(1), "{:?}", user.updated_at);
+
+        // Without the option the trigger is dropped
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        db.execute(&format!("UPDATE test_users SET updated_at = '{stale}'"))
+            .await?;
+        db.execute("UPDATE test_users SET age = 22").await?;
+        let user = TestUser::find_by_id("u1", &db).await?.unwrap();
+        assert_eq!(
+            user.updated_at.unwrap().to_rfc3339(),
+            "2000-01-01T00:00:00+00:00"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_preserve_extra_columns_keeps_removed_field_data(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use orso::migrations::{MigrationAction, MigrationConfig, MigrationEntry};
+
+        async fn legacy_codes(db: &Database) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+            let mut rows = db
+                .query("SELECT legacy_code FROM test_keep ORDER BY name", vec![])
+                .await?;
+            let mut codes = Vec::new();
+            while let Some(row) = rows.next().await? {
+                codes.push(row.get::<Option<String>>(0)?.unwrap_or_default());
+            }
+            Ok(codes)
+        }
+
+        // Globally, through MigrationConfig
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestKeepV1)]).await?;
+        TestKeepV1 {
+            id: None,
+            legacy_code: "LEG-1".to_string(),
+            name: "a".to_string(),
+        }
+        .insert(&db)
+        .await?;
+
+        let config = MigrationConfig::default().with_preserve_extra_columns(true);
+        let results = Migrations::init_with_config(&db, &[migration!(TestKeepV2)], &config).await?;
+        assert!(matches!(
+            results[0].action,
+            MigrationAction::DataMigrated { .. }
+        ));
+        assert_eq!(legacy_codes(&db).await?, vec!["LEG-1"]);
+
+        // The kept column is nullable, so the slimmer model can still insert, and the
+        // schema now counts as in sync
+        TestKeepV2 {
+            id: None,
+            name: "b".to_string(),
+        }
+        .insert(&db)
+        .await?;
+        assert_eq!(legacy_codes(&db).await?, vec!["LEG-1", ""]);
+        let results = Migrations::init_with_config(&db, &[migration!(TestKeepV2)], &config).await?;
+        assert!(matches!(results[0].action, MigrationAction::SchemaMatched));
+
+        // Per migration, overriding the default config
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestKeepV1)]).await?;
+        TestKeepV1 {
+            id: None,
+            legacy_code: "LEG-2".to_string(),
+            name: "a".to_string(),
+        }
+        .insert(&db)
+        .await?;
+        let keep: Box<dyn MigrationTrait> =
+            Box::new(MigrationEntry::<TestKeepV2>::new().preserve_extra_columns(true));
+        Migrations::init(&db, &[keep]).await?;
+        assert_eq!(legacy_codes(&db).await?, vec!["LEG-2"]);
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_flags")]
+    struct TestFlags {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        label: String,
+        active: bool,
+        verified: Option<bool>,
+    }
+
+    #[tokio::test]
+    async fn test_option_bool_round_trips_all_states() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestFlags)]).await?;
+
+        for (label, verified) in [("unknown", None), ("yes", Some(true)), ("no", Some(false))] {
+            TestFlags {
+                id: None,
+                label: label.to_string(),
+                active: verified.unwrap_or(true),
+                verified,
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        for (label, verified) in [("unknown", None), ("yes", Some(true)), ("no", Some(false))] {
+            let flags =
+                TestFlags::find_one(FilterOperator::Single(Filter::eq("label", label)), &db)
+                    .await?
+                    .unwrap();
+            assert_eq!(flags.verified, verified, "{label}");
+            assert_eq!(flags.active, verified.unwrap_or(true), "{label}");
+
+            // An update that keeps the value must preserve it too
+            let mut updated = flags.clone();
+            updated.label = format!("{label}-updated");
+            updated.update(&db).await?;
+            let reread = TestFlags::find_by_id(flags.id.as_deref().unwrap(), &db)
+                .await?
+                .unwrap();
+            assert_eq!(reread.verified, verified, "{label} after update");
+        }
+
+        assert_eq!(
+            TestFlags::count_where(FilterOperator::Single(Filter::is_null("verified")), &db)
+                .await?,
+            1
+        );
+        assert_eq!(
+            TestFlags::count_where(FilterOperator::Single(Filter::eq("verified", true)), &db)
+                .await?,
+            1
+        );
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_checked_flags")]
+    struct TestCheckedFlags {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        #[orso_column(check_bool)]
+        active: bool,
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_bool_is_rejected() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestFlags), migration!(TestCheckedFlags)]).await?;
+        assert!(TestCheckedFlags::pending_changes(&db).await?.is_empty());
+
+        // Without the CHECK the write lands, but reading it back doesn't coerce 2 to true
+        db.execute("INSERT INTO test_flags (label, active) VALUES ('rogue', 2)")
+            .await?;
+        let err = TestFlags::find_all(&db).await.unwrap_err();
+        assert!(
+            matches!(&err, orso::Error::FieldDeserialization { field, .. } if field == "active"),
+            "{err:?}"
+        );
+
+        assert!(db
+            .execute("INSERT INTO test_checked_flags (active) VALUES (2)")
+            .await
+            .is_err());
+        TestCheckedFlags {
+            id: None,
+            active: true,
+        }
+        .insert(&db)
+        .await?;
+        assert!(TestCheckedFlags::find_all(&db).await?[0].active);
+
+        // A table created before the constraint was declared is rebuilt with it
+        let legacy_sql = TestCheckedFlags::migration_sql()
+            .replace("test_checked_flags", "test_checked_legacy")
+            .replace(" CHECK (active IN (0, 1))", "");
+        db.execute(&legacy_sql).await?;
+        db.execute("INSERT INTO test_checked_legacy (active) VALUES (1)")
+            .await?;
+        let config = orso::migrations::MigrationConfig::default();
+        assert!(
+            !orso::migrations::pending_changes_with_name::<TestCheckedFlags>(
+                &db,
+                "test_checked_legacy",
+                &config
+            )
+            .await?
+            .is_empty()
+        );
+        Migrations::init(&db, &[migration!(TestCheckedFlags, "test_checked_legacy")]).await?;
+        assert!(db
+            .execute("INSERT INTO test_checked_legacy (active) VALUES (2)")
+            .await
+            .is_err());
+        assert_eq!(
+            TestCheckedFlags::count_with_table(&db, "test_checked_legacy").await?,
+            1
+        );
+        assert!(
+            orso::migrations::pending_changes_with_name::<TestCheckedFlags>(
+                &db,
+                "test_checked_legacy",
+                &config
+            )
+            .await?
+            .is_empty()
+        );
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_devices")]
+    struct TestDevice {
+        #[orso_column(primary_key, uuid_blob)]
+        id: Option<String>,
+
+        label: String,
+    }
+
+    #[tokio::test]
+    async fn test_uuid_blob_primary_key() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestDevice)]).await?;
+
+        // Generated by the column default
+        TestDevice {
+            label: "generated".to_string(),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        let generated = TestDevice::find_all(&db).await?.remove(0);
+        let generated_id = generated.id.clone().unwrap();
+        assert!(orso::Uuid::parse_str(&generated_id).is_ok());
+        assert_eq!(generated_id.len(), 36);
+
+        let explicit_id = orso::Uuid::new_v4().to_string();
+        let mut explicit = TestDevice {
+            id: Some(explicit_id.clone()),
+            label: "explicit".to_string(),
+        };
+        explicit.insert(&db).await?;
+
+        let mut rows = db
+            .query(
+                "SELECT typeof(id), length(id) FROM test_devices WHERE label = 'explicit'",
+                vec![],
+            )
+            .await?;
+        let row = rows.next().await?.unwrap();
+        assert_eq!(row.get::<String>(0)?, "blob");
+        assert_eq!(row.get::<i64>(1)?, 16);
+
+        let found = TestDevice::find_by_id(explicit_id.as_str(), &db)
+            .await?
+            .unwrap();
+        assert_eq!(found.id.as_deref(), Some(explicit_id.as_str()));
+        assert_eq!(found.label, "explicit");
+        assert_eq!(
+            TestDevice::find_by_id(generated_id.as_str(), &db)
+                .await?
+                .unwrap()
+                .label,
+            "generated"
+        );
+
+        explicit.label = "renamed".to_string();
+        explicit.update(&db).await?;
+        let found = TestDevice::find_by_id(explicit_id.as_str(), &db)
+            .await?
+            .unwrap();
+        assert_eq!(found.label, "renamed");
+
+        TestDevice::batch_delete(&[generated_id.as_str()], &db).await?;
+        assert!(TestDevice::find_by_id(generated_id.as_str(), &db)
+            .await?
+            .is_none());
+        assert_eq!(TestDevice::count(&db).await?, 1);
+
+        // The BLOB key matches the model's schema, so nothing to migrate
+        let results = Migrations::init(&db, &[migration!(TestDevice)]).await?;
+        assert!(matches!(
+            results[0].action,
+            orso::migrations::MigrationAction::SchemaMatched
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_count_by_having_filters_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let db = Database::init(DatabaseConfig::memory()).await?;
+        Migrations::init(&db, &[migration!(TestUser), migration!(TestPost)]).await?;
+
+        let mut ids = Vec::new();
+        for (name, posts) in [("prolific", 7), ("steady", 5), ("quiet", 2)] {
+            TestUser {
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
+                age: 30,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+            let user = TestUser::find_one(FilterOperator::Single(Filter::eq("name", name)), &db)
+                .await?
+                .unwrap();
+            let id = user.id.unwrap();
+            for i in 0..posts {
+                TestPost {
+                    id: None,
+                    user_id: id.clone(),
+                    title: format!("{name} {i}"),
+                }
+                .insert(&db)
+                .await?;
+            }
+            ids.push(id);
+        }
+
+        let busy = TestPost::count_by_having("user_id", 5, &db).await?;
+        assert_eq!(busy.len(), 1);
+        assert_eq!(busy[&Value::Text(ids[0].clone())], 7);
+
+        assert_eq!(TestPost::count_by_having("user_id", 1, &db).await?.len(), 3);
+        assert!(TestPost::count_by_having("user_id", 7, &db)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_count_multi_labeled_counts() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        TestUser::ensure_table(&db).await?;
+
+        for (i, age) in [15, 22, 35, 41, 67].iter().enumerate() {
+            TestUser {
+                name: format!("user{i}"),
+                email: format!("user{i}@example.com"),
+                age: *age,
+                ..Default::default()
+            }
+            .insert(&db)
+            .await?;
+        }
+
+        let counts = TestUser::count_multi(
+            &[
+                ("minors", FilterOperator::Single(Filter::lt("age", 18))),
+                ("adults", FilterOperator::Single(Filter::ge("age", 18))),
+                (
+                    "thirties",
+                    FilterOperator::Single(Filter::between("age", 30, 39)),
+                ),
+                ("nobody", FilterOperator::Single(Filter::gt("age", 100))),
+            ],
+            &db,
+        )
+        .await?;
+
+        assert_eq!(counts.len(), 4);
+        assert_eq!(counts["minors"], 1);
+        assert_eq!(counts["adults"], 4);
+        assert_eq!(counts["thirties"], 1);
+        assert_eq!(counts["nobody"], 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain_uses_index() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestUser)]).await?;
+        db.execute("CREATE INDEX idx_test_users_age ON test_users (age)")
+            .await?;
+
+        let plan = db
+            .explain(
+                "SELECT * FROM test_users WHERE age = ?",
+                vec![libsql::Value::Integer(30)],
+            )
+            .await?;
+
+        assert!(
+            plan.contains("idx_test_users_age"),
+            "unexpected plan: {plan}"
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_compressed_field_integration() -> Result<(), Box<dyn std::error::Error>> {
         // Create in-memory database
@@ -242,6 +4553,113 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_update_fields_partial() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestCompressed)]).await?;
+
+        let test_data = TestCompressed {
+            id: None,
+            data_points: (0..10_000).map(|i| i as i64 * 3).collect(),
+            name: "Before".to_string(),
+            age: 40,
+        };
+        test_data.insert(&db).await?;
+
+        let record = TestCompressed::find_all(&db).await?.remove(0);
+        let id = record.id.clone().unwrap();
+
+        let read_blob = |db: &Database, id: String| {
+            let conn = db.conn();
+            async move {
+                let mut rows = conn
+                    .query(
+                        "SELECT data_points FROM test_compressed WHERE id = ?",
+                        vec![libsql::Value::Text(id)],
+                    )
+                    .await?;
+                let row = rows.next().await?.expect("row exists");
+                row.get_value(0)
+            }
+        };
+        let blob_before = read_blob(&db, id.clone()).await?;
+
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("name", Value::Text("After".to_string()));
+        let affected = TestCompressed::update_fields(&id, fields, &db).await?;
+        assert_eq!(affected, 1);
+
+        // The compressed column is not rewritten
+        let blob_after = read_blob(&db, id.clone()).await?;
+        assert_eq!(blob_before, blob_after);
+
+        let updated = TestCompressed::find_by_id(&id, &db).await?.unwrap();
+        assert_eq!(updated.name, "After");
+        assert_eq!(updated.age, 40);
+        assert_eq!(updated.data_points, record.data_points);
+
+        // Unknown columns and the primary key are rejected
+        let mut bad = std::collections::HashMap::new();
+        bad.insert("missing", Value::Integer(1));
+        assert!(TestCompressed::update_fields(&id, bad, &db).await.is_err());
+        let mut pk = std::collections::HashMap::new();
+        pk.insert("id", Value::Text("other".to_string()));
+        assert!(TestCompressed::update_fields(&id, pk, &db).await.is_err());
+
+        Ok(())
+    }
+
+    #[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+    #[orso_table("test_audited")]
+    struct TestAudited {
+        #[orso_column(primary_key)]
+        id: Option<String>,
+
+        title: String,
+
+        #[orso_column(created_by)]
+        created_by: Option<String>,
+
+        #[orso_column(updated_by)]
+        updated_by: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn test_write_context_audit_columns() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+        Migrations::init(&db, &[migration!(TestAudited)]).await?;
+
+        assert_eq!(TestAudited::created_by_field(), Some("created_by"));
+        assert_eq!(TestAudited::updated_by_field(), Some("updated_by"));
+
+        let doc = TestAudited {
+            id: None,
+            title: "Draft".to_string(),
+            created_by: None,
+            updated_by: None,
+        };
+        doc.insert_with_context(&orso::WriteContext::new("alice"), &db)
+            .await?;
+
+        let mut stored = TestAudited::find_all(&db).await?.remove(0);
+        assert_eq!(stored.created_by.as_deref(), Some("alice"));
+        assert_eq!(stored.updated_by.as_deref(), Some("alice"));
+
+        stored.title = "Final".to_string();
+        stored
+            .update_with_context(&orso::WriteContext::new("bob"), &db)
+            .await?;
+
+        let stored = TestAudited::find_all(&db).await?.remove(0);
+        assert_eq!(stored.title, "Final");
+        assert_eq!(stored.created_by.as_deref(), Some("alice"));
+        assert_eq!(stored.updated_by.as_deref(), Some("bob"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_compressed_field_delete() -> Result<(), Box<dyn std::error::Error>> {
         // Create in-memory database
@@ -568,6 +4986,29 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_migrated_tables_reported() -> Result<(), Box<dyn std::error::Error>> {
+        let config = DatabaseConfig::memory();
+        let db = Database::init(config).await?;
+
+        let results = Migrations::init(
+            &db,
+            &[
+                migration!(TestUser),
+                migration!(TestCompressed),
+                migration!(TestCompressed, "test_compressed_archive"),
+            ],
+        )
+        .await?;
+
+        assert_eq!(
+            Migrations::migrated_tables(&results),
+            vec!["test_users", "test_compressed", "test_compressed_archive"]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_migration_no_change_detection() -> Result<(), Box<dyn std::error::Error>> {
         use crate as orso;
@@ -803,7 +5244,7 @@ mod tests {
         // Let's check the table schema to see what DEFAULT is set
         let schema_sql =
             "SELECT sql FROM sqlite_master WHERE type='table' AND name='id_generation_test'";
-        let mut rows = db.conn.query(schema_sql, ()).await?;
+        let mut rows = db.conn().query(schema_sql, ()).await?;
 
         if let Some(row) = rows.next().await? {
             let schema: String = row.get(0)?;
@@ -979,7 +5420,7 @@ Database retrieval verification:"
 Checking table schema..."
         );
         let mut rows = db
-            .conn
+            .conn()
             .query(
                 "SELECT sql FROM sqlite_master WHERE type='table' AND name='compression_test'",
                 (),
@@ -1538,7 +5979,7 @@ Test completed successfully!"
 
         // Batch upsert
         match BatchOperationsTest::batch_upsert(&upsert_data, &db2).await {
-            Ok(_) => println!("✓ Batch upsert succeeded"),
+            Ok(rows) => println!("✓ Batch upsert succeeded ({rows} rows)"),
             Err(e) => println!("✗ Batch upsert failed: {}", e),
         }
 
@@ -1621,7 +6062,7 @@ Test completed successfully!"
 
         // Check what's actually in the database
         let mut rows = db
-            .conn
+            .conn()
             .query("SELECT data_points FROM debug_compressed LIMIT 1", ())
             .await?;
         if let Some(row) = rows.next().await? {
@@ -1718,7 +6159,7 @@ Test completed successfully!"
 
         // Check what's actually in the database
         let mut rows = db
-            .conn
+            .conn()
             .query("SELECT data_points FROM debug_compressed LIMIT 1", ())
             .await?;
         if let Some(row) = rows.next().await? {
@@ -1828,7 +6269,7 @@ Test completed successfully!"
 
         // Check what's actually in the database
         let mut rows = db
-            .conn
+            .conn()
             .query(
                 "SELECT collected_data, vec_data FROM collect_vs_vec_test LIMIT 1",
                 (),
@@ -1957,7 +6398,7 @@ Test completed successfully!"
 
         // Check what's actually in the database
         let mut rows = db
-            .conn
+            .conn()
             .query(
                 "SELECT compressed_regular, compressed_with_alloc FROM allocator_test LIMIT 1",
                 (),
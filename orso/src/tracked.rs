@@ -0,0 +1,83 @@
+// Dirty tracking for partial updates
+use crate::{operations::CrudOperations, Database, Error, Orso, Result};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+/// A record paired with a snapshot of its loaded state, so `update` only writes the
+/// columns that changed since then.
+#[derive(Debug, Clone)]
+pub struct Tracked<T: Orso> {
+    record: T,
+    snapshot: HashMap<String, serde_json::Value>,
+}
+
+impl<T: Orso> Tracked<T> {
+    /// Start tracking a record from its current state
+    pub fn new(record: T) -> Result<Self> {
+        let snapshot = Self::snapshot_of(&record)?;
+        Ok(Self { record, snapshot })
+    }
+
+    // The snapshot is plain JSON, so taking it never encodes compressed fields
+    fn snapshot_of(record: &T) -> Result<HashMap<String, serde_json::Value>> {
+        Ok(serde_json::from_value(serde_json::to_value(record)?)?)
+    }
+
+    /// Fields whose value differs from the snapshot, in declaration order
+    pub fn changed_fields(&self) -> Result<Vec<&'static str>> {
+        let current = Self::snapshot_of(&self.record)?;
+        let generated = T::generated_fields();
+        let touched = T::touch_on_update_fields();
+
+        Ok(T::field_names()
+            .into_iter()
+            .filter(|field| *field != T::primary_key_field())
+            .filter(|field| !touched.contains(field))
+            .filter(|field| !generated.iter().any(|(name, _)| name == field))
+            .filter(|field| current.get(*field) != self.snapshot.get(*field))
+            .collect())
+    }
+
+    /// Write the changed fields (if any) and reset the snapshot. Returns rows affected.
+    pub async fn update(&mut self, db: &Database) -> Result<u64> {
+        self.update_with_table(db, T::table_name()).await
+    }
+
+    pub async fn update_with_table(&mut self, db: &Database, table_name: &str) -> Result<u64> {
+        let changed = self.changed_fields()?;
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        let id = self.record.get_primary_key().ok_or_else(|| {
+            Error::Validation("Cannot update a tracked record without a primary key".to_string())
+        })?;
+        let map = self.record.to_map_only(Some(&changed))?;
+        let fields: HashMap<&str, crate::Value> =
+            map.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
+
+        let affected =
+            CrudOperations::update_fields_with_table::<T>(&id, fields, db, table_name).await?;
+        self.snapshot = Self::snapshot_of(&self.record)?;
+        Ok(affected)
+    }
+
+    /// Stop tracking and return the record
+    pub fn into_inner(self) -> T {
+        self.record
+    }
+}
+
+impl<T: Orso> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.record
+    }
+}
+
+impl<T: Orso> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.record
+    }
+}
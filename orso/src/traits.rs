@@ -1,6 +1,7 @@
 use crate::{Database, FilterOperator, Result};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, de::DeserializeOwned};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +15,56 @@ pub enum FieldType {
     Timestamp,
 }
 
+/// A column computed by SQLite (`GENERATED ALWAYS AS (expression)`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedColumn {
+    pub expression: String,
+    pub stored: bool,
+}
+
+/// An index declared with `#[orso_index(...)]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableIndex {
+    pub columns: Vec<&'static str>,
+    /// Every column indexed `DESC` rather than `ASC`
+    pub descending: bool,
+    pub unique: bool,
+    /// `WHERE` clause of a partial index
+    pub predicate: Option<&'static str>,
+    /// Explicit index name; defaults to `idx_{table}_{columns}`
+    pub name: Option<&'static str>,
+}
+
+impl TableIndex {
+    pub fn index_name(&self, table_name: &str) -> String {
+        match self.name {
+            Some(name) => name.to_string(),
+            None => format!("idx_{}_{}", table_name, self.columns.join("_")),
+        }
+    }
+
+    /// The `CREATE INDEX` statement, in the form SQLite keeps in `sqlite_master`
+    pub fn create_sql(&self, table_name: &str) -> String {
+        let order = if self.descending { " DESC" } else { "" };
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| format!("{column}{order}"))
+            .collect();
+        let mut sql = format!(
+            "CREATE {}INDEX {} ON {} ({})",
+            if self.unique { "UNIQUE " } else { "" },
+            self.index_name(table_name),
+            table_name,
+            columns.join(", ")
+        );
+        if let Some(predicate) = self.predicate {
+            sql.push_str(&format!(" WHERE {predicate}"));
+        }
+        sql
+    }
+}
+
 #[allow(async_fn_in_trait)]
 pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
     fn table_name() -> &'static str;
@@ -26,9 +77,63 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
     fn updated_at_field() -> Option<&'static str> {
         None
     }
+    fn created_by_field() -> Option<&'static str> {
+        None
+    }
+    fn updated_by_field() -> Option<&'static str> {
+        None
+    }
+    /// Columns that default to the current time on insert (created_at, updated_at,
+    /// `default_now` and `touch_on_update` fields)
+    fn default_now_fields() -> Vec<&'static str> {
+        Self::created_at_field()
+            .into_iter()
+            .chain(Self::updated_at_field())
+            .collect()
+    }
+    /// Columns set to the current time on every update (updated_at and `touch_on_update` fields)
+    fn touch_on_update_fields() -> Vec<&'static str> {
+        Self::updated_at_field().into_iter().collect()
+    }
+    /// Column stamped with `schema_version()` on every write (`schema_version` attribute)
+    fn schema_version_field() -> Option<&'static str> {
+        None
+    }
+    /// Version derived from a fingerprint of the model's table definition; changes
+    /// whenever a field, type or constraint changes
+    fn schema_version() -> i64 {
+        crate::Utils::schema_fingerprint(&Self::migration_sql())
+    }
     fn unique_fields() -> Vec<&'static str> {
         vec![]
     }
+    /// `(column, referenced table)` for each `#[orso_column(ref = "...")]` field
+    fn foreign_keys() -> Vec<(&'static str, &'static str)> {
+        vec![]
+    }
+    /// Whether the primary key is a UUID stored as a 16-byte BLOB (`uuid_blob`)
+    fn primary_key_uuid_blob() -> bool {
+        false
+    }
+    /// Columns indexed for full-text search (`#[orso_column(fts)]`)
+    fn fts_fields() -> Vec<&'static str> {
+        vec![]
+    }
+    fn generated_fields() -> Vec<(&'static str, GeneratedColumn)> {
+        vec![]
+    }
+    /// Boolean columns constrained to 0/1 (`#[orso_column(check_bool)]`)
+    fn check_bool_fields() -> Vec<&'static str> {
+        vec![]
+    }
+    fn indexes() -> Vec<TableIndex> {
+        vec![]
+    }
+    /// Order used by `find_all`, `find_where`, `list` and `list_where` when no sort is
+    /// given (`#[orso_table(order_by = "created_at desc")]`)
+    fn default_sort() -> Option<Vec<crate::Sort>> {
+        None
+    }
     fn has_auto_id() -> bool {
         true
     }
@@ -40,36 +145,285 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
     fn field_types() -> Vec<FieldType>;
     fn field_nullable() -> Vec<bool>;
     fn field_compressed() -> Vec<bool>;
+    /// Per field, whether it is a `#[orso_column(compress_text)]` string stored as a
+    /// compressed BLOB
+    fn field_compressed_text() -> Vec<bool> {
+        vec![false; Self::field_names().len()]
+    }
+    /// Names of the compressed columns (vectors and `compress_text` strings), in field order
+    fn compressed_field_names() -> Vec<&'static str> {
+        Self::field_names()
+            .into_iter()
+            .zip(Self::field_compressed())
+            .zip(Self::field_compressed_text())
+            .filter_map(|((name, compressed), text)| (compressed || text).then_some(name))
+            .collect()
+    }
     fn columns() -> Vec<&'static str>;
 
-    fn get_primary_key(&self) -> Option<String>;
-    fn set_primary_key(&mut self, id: String);
+    /// Primary-key type, inferred by the derive from the primary-key field
+    type Key: std::fmt::Display + std::str::FromStr + Send + Sync;
+
+    fn get_key(&self) -> Option<Self::Key>;
+    fn set_key(&mut self, key: Self::Key);
+
+    fn get_primary_key(&self) -> Option<String> {
+        self.get_key().map(|key| key.to_string())
+    }
+    /// Set the key from its text form; text that doesn't parse as `Key` is an error and
+    /// leaves the key as it was
+    fn set_primary_key(&mut self, id: String) -> Result<()> {
+        let key = id.parse().map_err(|_| {
+            crate::Error::Validation(format!(
+                "Invalid primary key '{id}' for {}",
+                Self::table_name()
+            ))
+        })?;
+        self.set_key(key);
+        Ok(())
+    }
+    /// A copy with the primary key and created_at/updated_at cleared, so inserting it stores
+    /// a new row with fresh generated values
+    fn as_new(&self) -> Self;
+
     fn get_created_at(&self) -> Option<DateTime<Utc>>;
     fn get_updated_at(&self) -> Option<DateTime<Utc>>;
     fn set_updated_at(&mut self, updated_at: DateTime<Utc>);
 
     fn migration_sql() -> String;
 
-    fn to_map(&self) -> Result<HashMap<String, crate::Value>>;
+    /// Create the table if it doesn't exist yet. Unlike `Migrations::init`, an existing
+    /// table is left as is, even if its schema differs.
+    async fn ensure_table(db: &Database) -> Result<()> {
+        crate::migrations::ensure_table_exists_with_name::<Self>(db, Self::table_name()).await
+    }
+
+    /// Create an empty table named `new_table` with this model's columns and indexes
+    async fn create_like(new_table: &str, db: &Database) -> Result<()> {
+        crate::Migrations::create_like::<Self>(db, new_table).await
+    }
+
+    /// Schema differences a migration would fix for this model's table, without applying
+    /// them; empty when the table is in sync
+    async fn pending_changes(db: &Database) -> Result<Vec<crate::PendingChange>> {
+        crate::migrations::pending_changes::<Self>(db).await
+    }
+
+    fn to_map(&self) -> Result<HashMap<String, crate::Value>> {
+        self.to_map_only(None)
+    }
+    /// Like `to_map`, restricted to `fields` (all fields when `None`); unselected
+    /// compressed fields are not encoded
+    fn to_map_only(&self, fields: Option<&[&str]>) -> Result<HashMap<String, crate::Value>> {
+        Ok(self
+            .to_map_cow_only(fields)?
+            .into_iter()
+            .map(|(column, value)| (column.into_owned(), value))
+            .collect())
+    }
+    /// `to_map` keyed by the static column names, so building the map doesn't allocate a
+    /// `String` per column. Prefer it on hot write paths that only read the keys.
+    fn to_map_cow(&self) -> Result<HashMap<Cow<'static, str>, crate::Value>> {
+        self.to_map_cow_only(None)
+    }
+    /// `to_map_only` keyed like `to_map_cow`
+    fn to_map_cow_only(
+        &self,
+        fields: Option<&[&str]>,
+    ) -> Result<HashMap<Cow<'static, str>, crate::Value>>;
     fn from_map(map: HashMap<String, crate::Value>) -> Result<Self>;
 
+    /// Insert a new row. An existing primary key or unique value fails with
+    /// `Error::UniqueViolation`; use `insert_or_update` or `upsert` to write existing keys.
     async fn insert(&self, db: &Database) -> Result<()> {
         crate::operations::CrudOperations::insert(self, db).await
     }
     async fn insert_with_table(&self, db: &Database, table_name: &str) -> Result<()> {
         crate::operations::CrudOperations::insert_with_table(self, db, table_name).await
     }
+    /// Insert stored column values as they are; compressed columns take their encoded blob
+    /// without recompression (see `CrudOperations::insert_raw`)
+    async fn insert_raw(map: HashMap<String, crate::Value>, db: &Database) -> Result<()> {
+        crate::operations::CrudOperations::insert_raw::<Self>(map, db).await
+    }
+    async fn insert_raw_with_table(
+        map: HashMap<String, crate::Value>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()> {
+        crate::operations::CrudOperations::insert_raw_with_table::<Self>(map, db, table_name).await
+    }
+    /// Copy the rows of `source_table` into this model's table, renaming columns per
+    /// `column_map` (source -> target), e.g. after splitting or renaming a table
+    async fn migrate_from(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        db: &Database,
+    ) -> Result<u64> {
+        crate::operations::CrudOperations::migrate_from::<Self>(source_table, column_map, db).await
+    }
+    async fn migrate_from_with_table(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64> {
+        crate::operations::CrudOperations::migrate_from_with_table::<Self>(
+            source_table,
+            column_map,
+            db,
+            table_name,
+        )
+        .await
+    }
+    /// `migrate_from` with a per-row `transform` of the renamed source row
+    async fn migrate_from_with_transform<F>(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        transform: F,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        F: FnMut(&mut HashMap<String, crate::Value>) -> Result<()>,
+    {
+        crate::operations::CrudOperations::migrate_from_with_transform::<Self, F>(
+            source_table,
+            column_map,
+            transform,
+            db,
+        )
+        .await
+    }
+    async fn migrate_from_with_transform_and_table<F>(
+        source_table: &str,
+        column_map: HashMap<&str, &str>,
+        transform: F,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        F: FnMut(&mut HashMap<String, crate::Value>) -> Result<()>,
+    {
+        crate::operations::CrudOperations::migrate_from_with_transform_and_table::<Self, F>(
+            source_table,
+            column_map,
+            transform,
+            db,
+            table_name,
+        )
+        .await
+    }
+    /// Insert into the monthly shard of `base_name` for `date` (e.g. `bars_2025_01`),
+    /// creating the shard first if needed. Returns the shard's table name.
+    async fn insert_into_month_shard(
+        &self,
+        db: &Database,
+        base_name: &str,
+        date: &impl chrono::Datelike,
+    ) -> Result<String> {
+        let table_name = crate::Migrations::ensure_month_shard::<Self>(db, base_name, date).await?;
+        self.insert_with_table(db, &table_name).await?;
+        Ok(table_name)
+    }
+    /// Insert, resolving a collision with an existing row per `strategy`. Returns rows written.
+    async fn insert_with(&self, strategy: crate::ConflictStrategy, db: &Database) -> Result<u64> {
+        crate::operations::CrudOperations::insert_with_strategy(self, strategy, db).await
+    }
+    async fn insert_with_strategy_and_table(
+        &self,
+        strategy: crate::ConflictStrategy,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64> {
+        crate::operations::CrudOperations::insert_with_strategy_and_table(
+            self, strategy, db, table_name,
+        )
+        .await
+    }
+    async fn insert_with_context(
+        &self,
+        context: &crate::WriteContext,
+        db: &Database,
+    ) -> Result<()> {
+        crate::operations::CrudOperations::insert_with_context(self, context, db).await
+    }
+    async fn insert_with_context_and_table(
+        &self,
+        context: &crate::WriteContext,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()> {
+        crate::operations::CrudOperations::insert_with_context_and_table(
+            self, context, db, table_name,
+        )
+        .await
+    }
 
-    async fn find_by_id(id: &str, db: &Database) -> Result<Option<Self>> {
-        crate::operations::CrudOperations::find_by_id::<Self>(id, db).await
+    async fn find_by_id(id: impl Into<Self::Key>, db: &Database) -> Result<Option<Self>> {
+        let id = id.into().to_string();
+        crate::operations::CrudOperations::find_by_id::<Self>(&id, db).await
+    }
+
+    /// Load the parent row that foreign-key `column` refers to; `None` when the column is
+    /// NULL or the parent is gone. The derive also generates a `fetch_<name>` shorthand per
+    /// `ref` column (`fetch_user` for `user_id`).
+    async fn fetch_ref<P: Orso>(&self, column: &str, db: &Database) -> Result<Option<P>> {
+        let Some((_, table)) = Self::foreign_keys()
+            .into_iter()
+            .find(|(name, _)| *name == column)
+        else {
+            return Err(crate::Error::Validation(format!(
+                "'{column}' is not a foreign key of {}",
+                Self::table_name()
+            )));
+        };
+        if table != P::table_name() {
+            return Err(crate::Error::Validation(format!(
+                "'{column}' references {table}, not {}",
+                P::table_name()
+            )));
+        }
+
+        let map = self.to_map_only(Some(&[column]))?;
+        match map.get(column) {
+            Some(crate::Value::Text(id)) => {
+                crate::operations::CrudOperations::find_by_id::<P>(id, db).await
+            }
+            Some(crate::Value::Integer(id)) => {
+                crate::operations::CrudOperations::find_by_id::<P>(&id.to_string(), db).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Records of `C` whose `fk_column` points at the record with `parent_id`, e.g.
+    /// `User::find_children::<Post>(&user_id, "user_id", &db)`
+    async fn find_children<C: Orso>(
+        parent_id: &str,
+        fk_column: &str,
+        db: &Database,
+    ) -> Result<Vec<C>> {
+        crate::operations::CrudOperations::find_children::<C>(parent_id, fk_column, db).await
     }
 
     async fn find_by_id_with_table(
-        id: &str,
+        id: impl Into<Self::Key>,
         db: &Database,
         table_name: &str,
     ) -> Result<Option<Self>> {
-        crate::operations::CrudOperations::find_by_id_with_table::<Self>(id, db, table_name).await
+        let id = id.into().to_string();
+        crate::operations::CrudOperations::find_by_id_with_table::<Self>(&id, db, table_name).await
+    }
+
+    /// Find a record by its primary key and track it for partial updates
+    async fn find_by_id_tracked(
+        id: impl Into<Self::Key>,
+        db: &Database,
+    ) -> Result<Option<crate::Tracked<Self>>> {
+        Self::find_by_id(id, db)
+            .await?
+            .map(crate::Tracked::new)
+            .transpose()
     }
 
     async fn find_all(db: &Database) -> Result<Vec<Self>> {
@@ -84,6 +438,215 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         crate::operations::CrudOperations::find_where::<Self>(filter, db).await
     }
 
+    /// Records matching `filter`, keyed by their `key_column` value, e.g. posts per
+    /// `user_id` in one query
+    async fn find_where_grouped(
+        filter: FilterOperator,
+        key_column: &str,
+        db: &Database,
+    ) -> Result<HashMap<crate::Value, Vec<Self>>> {
+        crate::operations::CrudOperations::find_where_grouped::<Self>(filter, key_column, db).await
+    }
+
+    async fn find_where_grouped_with_table(
+        filter: FilterOperator,
+        key_column: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<crate::Value, Vec<Self>>> {
+        crate::operations::CrudOperations::find_where_grouped_with_table::<Self>(
+            filter, key_column, db, table_name,
+        )
+        .await
+    }
+
+    /// Up to `n` randomly sampled records
+    async fn find_random(n: usize, db: &Database) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::find_random::<Self>(n, db).await
+    }
+
+    async fn find_random_with_table(
+        n: usize,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::find_random_with_table::<Self>(n, db, table_name).await
+    }
+
+    /// Records one at a time, fetched from the database in `fetch_batch_size` batches
+    fn stream(db: &Database) -> Result<crate::RecordStream<'_, Self>> {
+        crate::operations::CrudOperations::stream::<Self>(None, db)
+    }
+
+    fn stream_where(
+        filter: FilterOperator,
+        db: &Database,
+    ) -> Result<crate::RecordStream<'_, Self>> {
+        crate::operations::CrudOperations::stream::<Self>(Some(filter), db)
+    }
+
+    fn stream_with_table<'a>(
+        db: &'a Database,
+        table_name: &str,
+    ) -> Result<crate::RecordStream<'a, Self>> {
+        crate::operations::CrudOperations::stream_with_table::<Self>(None, db, table_name)
+    }
+
+    /// A page of records ordered by `key_column` (any NOT NULL, non-compressed column),
+    /// starting after `pagination.cursor`; pass the result's `next_cursor` to continue
+    async fn find_after(
+        key_column: &str,
+        pagination: &crate::CursorPagination,
+        db: &Database,
+    ) -> Result<crate::CursorPaginatedResult<Self>> {
+        crate::operations::CrudOperations::find_after::<Self>(None, key_column, pagination, db)
+            .await
+    }
+
+    async fn find_after_where(
+        filter: FilterOperator,
+        key_column: &str,
+        pagination: &crate::CursorPagination,
+        db: &Database,
+    ) -> Result<crate::CursorPaginatedResult<Self>> {
+        crate::operations::CrudOperations::find_after::<Self>(
+            Some(filter),
+            key_column,
+            pagination,
+            db,
+        )
+        .await
+    }
+
+    async fn find_after_with_table(
+        key_column: &str,
+        pagination: &crate::CursorPagination,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<crate::CursorPaginatedResult<Self>> {
+        crate::operations::CrudOperations::find_after_with_table::<Self>(
+            None, key_column, pagination, db, table_name,
+        )
+        .await
+    }
+
+    /// Records written after `since`, by `updated_at` (or `created_at` when the model has no
+    /// `updated_at` column), oldest first; for incremental sync to another store
+    async fn changed_since(since: DateTime<Utc>, db: &Database) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::changed_since::<Self>(since, db).await
+    }
+
+    async fn changed_since_with_table(
+        since: DateTime<Utc>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::changed_since_with_table::<Self>(since, db, table_name)
+            .await
+    }
+
+    /// Stream every record to `writer` as newline-delimited JSON; returns records written
+    async fn export_ndjson<W>(writer: W, db: &Database) -> Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        crate::operations::CrudOperations::export_ndjson::<Self, W>(writer, db).await
+    }
+
+    async fn export_ndjson_with_table<W>(writer: W, db: &Database, table_name: &str) -> Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        crate::operations::CrudOperations::export_ndjson_with_table::<Self, W>(
+            writer, db, table_name,
+        )
+        .await
+    }
+
+    /// Stream records as newline-delimited JSON through a gzip or zstd encoder
+    async fn export_ndjson_compressed<W>(
+        writer: W,
+        compression: crate::ExportCompression,
+        db: &Database,
+    ) -> Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        crate::operations::CrudOperations::export_ndjson_compressed::<Self, W>(
+            writer,
+            compression,
+            db,
+        )
+        .await
+    }
+
+    async fn export_ndjson_compressed_with_table<W>(
+        writer: W,
+        compression: crate::ExportCompression,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        crate::operations::CrudOperations::export_ndjson_compressed_with_table::<Self, W>(
+            writer,
+            compression,
+            db,
+            table_name,
+        )
+        .await
+    }
+
+    /// Insert records from newline-delimited JSON (plain, gzip or zstd), `chunk_size` per
+    /// transaction
+    async fn import_ndjson<R>(reader: R, chunk_size: usize, db: &Database) -> Result<u64>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        crate::operations::CrudOperations::import_ndjson::<Self, R>(reader, chunk_size, db).await
+    }
+
+    async fn import_ndjson_with_table<R>(
+        reader: R,
+        chunk_size: usize,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        crate::operations::CrudOperations::import_ndjson_with_table::<Self, R>(
+            reader, chunk_size, db, table_name,
+        )
+        .await
+    }
+
+    /// Full-text search over the `fts` columns (FTS5 `MATCH` syntax), best matches first
+    async fn full_text_search(query: &str, db: &Database) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::full_text_search::<Self>(query, db).await
+    }
+
+    async fn full_text_search_with_table(
+        query: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::full_text_search_with_table::<Self>(
+            query, db, table_name,
+        )
+        .await
+    }
+
+    /// Read from several tables with this schema at once (`UNION ALL`), e.g. monthly shards
+    async fn find_all_union(
+        tables: &[&str],
+        filter: Option<FilterOperator>,
+        db: &Database,
+    ) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::find_all_union::<Self>(tables, filter, db).await
+    }
+
     async fn find_where_with_table(
         filter: FilterOperator,
         db: &Database,
@@ -101,6 +664,46 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         crate::operations::CrudOperations::update_with_table(self, db, table_name).await
     }
 
+    async fn update_with_context(
+        &self,
+        context: &crate::WriteContext,
+        db: &Database,
+    ) -> Result<()> {
+        crate::operations::CrudOperations::update_with_context(self, context, db).await
+    }
+
+    async fn update_with_context_and_table(
+        &self,
+        context: &crate::WriteContext,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<()> {
+        crate::operations::CrudOperations::update_with_context_and_table(
+            self, context, db, table_name,
+        )
+        .await
+    }
+
+    async fn update_fields(
+        id: &str,
+        fields: HashMap<&str, crate::Value>,
+        db: &Database,
+    ) -> Result<u64> {
+        crate::operations::CrudOperations::update_fields::<Self>(id, fields, db).await
+    }
+
+    async fn update_fields_with_table(
+        id: &str,
+        fields: HashMap<&str, crate::Value>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<u64> {
+        crate::operations::CrudOperations::update_fields_with_table::<Self>(
+            id, fields, db, table_name,
+        )
+        .await
+    }
+
     async fn delete(&self, db: &Database) -> Result<bool> {
         crate::operations::CrudOperations::delete(self, db).await
     }
@@ -117,6 +720,83 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         crate::operations::CrudOperations::count_with_table::<Self>(db, table_name).await
     }
 
+    /// Bytes a compressed column occupies on disk, summed over all rows
+    async fn column_blob_bytes(column: &str, db: &Database) -> Result<i64> {
+        crate::operations::CrudOperations::column_blob_bytes::<Self>(column, db).await
+    }
+
+    async fn column_blob_bytes_with_table(
+        column: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<i64> {
+        crate::operations::CrudOperations::column_blob_bytes_with_table::<Self>(
+            column, db, table_name,
+        )
+        .await
+    }
+
+    /// Per compressed column: stored bytes, element count and an uncompressed estimate
+    /// (8 bytes per element), e.g. for capacity reviews. Reads only value headers and SQL
+    /// `length()` sums; nothing is decompressed.
+    async fn compression_report(db: &Database) -> Result<Vec<crate::ColumnCompressionReport>> {
+        crate::operations::CrudOperations::compression_report::<Self>(db).await
+    }
+
+    async fn compression_report_with_table(
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<crate::ColumnCompressionReport>> {
+        crate::operations::CrudOperations::compression_report_with_table::<Self>(db, table_name)
+            .await
+    }
+
+    /// Records whose compressed integer column holds exactly `values`, e.g. to detect a
+    /// duplicate series; compares encoded BLOBs, so nothing is decompressed
+    async fn find_by_compressed(column: &str, values: &[i64], db: &Database) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::find_by_compressed::<Self>(column, values, db).await
+    }
+
+    async fn find_by_compressed_with_table(
+        column: &str,
+        values: &[i64],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::find_by_compressed_with_table::<Self>(
+            column, values, db, table_name,
+        )
+        .await
+    }
+
+    /// Encode arrays for the compressed column `field` without touching the database, e.g.
+    /// to prepare blobs for `insert_raw`; same bytes `to_map` would store
+    fn compress_field<E>(field: &str, arrays: &[Vec<E>]) -> Result<Vec<Vec<u8>>>
+    where
+        E: crate::CompressedElement,
+    {
+        crate::Utils::check_compressed_field::<Self, E>(field)?;
+        E::compress_many(arrays)
+    }
+
+    /// Decode blobs read from the compressed column `field`, the inverse of `compress_field`
+    fn decompress_field<E>(field: &str, blobs: &[Vec<u8>]) -> Result<Vec<Vec<E>>>
+    where
+        E: crate::CompressedElement,
+    {
+        crate::Utils::check_compressed_field::<Self, E>(field)?;
+        E::decompress_many(blobs)
+    }
+
+    /// Row count and approximate stored bytes, e.g. for a table usage view
+    async fn stats(db: &Database) -> Result<crate::TableStats> {
+        crate::operations::CrudOperations::stats::<Self>(db).await
+    }
+
+    async fn stats_with_table(db: &Database, table_name: &str) -> Result<crate::TableStats> {
+        crate::operations::CrudOperations::stats_with_table::<Self>(db, table_name).await
+    }
+
     // Advanced CRUD operations
     async fn insert_or_update(&self, db: &Database) -> Result<()> {
         crate::operations::CrudOperations::insert_or_update(self, db).await
@@ -139,6 +819,20 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         crate::operations::CrudOperations::batch_create(models, db).await
     }
 
+    /// Insert each record independently; returns per-record ids or errors, in order
+    async fn batch_create_lenient(models: &[Self], db: &Database) -> Result<Vec<Result<String>>> {
+        crate::operations::CrudOperations::batch_create_lenient(models, db).await
+    }
+
+    async fn batch_create_lenient_with_table(
+        models: &[Self],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Result<String>>> {
+        crate::operations::CrudOperations::batch_create_lenient_with_table(models, db, table_name)
+            .await
+    }
+
     async fn batch_insert_with_table(
         models: &[Self],
         db: &Database,
@@ -168,7 +862,7 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
             .await
     }
 
-    async fn batch_upsert(models: &[Self], db: &Database) -> Result<()> {
+    async fn batch_upsert(models: &[Self], db: &Database) -> Result<u64> {
         crate::operations::CrudOperations::batch_upsert(models, db).await
     }
 
@@ -176,7 +870,7 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         models: &[Self],
         db: &Database,
         table_name: &str,
-    ) -> Result<()> {
+    ) -> Result<u64> {
         crate::operations::CrudOperations::batch_upsert_with_table(models, db, table_name).await
     }
 
@@ -326,6 +1020,20 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         crate::operations::CrudOperations::find_by_ids_with_table::<Self>(ids, db, table_name).await
     }
 
+    /// Primary keys of the records matching `filter` (all records for `None`), reading
+    /// only the key column
+    async fn find_ids(filter: Option<FilterOperator>, db: &Database) -> Result<Vec<String>> {
+        crate::operations::CrudOperations::find_ids::<Self>(filter, db).await
+    }
+
+    async fn find_ids_with_table(
+        filter: Option<FilterOperator>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        crate::operations::CrudOperations::find_ids_with_table::<Self>(filter, db, table_name).await
+    }
+
     async fn find_by_field_in(
         field: &str,
         values: &[crate::Value],
@@ -437,6 +1145,21 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
             .await
     }
 
+    async fn delete_where_returning(filter: FilterOperator, db: &Database) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::delete_where_returning::<Self>(filter, db).await
+    }
+
+    async fn delete_where_returning_with_table(
+        filter: FilterOperator,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<Vec<Self>> {
+        crate::operations::CrudOperations::delete_where_returning_with_table::<Self>(
+            filter, db, table_name,
+        )
+        .await
+    }
+
     // List operations with sorting
     async fn list(
         sort: Option<Vec<crate::Sort>>,
@@ -465,6 +1188,32 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         crate::operations::CrudOperations::list_where::<Self>(filter, sort, pagination, db).await
     }
 
+    /// A page of records matching `filter` and the total match count, in one query
+    async fn find_where_with_count(
+        filter: FilterOperator,
+        sort: Option<Vec<crate::Sort>>,
+        pagination: Option<&crate::Pagination>,
+        db: &Database,
+    ) -> Result<(Vec<Self>, u64)> {
+        crate::operations::CrudOperations::find_where_with_count::<Self>(
+            filter, sort, pagination, db,
+        )
+        .await
+    }
+
+    async fn find_where_with_count_and_table(
+        filter: FilterOperator,
+        sort: Option<Vec<crate::Sort>>,
+        pagination: Option<&crate::Pagination>,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<(Vec<Self>, u64)> {
+        crate::operations::CrudOperations::find_where_with_count_and_table::<Self>(
+            filter, sort, pagination, db, table_name,
+        )
+        .await
+    }
+
     async fn list_where_with_table(
         filter: FilterOperator,
         sort: Option<Vec<crate::Sort>>,
@@ -529,6 +1278,57 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
         .await
     }
 
+    /// Row count per distinct value of `column`
+    async fn count_by(column: &str, db: &Database) -> Result<HashMap<crate::Value, i64>> {
+        crate::operations::CrudOperations::count_by::<Self>(column, db).await
+    }
+
+    async fn count_by_with_table(
+        column: &str,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<crate::Value, i64>> {
+        crate::operations::CrudOperations::count_by_with_table::<Self>(column, db, table_name).await
+    }
+
+    /// Row count per distinct value of `column` for values with more than `min_count` rows
+    async fn count_by_having(
+        column: &str,
+        min_count: i64,
+        db: &Database,
+    ) -> Result<HashMap<crate::Value, i64>> {
+        crate::operations::CrudOperations::count_by_having::<Self>(column, min_count, db).await
+    }
+
+    async fn count_by_having_with_table(
+        column: &str,
+        min_count: i64,
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<crate::Value, i64>> {
+        crate::operations::CrudOperations::count_by_having_with_table::<Self>(
+            column, min_count, db, table_name,
+        )
+        .await
+    }
+
+    /// Row counts for several labeled filters in one query, keyed by label
+    async fn count_multi(
+        filters: &[(&str, FilterOperator)],
+        db: &Database,
+    ) -> Result<HashMap<String, i64>> {
+        crate::operations::CrudOperations::count_multi::<Self>(filters, db).await
+    }
+
+    async fn count_multi_with_table(
+        filters: &[(&str, FilterOperator)],
+        db: &Database,
+        table_name: &str,
+    ) -> Result<HashMap<String, i64>> {
+        crate::operations::CrudOperations::count_multi_with_table::<Self>(filters, db, table_name)
+            .await
+    }
+
     // Legacy batch operations (for compatibility)
     async fn batch_insert(records: &[Self], db: &Database) -> Result<u64> {
         Self::batch_create(records, db).await?;
@@ -536,6 +1336,11 @@ pub trait Orso: Serialize + DeserializeOwned + Send + Sync + Clone {
     }
 
     // Filter operations
+    /// Subquery selecting this model's primary keys, for use with `Filter::new_in_subquery`
+    fn subquery_ids(filter: FilterOperator) -> crate::Subquery {
+        crate::Subquery::new(Self::table_name(), Self::primary_key_field(), Some(filter))
+    }
+
     fn build_filter_operator(filter: &FilterOperator) -> Result<(String, Vec<libsql::Value>)> {
         crate::filters::FilterOperations::build_filter_operator(filter)
     }
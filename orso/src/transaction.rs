@@ -0,0 +1,89 @@
+// Explicit transactions
+use crate::{Database, Error, Orso, Result};
+
+/// How `BEGIN` acquires locks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionMode {
+    /// Locks are taken by the first read/write (`BEGIN DEFERRED`)
+    Deferred,
+    /// Takes the write lock up front, so other writers wait (`BEGIN IMMEDIATE`)
+    Immediate,
+    /// Like `Immediate`, and also keeps out readers in rollback-journal mode
+    Exclusive,
+}
+
+impl From<TransactionMode> for libsql::TransactionBehavior {
+    fn from(mode: TransactionMode) -> Self {
+        match mode {
+            TransactionMode::Deferred => libsql::TransactionBehavior::Deferred,
+            TransactionMode::Immediate => libsql::TransactionBehavior::Immediate,
+            TransactionMode::Exclusive => libsql::TransactionBehavior::Exclusive,
+        }
+    }
+}
+
+/// A transaction on the database's connection, opened with `Database::transaction` or
+/// `Database::transaction_with_mode`.
+///
+/// Statements run through `db()` while the transaction is open are part of it. Other
+/// statements on the `Database` it was opened from wait until it ends, so they are never
+/// committed or rolled back with it. Dropping it without `commit` rolls it back.
+pub struct Transaction<'a> {
+    inner: libsql::Transaction,
+    scope: Database,
+    mode: TransactionMode,
+    // Released last, once the transaction has ended
+    _exclusive: tokio::sync::RwLockWriteGuard<'a, ()>,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(
+        exclusive: tokio::sync::RwLockWriteGuard<'a, ()>,
+        scope: Database,
+        inner: libsql::Transaction,
+        mode: TransactionMode,
+    ) -> Self {
+        Self {
+            inner,
+            scope,
+            mode,
+            _exclusive: exclusive,
+        }
+    }
+
+    /// The database to run operations against inside the transaction
+    pub fn db(&self) -> &Database {
+        &self.scope
+    }
+
+    /// The mode the transaction was opened with
+    pub fn mode(&self) -> TransactionMode {
+        self.mode
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        self.inner.commit().await?;
+        Ok(())
+    }
+
+    pub async fn rollback(self) -> Result<()> {
+        self.inner.rollback().await?;
+        Ok(())
+    }
+
+    /// Load a record for a read-modify-write. SQLite has no `SELECT ... FOR UPDATE`;
+    /// instead the transaction must have been opened `Immediate` (or `Exclusive`), so it
+    /// already holds the write lock and other writers wait until it finishes.
+    pub async fn find_by_id_for_update<T: Orso>(&self, id: impl Into<T::Key>) -> Result<Option<T>> {
+        if !matches!(
+            self.mode,
+            TransactionMode::Immediate | TransactionMode::Exclusive
+        ) {
+            return Err(Error::Validation(format!(
+                "find_by_id_for_update needs an Immediate or Exclusive transaction, not {:?}",
+                self.mode
+            )));
+        }
+        T::find_by_id(id, &self.scope).await
+    }
+}
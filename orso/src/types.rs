@@ -1,6 +1,6 @@
 use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Value {
     Null,
     Integer(i64),
@@ -10,6 +10,38 @@ pub enum Value {
     Boolean(bool),
 }
 
+// Reals compare by bit pattern so that Value can be Eq + Hash (e.g. as a map key):
+// NaN equals itself and 0.0 differs from -0.0
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Real(a), Value::Real(b)) => a.to_bits() == b.to_bits(),
+            (Value::Text(a), Value::Text(b)) => a == b,
+            (Value::Blob(a), Value::Blob(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl std::hash::Hash for Value {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => {}
+            Value::Integer(i) => i.hash(state),
+            Value::Real(f) => f.to_bits().hash(state),
+            Value::Text(s) => s.hash(state),
+            Value::Blob(b) => b.hash(state),
+            Value::Boolean(b) => b.hash(state),
+        }
+    }
+}
+
 impl From<i64> for Value {
     fn from(v: i64) -> Self {
         Value::Integer(v)
@@ -113,6 +145,116 @@ impl From<serde_json::Value> for Value {
     }
 }
 
+/// Conversion from a column `Value` into a Rust type, used by [`ValueMapExt::get_as`]
+pub trait FromValue: Sized {
+    /// Convert the value, or describe why it doesn't fit
+    fn from_value(value: &Value) -> std::result::Result<Self, String>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Integer(i) => Ok(*i),
+            Value::Boolean(b) => Ok(*b as i64),
+            _ => Err("expected an integer".to_string()),
+        }
+    }
+}
+
+macro_rules! from_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: &Value) -> std::result::Result<Self, String> {
+                    let i = i64::from_value(value)?;
+                    <$t>::try_from(i)
+                        .map_err(|_| format!("{} is out of range for {}", i, stringify!($t)))
+                }
+            }
+        )*
+    };
+}
+
+from_value_int!(i32, u32, u64, usize);
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Real(f) => Ok(*f),
+            Value::Integer(i) => Ok(*i as f64),
+            _ => Err("expected a number".to_string()),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        f64::from_value(value).map(|f| f as f32)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Boolean(b) => Ok(*b),
+            Value::Integer(i) => Ok(*i != 0),
+            _ => Err("expected a boolean".to_string()),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Text(s) => Ok(s.clone()),
+            _ => Err("expected text".to_string()),
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Blob(b) => Ok(b.clone()),
+            _ => Err("expected a blob".to_string()),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+/// Typed access to a row map, e.g. one returned by `QueryBuilder::execute_maps`
+pub trait ValueMapExt {
+    /// Read `key` as `T`; errors if the column is missing or its value doesn't fit `T`
+    fn get_as<T: FromValue>(&self, key: &str) -> crate::Result<T>;
+}
+
+impl ValueMapExt for std::collections::HashMap<String, Value> {
+    fn get_as<T: FromValue>(&self, key: &str) -> crate::Result<T> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| crate::Error::NotFound(format!("Column `{key}` is not in the row")))?;
+        T::from_value(value).map_err(|message| crate::Error::FieldDeserialization {
+            field: key.to_string(),
+            value: value.clone(),
+            message,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 pub enum SortOrder {
     #[default]
@@ -129,6 +271,29 @@ impl std::fmt::Display for SortOrder {
     }
 }
 
+/// What an insert does when it collides with an existing row
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Return the constraint error (plain `INSERT`)
+    #[default]
+    Fail,
+    /// Keep the existing row and skip the insert
+    Ignore,
+    /// Delete the existing row and insert the new one
+    Replace,
+    /// Update the existing row's non-unique columns (upsert on the unique columns)
+    Update,
+}
+
+/// Stream compression for exports. Imports detect it from the leading bytes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum ExportCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Aggregate {
     Count,
@@ -178,6 +343,8 @@ pub enum Operator {
     Gt,
     Ge,
     Like,
+    /// LIKE with `\` as the escape character (`LIKE ? ESCAPE '\'`)
+    LikeEscaped,
     NotLike,
     In,
     NotIn,
@@ -196,7 +363,7 @@ impl std::fmt::Display for Operator {
             Operator::Le => write!(f, "<="),
             Operator::Gt => write!(f, ">"),
             Operator::Ge => write!(f, ">="),
-            Operator::Like => write!(f, "LIKE"),
+            Operator::Like | Operator::LikeEscaped => write!(f, "LIKE"),
             Operator::NotLike => write!(f, "NOT LIKE"),
             Operator::In => write!(f, "IN"),
             Operator::NotIn => write!(f, "NOT IN"),
@@ -208,6 +375,169 @@ impl std::fmt::Display for Operator {
     }
 }
 
+/// Row count and approximate storage size of a table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStats {
+    pub rows: u64,
+    /// Sum of `length()` over every column and row; a payload estimate that ignores page
+    /// and index overhead
+    pub bytes: u64,
+}
+
+/// Element type of a compressed column value, as found by `Utils::inspect_blob`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BlobElementType {
+    I64,
+    U64,
+    F64,
+    F32,
+}
+
+/// What a compressed (`ORSO`-prefixed) column value decodes to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlobInfo {
+    /// First element type whose decoder accepted the blob
+    pub element_type: BlobElementType,
+    /// Number of decoded elements
+    pub len: usize,
+    /// Size of the blob in bytes
+    pub bytes: usize,
+    /// Up to `preview` elements from the start and from the end
+    pub head: Vec<Value>,
+    pub tail: Vec<Value>,
+}
+
+/// Storage used by one compressed column, from `Orso::compression_report`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColumnCompressionReport {
+    pub column: String,
+    /// Rows holding a non-NULL value
+    pub rows: u64,
+    /// Elements stored across those rows, read from the value headers; for a
+    /// `compress_text` column, the UTF-8 length of the text
+    pub elements: u64,
+    /// Bytes the stored values occupy, as summed by SQL `length()`
+    pub compressed_bytes: u64,
+    /// `elements` × 8 (the values as plain 64-bit numbers), or `elements` for a
+    /// `compress_text` column
+    pub estimated_uncompressed_bytes: u64,
+}
+
+impl ColumnCompressionReport {
+    /// Estimated uncompressed size over stored size; 0.0 for a column with no bytes stored
+    pub fn ratio(&self) -> f64 {
+        if self.compressed_bytes == 0 {
+            return 0.0;
+        }
+        self.estimated_uncompressed_bytes as f64 / self.compressed_bytes as f64
+    }
+}
+
+/// Element type of a compressed `Vec` field, encoded with the same codec the derive uses,
+/// so blobs built offline can be inserted with `insert_raw` and read back as the field
+pub trait CompressedElement: Sized {
+    /// Stored with the float codec (`FieldType::Numeric` columns)
+    const FLOAT: bool;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>>;
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>>;
+}
+
+impl CompressedElement for i64 {
+    const FLOAT: bool = false;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(crate::IntegerCodec::default().compress_many_i64(arrays)?)
+    }
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>> {
+        Ok(crate::IntegerCodec::default().decompress_many_i64(blobs)?)
+    }
+}
+
+impl CompressedElement for u64 {
+    const FLOAT: bool = false;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(crate::IntegerCodec::default().compress_many_u64(arrays)?)
+    }
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>> {
+        Ok(crate::IntegerCodec::default().decompress_many_u64(blobs)?)
+    }
+}
+
+// `i32`/`u32` fields are widened to the 64-bit codec, as in `to_map`
+impl CompressedElement for i32 {
+    const FLOAT: bool = false;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>> {
+        let wide: Vec<Vec<i64>> = arrays
+            .iter()
+            .map(|array| array.iter().map(|&v| v as i64).collect())
+            .collect();
+        i64::compress_many(&wide)
+    }
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>> {
+        Ok(i64::decompress_many(blobs)?
+            .into_iter()
+            .map(|array| array.into_iter().map(|v| v as i32).collect())
+            .collect())
+    }
+}
+
+impl CompressedElement for u32 {
+    const FLOAT: bool = false;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>> {
+        let wide: Vec<Vec<u64>> = arrays
+            .iter()
+            .map(|array| array.iter().map(|&v| v as u64).collect())
+            .collect();
+        u64::compress_many(&wide)
+    }
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>> {
+        Ok(u64::decompress_many(blobs)?
+            .into_iter()
+            .map(|array| array.into_iter().map(|v| v as u32).collect())
+            .collect())
+    }
+}
+
+impl CompressedElement for f64 {
+    const FLOAT: bool = true;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(crate::FloatingCodec::default().compress_many_f64(arrays, None)?)
+    }
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>> {
+        Ok(crate::FloatingCodec::default().decompress_many_f64(blobs, None)?)
+    }
+}
+
+impl CompressedElement for f32 {
+    const FLOAT: bool = true;
+
+    fn compress_many(arrays: &[Vec<Self>]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(crate::FloatingCodec::default().compress_many_f32(arrays, None)?)
+    }
+    fn decompress_many(blobs: &[Vec<u8>]) -> crate::Result<Vec<Vec<Self>>> {
+        Ok(crate::FloatingCodec::default().decompress_many_f32(blobs, None)?)
+    }
+}
+
+/// Metadata supplied with a write, used to stamp `created_by` / `updated_by` columns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WriteContext {
+    pub user_id: Option<String>,
+}
+
+impl WriteContext {
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: Some(user_id.into()),
+        }
+    }
+}
+
 pub fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: Deserializer<'de>,
@@ -7,6 +7,16 @@ use uuid::Uuid;
 #[derive(Debug, Clone)]
 pub struct Utils;
 
+// Header of a compress_text blob; distinct from the numeric codecs' `ORSO` magic
+const TEXT_MAGIC: &[u8] = b"ORST";
+const TEXT_CODEC_LZ4: u8 = 1;
+// Most an LZ4 block can expand: each extra byte of a match length adds up to 255 bytes
+const LZ4_MAX_RATIO: u64 = 255;
+// Magic, codec byte and UTF-8 length
+pub(crate) const TEXT_HEADER_LEN: usize = 13;
+// Magic, version, codec and element type bytes, then the element count
+pub(crate) const ORSO_HEADER_LEN: usize = 15;
+
 impl Utils {
     pub fn generate_id() -> Option<String> {
         Some(Uuid::new_v4().to_string())
@@ -43,6 +53,168 @@ impl Utils {
     pub fn unix_timestamp_millis_to_datetime(timestamp: i64) -> DateTime<Utc> {
         DateTime::from_timestamp_millis(timestamp).unwrap_or_else(|| Utc::now())
     }
+
+    /// 16-byte form of a UUID given as text, as stored in a `uuid_blob` key column
+    pub fn uuid_text_to_blob(text: &str) -> crate::Result<Vec<u8>> {
+        Uuid::parse_str(text)
+            .map(|uuid| uuid.as_bytes().to_vec())
+            .map_err(|e| crate::Error::Validation(format!("Invalid UUID '{text}': {e}")))
+    }
+
+    /// Hyphenated text form of a 16-byte UUID blob, or None if it isn't 16 bytes
+    pub fn uuid_blob_to_text(bytes: &[u8]) -> Option<String> {
+        Uuid::from_slice(bytes)
+            .ok()
+            .map(|uuid| uuid.hyphenated().to_string())
+    }
+
+    /// Ensure `field` is a compressed column of `T` whose codec matches the element type `E`
+    pub(crate) fn check_compressed_field<T, E>(field: &str) -> crate::Result<()>
+    where
+        T: crate::Orso,
+        E: crate::CompressedElement,
+    {
+        let position = T::field_names()
+            .iter()
+            .position(|name| *name == field)
+            .ok_or_else(|| {
+                crate::Error::Validation(format!(
+                    "Unknown column '{field}' for table {}",
+                    T::table_name()
+                ))
+            })?;
+        if T::field_compressed().get(position) != Some(&true) {
+            return Err(crate::Error::Validation(format!(
+                "Column '{field}' is not a compressed BLOB column"
+            )));
+        }
+        let float = matches!(
+            T::field_types().get(position),
+            Some(crate::FieldType::Numeric)
+        );
+        if float != E::FLOAT {
+            let codec = if float { "float" } else { "integer" };
+            return Err(crate::Error::Validation(format!(
+                "Column '{field}' is compressed with the {codec} codec"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Decode a compressed column value for debugging: the element type, element count and
+    /// the first and last `preview` values. Fails if the bytes lack the `ORSO` magic or no
+    /// decoder accepts them.
+    pub fn inspect_blob(blob: &[u8], preview: usize) -> crate::Result<crate::BlobInfo> {
+        use crate::{BlobElementType, Value};
+
+        if blob.len() < 4 || &blob[0..4] != b"ORSO" {
+            return Err(crate::Error::Validation(
+                "Not an ORSO-compressed blob (missing magic)".to_string(),
+            ));
+        }
+
+        let integers = crate::IntegerCodec::default();
+        let floats = crate::FloatingCodec::default();
+        let (element_type, values): (BlobElementType, Vec<Value>) =
+            if let Ok(values) = integers.decompress_i64(blob) {
+                let values = values.into_iter().map(Value::Integer);
+                (BlobElementType::I64, values.collect())
+            } else if let Ok(values) = integers.decompress_u64(blob) {
+                let values = values.into_iter().map(|v| Value::Integer(v as i64));
+                (BlobElementType::U64, values.collect())
+            } else if let Ok(values) = floats.decompress_f64(blob, None) {
+                let values = values.into_iter().map(Value::Real);
+                (BlobElementType::F64, values.collect())
+            } else if let Ok(values) = floats.decompress_f32(blob, None) {
+                let values = values.into_iter().map(|v| Value::Real(v as f64));
+                (BlobElementType::F32, values.collect())
+            } else {
+                return Err(crate::Error::Validation(format!(
+                    "ORSO blob of {} bytes could not be decoded as i64, u64, f64 or f32",
+                    blob.len()
+                )));
+            };
+
+        let head_len = preview.min(values.len());
+        let tail_len = preview.min(values.len() - head_len);
+        Ok(crate::BlobInfo {
+            element_type,
+            len: values.len(),
+            bytes: blob.len(),
+            head: values[..head_len].to_vec(),
+            tail: values[values.len() - tail_len..].to_vec(),
+        })
+    }
+
+    /// Encode a `compress_text` column: the `ORST` magic, a codec byte (1 = LZ4 block), the
+    /// UTF-8 length as a u64 LE and the compressed bytes
+    pub fn compress_text(text: &str) -> crate::Result<Vec<u8>> {
+        let compressed = lz4_flex::block::compress(text.as_bytes());
+        let mut blob = Vec::with_capacity(TEXT_HEADER_LEN + compressed.len());
+        blob.extend_from_slice(TEXT_MAGIC);
+        blob.push(TEXT_CODEC_LZ4);
+        blob.extend_from_slice(&(text.len() as u64).to_le_bytes());
+        blob.extend_from_slice(&compressed);
+        Ok(blob)
+    }
+
+    /// Decode a blob written by `compress_text`. The header's length is checked against
+    /// what the payload could possibly decode to before anything is allocated, so a
+    /// corrupt header can't force a huge decompression.
+    pub fn decompress_text(blob: &[u8]) -> crate::Result<String> {
+        let len = Self::compressed_text_len(blob).ok_or_else(|| {
+            crate::Error::Serialization("Not an ORSO text blob (missing header)".to_string())
+        })?;
+        if blob[TEXT_MAGIC.len()] != TEXT_CODEC_LZ4 {
+            return Err(crate::Error::Serialization(format!(
+                "Unknown text codec {}",
+                blob[TEXT_MAGIC.len()]
+            )));
+        }
+        let payload = &blob[TEXT_HEADER_LEN..];
+        if len > (payload.len() as u64).saturating_mul(LZ4_MAX_RATIO) {
+            return Err(crate::Error::Serialization(format!(
+                "Compressed text header says {len} bytes, more than {} payload bytes can hold",
+                payload.len()
+            )));
+        }
+        let bytes = lz4_flex::block::decompress(payload, len as usize)
+            .map_err(|e| crate::Error::Serialization(format!("Failed to decompress text: {e}")))?;
+        if bytes.len() as u64 != len {
+            return Err(crate::Error::Serialization(format!(
+                "Compressed text decoded to {} bytes, header says {len}",
+                bytes.len()
+            )));
+        }
+        String::from_utf8(bytes)
+            .map_err(|e| crate::Error::Serialization(format!("Compressed text is not UTF-8: {e}")))
+    }
+
+    /// UTF-8 length recorded in the header of a `compress_text` blob; `blob` may be just
+    /// its first `TEXT_HEADER_LEN` bytes
+    pub(crate) fn compressed_text_len(blob: &[u8]) -> Option<u64> {
+        let header = blob.strip_prefix(TEXT_MAGIC)?.get(1..9)?;
+        Some(u64::from_le_bytes(header.try_into().ok()?))
+    }
+
+    /// Element count recorded in the header of an `ORSO` blob (magic, version, codec and
+    /// element type bytes, then the count as a u64 LE), read without decoding the payload;
+    /// `blob` may be just its first `ORSO_HEADER_LEN` bytes
+    pub(crate) fn compressed_element_count(blob: &[u8]) -> Option<u64> {
+        let header = blob.strip_prefix(b"ORSO")?.get(3..11)?;
+        Some(u64::from_le_bytes(header.try_into().ok()?))
+    }
+
+    /// Stable non-negative fingerprint of a schema definition (64-bit FNV-1a with the
+    /// top bit cleared), the same across builds and platforms
+    pub fn schema_fingerprint(schema: &str) -> i64 {
+        let hash = schema
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        (hash >> 1) as i64
+    }
 }
 impl Utils {
     pub fn value_to_libsql_value(value: &crate::Value) -> libsql::Value {
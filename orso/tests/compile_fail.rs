@@ -0,0 +1,6 @@
+// Derive misuse that must be rejected at compile time
+#[test]
+fn derive_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
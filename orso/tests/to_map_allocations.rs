@@ -0,0 +1,101 @@
+// Allocation comparison for the write-path column maps (timings are in
+// benches/batch_insert.rs). Lives in its own test binary because it installs a counting
+// global allocator.
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    // Per thread, so tests running in parallel don't count each other's allocations
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("wide_rows")]
+struct WideRow {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    symbol: String,
+    venue: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: i64,
+    trades: i64,
+    vwap: f64,
+    bid: f64,
+    ask: f64,
+    spread: f64,
+    halted: bool,
+    #[orso_column(created_at)]
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[orso_column(updated_at)]
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn row(i: i64) -> WideRow {
+    WideRow {
+        symbol: format!("SYM{i}"),
+        venue: "XNAS".to_string(),
+        open: i as f64,
+        high: i as f64 + 1.0,
+        low: i as f64 - 1.0,
+        close: i as f64 + 0.5,
+        volume: i * 100,
+        trades: i,
+        vwap: i as f64 + 0.25,
+        bid: i as f64,
+        ask: i as f64 + 0.1,
+        spread: 0.1,
+        ..Default::default()
+    }
+}
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = allocations();
+    f();
+    allocations() - before
+}
+
+#[test]
+fn to_map_cow_skips_per_column_key_allocations() {
+    let record = row(7);
+    let columns = record.to_map().unwrap().len();
+
+    let owned = allocations_during(|| drop(record.to_map().unwrap()));
+    let borrowed = allocations_during(|| drop(record.to_map_cow().unwrap()));
+
+    // One owned `String` key per column, plus the second map `to_map` builds
+    assert!(borrowed + columns <= owned, "{borrowed} vs {owned}");
+    assert_eq!(
+        record
+            .to_map_cow()
+            .unwrap()
+            .into_iter()
+            .map(|(k, v)| (k.into_owned(), v))
+            .collect::<std::collections::HashMap<_, _>>(),
+        record.to_map().unwrap()
+    );
+}
@@ -0,0 +1,13 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("flags")]
+struct Flag {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    #[orso_column(check_bool)]
+    active: i64,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("documents")]
+struct Document {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    #[orso_column(compress_text)]
+    body: Option<String>,
+}
+
+fn main() {}
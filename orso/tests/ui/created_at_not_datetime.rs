@@ -0,0 +1,13 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("events")]
+struct Event {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    #[orso_column(created_at)]
+    created_at: String,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("counters")]
+struct Counter {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    #[orso_column(default = 0)]
+    hits: i32,
+}
+
+fn main() {}
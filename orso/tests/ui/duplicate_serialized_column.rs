@@ -0,0 +1,14 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("quotes")]
+struct Quote {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    price: f64,
+    #[serde(rename(serialize = "price"))]
+    last_price: f64,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("scores")]
+struct Score {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    #[orso_column(not_null)]
+    points: Option<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("posts", order_by = "body")]
+struct Post {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    #[orso_column(compress_text)]
+    body: String,
+}
+
+fn main() {}
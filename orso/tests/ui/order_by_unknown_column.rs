@@ -0,0 +1,12 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table("posts", order_by = "published desc")]
+struct Post {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+    title: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use orso::Orso;
+use serde::{Deserialize, Serialize};
+
+#[derive(Orso, Serialize, Deserialize, Clone, Debug, Default)]
+#[orso_table(naming = "kebab_case")]
+struct Event {
+    #[orso_column(primary_key)]
+    id: Option<String>,
+}
+
+fn main() {}